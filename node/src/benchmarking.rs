@@ -23,13 +23,17 @@
 use crate::service::FullClient;
 
 use node_template_runtime as runtime;
-use runtime::{AccountId, Balance, BalancesCall, SystemCall};
+use pallet_nft::{Class, Id, Nft};
+use runtime::{
+	AccountId, Balance, BalancesCall, GenesisConfig, NftCall, NftMarketPlaceCall, SystemCall,
+	EXISTENTIAL_DEPOSIT,
+};
 use sc_cli::Result;
 use sc_client_api::BlockBackend;
 use sp_core::{Encode, Pair};
 use sp_inherents::{InherentData, InherentDataProvider};
 use sp_keyring::Sr25519Keyring;
-use sp_runtime::{OpaqueExtrinsic, SaturatedConversion};
+use sp_runtime::{OpaqueExtrinsic, Permill, SaturatedConversion};
 
 use std::{sync::Arc, time::Duration};
 
@@ -113,6 +117,219 @@ impl frame_benchmarking_cli::ExtrinsicBuilder for TransferKeepAliveBuilder {
 	}
 }
 
+/// Class the `mint` and `transfer` benchmarks work on, owned by Charlie.
+const TRANSFER_CLASS: Id = 1;
+/// First of the classes holding the listings bought by the `buy_offer` benchmark.
+const FIRST_LISTING_CLASS: Id = 2;
+/// Number of classes with listings.
+const LISTING_CLASSES: u64 = 4;
+const LISTINGS_PER_CLASS: u64 = 500;
+/// Tokens of Bob in [`TRANSFER_CLASS`], one is transferred per extrinsic.
+const TRANSFER_NFTS: u64 = 5_000;
+/// First token id of the benchmark collections, the demo collection comes before.
+const FIRST_NFT: Id = 1_000;
+const FIRST_LISTED_NFT: Id = FIRST_NFT + TRANSFER_NFTS;
+const LISTING_PRICE: Balance = 1_000 * EXISTENTIAL_DEPOSIT;
+
+/// Add the tokens and listings the NFT extrinsic builders rely on to `genesis`.
+///
+/// Bob owns [`TRANSFER_NFTS`] tokens of [`TRANSFER_CLASS`] and Alice lists
+/// [`LISTINGS_PER_CLASS`] tokens in each of the [`LISTING_CLASSES`] listing classes. The classes
+/// belong to Charlie and ask for a royalty, so that a sale pays the seller, the class owner and
+/// the marketplace fee like on a live chain.
+pub fn seed_nft_genesis(genesis: &mut GenesisConfig) {
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	let bob = Sr25519Keyring::Bob.to_account_id();
+	let charlie = Sr25519Keyring::Charlie.to_account_id();
+
+	let nft_module = &mut genesis.nft_module;
+	let market = &mut genesis.nft_market_place_module;
+	let royalty = Permill::from_percent(5);
+	market.parameters.royalty_cap = market.parameters.royalty_cap.max(royalty);
+	for class_id in TRANSFER_CLASS..FIRST_LISTING_CLASS + LISTING_CLASSES {
+		nft_module.classes.push((class_id, benchmark_class(charlie.clone())));
+		market.royalties.push((class_id, royalty));
+	}
+	for index in 0..TRANSFER_NFTS {
+		let nft_id = FIRST_NFT + index;
+		let nft = benchmark_nft(nft_id, TRANSFER_CLASS, bob.clone());
+		nft_module.nfts.push((nft_id, nft));
+	}
+	for index in 0..LISTING_CLASSES * LISTINGS_PER_CLASS {
+		let (class_id, nft_id) = listing(index as u32);
+		nft_module.nfts.push((nft_id, benchmark_nft(nft_id, class_id, alice.clone())));
+		market.listings.push((class_id, nft_id, LISTING_PRICE, None));
+	}
+}
+
+fn benchmark_class(owner: AccountId) -> Class<AccountId, Vec<u8>> {
+	Class {
+		owner,
+		description: b"Tokens used to benchmark the NFT extrinsics".to_vec(),
+		name: b"Benchmark".to_vec(),
+		url: b"https://example.com/benchmark".to_vec(),
+		external_url: b"https://example.com".to_vec(),
+	}
+}
+
+fn benchmark_nft(nft_id: Id, class_id: Id, owner: AccountId) -> Nft<AccountId, Vec<u8>> {
+	Nft {
+		owner,
+		description: format!("Benchmark token #{}", nft_id).into_bytes(),
+		name: format!("Benchmark #{}", nft_id).into_bytes(),
+		url: format!("https://example.com/benchmark/{}", nft_id).into_bytes(),
+		external_url: b"https://example.com".to_vec(),
+		class_id,
+		metadata_hash: None,
+		media_hash: None,
+	}
+}
+
+/// Class and id of the listed token bought by the extrinsic with the given nonce.
+fn listing(nonce: u32) -> (Id, Id) {
+	let index = nonce as u64;
+	(FIRST_LISTING_CLASS + index / LISTINGS_PER_CLASS, FIRST_LISTED_NFT + index)
+}
+
+/// Generates `NftModule::mint` extrinsics for the benchmarks.
+///
+/// Note: Should only be used for benchmarking.
+pub struct MintBuilder {
+	client: Arc<FullClient>,
+}
+
+impl MintBuilder {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		Self { client }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for MintBuilder {
+	fn pallet(&self) -> &str {
+		"nft"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"mint"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let acc = Sr25519Keyring::Bob.pair();
+		let nft = benchmark_nft(nonce as u64, TRANSFER_CLASS, acc.public().into());
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			NftCall::mint {
+				class_id: TRANSFER_CLASS,
+				name: nft.name,
+				description: nft.description,
+				url: nft.url,
+				external_url: nft.external_url,
+				metadata_hash: nft.metadata_hash,
+				media_hash: nft.media_hash,
+			}
+			.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Generates `NftModule::transfer` extrinsics for the benchmarks.
+///
+/// Every extrinsic gives another token of Bob to Alice, the chain must be started from the
+/// `benchmark` chain spec which holds the tokens.
+///
+/// Note: Should only be used for benchmarking.
+pub struct NftTransferBuilder {
+	client: Arc<FullClient>,
+}
+
+impl NftTransferBuilder {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		Self { client }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for NftTransferBuilder {
+	fn pallet(&self) -> &str {
+		"nft"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"transfer"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		if nonce as u64 >= TRANSFER_NFTS {
+			return Err("Bob has no token left to transfer in the benchmark genesis")
+		}
+		let acc = Sr25519Keyring::Bob.pair();
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			NftCall::transfer {
+				to: Sr25519Keyring::Alice.to_account_id(),
+				nft_id: FIRST_NFT + nonce as u64,
+				class_id: TRANSFER_CLASS,
+			}
+			.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Generates `NftMarketPlaceModule::buy_offer` extrinsics for the benchmarks.
+///
+/// Every extrinsic buys another listing of Alice, the chain must be started from the `benchmark`
+/// chain spec which holds the listings.
+///
+/// Note: Should only be used for benchmarking.
+pub struct BuyOfferBuilder {
+	client: Arc<FullClient>,
+}
+
+impl BuyOfferBuilder {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		Self { client }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for BuyOfferBuilder {
+	fn pallet(&self) -> &str {
+		"nft_market_place"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"buy_offer"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		if nonce as u64 >= LISTING_CLASSES * LISTINGS_PER_CLASS {
+			return Err("no listing left to buy in the benchmark genesis")
+		}
+		let acc = Sr25519Keyring::Bob.pair();
+		let (class_id, nft_id) = listing(nonce);
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			NftMarketPlaceCall::buy_offer { nft_id, class_id, amount: LISTING_PRICE }.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
 /// Create a transaction using the given `call`.
 ///
 /// Note: Should only be used for benchmarking.
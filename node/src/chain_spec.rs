@@ -1,12 +1,17 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	pallet_nft_market_place::MarketParameters, AccountId, AuraConfig, BalancesConfig,
+	GenesisConfig, GrandpaConfig, NftMarketPlaceModuleConfig, NftModuleConfig, Signature,
+	SudoConfig, SystemConfig, EXISTENTIAL_DEPOSIT, WASM_BINARY,
 };
+use pallet_nft::{Class, Nft};
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_runtime::{
+	traits::{IdentifyAccount, Verify},
+	Permill,
+};
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -76,6 +81,49 @@ pub fn development_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// Development chain with the tokens and listings needed by the NFT extrinsic builders of
+/// `benchmark extrinsic`, see [`crate::benchmarking::seed_nft_genesis`].
+pub fn benchmark_config() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Benchmark",
+		// ID
+		"benchmark",
+		ChainType::Development,
+		move || {
+			let mut genesis = testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice")],
+				// Sudo account
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				],
+				true,
+			);
+			crate::benchmarking::seed_nft_genesis(&mut genesis);
+			genesis
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		None,
+		// Properties
+		None,
+		// Extensions
+		None,
+	))
+}
+
 pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
@@ -132,6 +180,13 @@ fn testnet_genesis(
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
 ) -> GenesisConfig {
+	let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+	let bob = get_account_id_from_seed::<sr25519::Public>("Bob");
+	// Tokens 0 and 1 belong to Alice, 2 and 3 to Bob.
+	let nfts = (0..4)
+		.map(|id| (id, demo_nft(id, if id < 2 { alice.clone() } else { bob.clone() })))
+		.collect();
+
 	GenesisConfig {
 		system: SystemConfig {
 			// Add Wasm runtime to storage.
@@ -152,5 +207,50 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
+		nft_module: NftModuleConfig {
+			classes: vec![(0, demo_class(alice.clone()))],
+			nfts,
+			// Alice's off-chain worker reports on token metadata once her `nftm` key is inserted.
+			metadata_verifiers: vec![alice.clone()],
+		},
+		nft_market_place_module: NftMarketPlaceModuleConfig {
+			parameters: MarketParameters {
+				fee_rate: Permill::from_parts(25_000),
+				royalty_cap: Permill::from_percent(10),
+				min_bid_increment: Permill::from_percent(5),
+				// Bids in the last ten minutes push the end of the auction back.
+				anti_sniping_window: 10 * 60 * 1000,
+				max_auction_duration: 30 * 24 * 60 * 60 * 1000,
+			},
+			royalties: vec![(0, Permill::from_percent(5))],
+			// Bob puts one of his tokens up for sale.
+			listings: vec![(0, 3, 1_000 * EXISTENTIAL_DEPOSIT, None)],
+			auctions: Vec::new(),
+		},
+	}
+}
+
+/// Demo collection shipped with the development and local testnet specs.
+fn demo_class(owner: AccountId) -> Class<AccountId, Vec<u8>> {
+	Class {
+		owner,
+		description: b"A small collection to play with on development chains".to_vec(),
+		name: b"Demo Collection".to_vec(),
+		url: b"https://example.com/demo".to_vec(),
+		external_url: b"https://example.com".to_vec(),
+	}
+}
+
+/// Token `id` of the demo collection.
+fn demo_nft(id: u64, owner: AccountId) -> Nft<AccountId, Vec<u8>> {
+	Nft {
+		owner,
+		description: format!("Demo token #{}", id).into_bytes(),
+		name: format!("Demo #{}", id).into_bytes(),
+		url: format!("https://example.com/demo/{}", id).into_bytes(),
+		external_url: b"https://example.com".to_vec(),
+		class_id: 0,
+		metadata_hash: None,
+		media_hash: None,
 	}
 }
@@ -1,5 +1,7 @@
 use sc_cli::RunCmd;
 
+use crate::sealing::Sealing;
+
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
 	#[command(subcommand)]
@@ -7,6 +9,14 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks on demand instead of running Aura and GRANDPA, for development and tests.
+	#[arg(long, value_enum)]
+	pub sealing: Option<Sealing>,
+
+	/// Milliseconds between two blocks with `--sealing interval`.
+	#[arg(long, default_value_t = 6_000, value_parser = clap::value_parser!(u64).range(1..))]
+	pub sealing_interval: u64,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -50,4 +60,16 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Export NFT and marketplace events of the local database into SQLite.
+	Index(crate::indexer::IndexCmd),
+
+	/// Export the classes, NFTs, listings and auctions of a block to JSON or CSV.
+	ExportNfts(crate::snapshot::ExportNftsCmd),
+
+	/// Turn a JSON NFT snapshot into a chain spec genesis fragment.
+	ImportNfts(crate::snapshot::ImportNftsCmd),
+
+	/// Drive synthetic marketplace traffic through a throwaway dev chain and report throughput.
+	LoadTest(crate::load_test::LoadTestCmd),
 }
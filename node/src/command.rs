@@ -1,5 +1,8 @@
 use crate::{
-	benchmarking::{inherent_benchmark_data, RemarkBuilder, TransferKeepAliveBuilder},
+	benchmarking::{
+		inherent_benchmark_data, BuyOfferBuilder, MintBuilder, NftTransferBuilder, RemarkBuilder,
+		TransferKeepAliveBuilder,
+	},
 	chain_spec,
 	cli::{Cli, Subcommand},
 	service,
@@ -9,6 +12,7 @@ use node_template_runtime::{Block, EXISTENTIAL_DEPOSIT};
 use sc_cli::{ChainSpec, RuntimeVersion, SubstrateCli};
 use sc_service::PartialComponents;
 use sp_keyring::Sr25519Keyring;
+use std::time::Duration;
 
 impl SubstrateCli for Cli {
 	fn impl_name() -> String {
@@ -38,6 +42,7 @@ impl SubstrateCli for Cli {
 	fn load_spec(&self, id: &str) -> Result<Box<dyn sc_service::ChainSpec>, String> {
 		Ok(match id {
 			"dev" => Box::new(chain_spec::development_config()?),
+			"benchmark" => Box::new(chain_spec::benchmark_config()?),
 			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
 			path =>
 				Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
@@ -155,7 +160,8 @@ pub fn run() -> sc_cli::Result<()> {
 					},
 					BenchmarkCmd::Extrinsic(cmd) => {
 						let PartialComponents { client, .. } = service::new_partial(&config)?;
-						// Register the *Remark* and *TKA* builders.
+						// Register the *Remark*, *TKA* and NFT builders, the NFT transfers and
+						// sales need the tokens of `--chain benchmark`.
 						let ext_factory = ExtrinsicFactory(vec![
 							Box::new(RemarkBuilder::new(client.clone())),
 							Box::new(TransferKeepAliveBuilder::new(
@@ -163,6 +169,9 @@ pub fn run() -> sc_cli::Result<()> {
 								Sr25519Keyring::Alice.to_account_id(),
 								EXISTENTIAL_DEPOSIT,
 							)),
+							Box::new(MintBuilder::new(client.clone())),
+							Box::new(NftTransferBuilder::new(client.clone())),
+							Box::new(BuyOfferBuilder::new(client.clone())),
 						]);
 
 						cmd.run(client, inherent_benchmark_data()?, Vec::new(), &ext_factory)
@@ -193,10 +202,34 @@ pub fn run() -> sc_cli::Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run::<Block>(&config))
 		},
+		Some(Subcommand::Index(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, .. } = service::new_partial(&config)?;
+				cmd.run(client, backend)
+			})
+		},
+		Some(Subcommand::ExportNfts(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, .. } = service::new_partial(&config)?;
+				cmd.run(client, backend)
+			})
+		},
+		Some(Subcommand::ImportNfts(cmd)) => cmd.run(),
+		Some(Subcommand::LoadTest(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| cmd.run(config))
+		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let (sealing, interval) = (cli.sealing, Duration::from_millis(cli.sealing_interval));
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				match sealing {
+					Some(sealing) => service::new_dev(config, sealing, interval),
+					None => service::new_full(config),
+				}
+				.map_err(sc_cli::Error::Service)
 			})
 		},
 	}
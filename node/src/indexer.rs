@@ -0,0 +1,377 @@
+//! `index` subcommand: replays the local chain database into an SQLite file.
+//!
+//! Every block from the last indexed one up to the best block is read from the database and
+//! the `pallet_nft` / `pallet_nft_market_place` events in it are written to SQLite, so
+//! questions like "sales history of class X" are a query away. Run it again to carry on from
+//! where the previous run stopped.
+//!
+//! # Schema
+//!
+//! ```sql
+//! -- indexed blocks, `finalized` is 0 or 1
+//! blocks(number INTEGER PRIMARY KEY, hash TEXT, finalized INTEGER)
+//! -- one row per event; `kind` is the `type` of the `market_subscribeActivity` items and
+//! -- `data` the whole item as JSON. `account`, `counterparty` and `amount` depend on the kind:
+//! --   minted, burned          owner
+//! --   transferred             from, to
+//! --   listed                  seller, -, price
+//! --   listingExpired          seller
+//! --   sold                    seller, buyer, price
+//! --   auctionCreated          seller, -, reserve price
+//! --   bid                     bidder, -, amount
+//! --   auctionFinished         winner, -, price
+//! --   offerMade               buyer, -, amount
+//! --   listingCanceled, auctionCanceled have none
+//! events(block_number, event_index, class_id, nft_id, kind, account, counterparty, amount, data)
+//! -- current owner of every token that was not burned
+//! ownership(class_id, nft_id, owner, block_number)
+//! -- fixed price and auction sales, `seller` is NULL for auctions
+//! sales(block_number, event_index, class_id, nft_id, seller, buyer, price)
+//! ```
+//!
+//! Accounts are SS58 strings and amounts decimal strings. Rows of blocks that were not
+//! finalized when they were indexed are reverted and indexed again by the next run, so a reorg
+//! never leaves events of a dropped fork behind.
+
+use std::{path::PathBuf, sync::Arc};
+
+use node_template_runtime::{opaque::Block, AccountId, BlockNumber, Hash};
+use rusqlite::{params, Connection, OptionalExtension};
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_client_api::Backend;
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Ss58Codec;
+
+use crate::rpc::activity::{self, Activity, ActivityEvent, ActivityFilter};
+
+/// Blocks written per SQLite transaction.
+const BATCH: BlockNumber = 1_000;
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS blocks (
+		number INTEGER PRIMARY KEY,
+		hash TEXT NOT NULL,
+		finalized INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS events (
+		block_number INTEGER NOT NULL REFERENCES blocks(number),
+		event_index INTEGER NOT NULL,
+		class_id INTEGER NOT NULL,
+		nft_id INTEGER NOT NULL,
+		kind TEXT NOT NULL,
+		account TEXT,
+		counterparty TEXT,
+		amount TEXT,
+		data TEXT NOT NULL,
+		PRIMARY KEY (block_number, event_index)
+	);
+	CREATE INDEX IF NOT EXISTS events_by_token ON events (class_id, nft_id);
+	CREATE INDEX IF NOT EXISTS events_by_account ON events (account);
+	CREATE INDEX IF NOT EXISTS events_by_counterparty ON events (counterparty);
+	CREATE VIEW IF NOT EXISTS ownership AS
+		SELECT e.class_id, e.nft_id,
+			CASE e.kind WHEN 'transferred' THEN e.counterparty ELSE e.account END AS owner,
+			e.block_number
+		FROM events e
+		WHERE e.kind IN ('minted', 'transferred')
+			AND NOT EXISTS (
+				SELECT 1 FROM events later
+				WHERE later.class_id = e.class_id AND later.nft_id = e.nft_id
+					AND later.kind IN ('minted', 'transferred', 'burned')
+					AND (later.block_number, later.event_index) > (e.block_number, e.event_index)
+			);
+	CREATE VIEW IF NOT EXISTS sales AS
+		SELECT block_number, event_index, class_id, nft_id,
+			CASE kind WHEN 'sold' THEN account END AS seller,
+			CASE kind WHEN 'sold' THEN counterparty ELSE account END AS buyer,
+			amount AS price
+		FROM events
+		WHERE kind IN ('sold', 'auctionFinished');
+";
+
+/// The `index` command used to export marketplace activity into SQLite.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct IndexCmd {
+	/// SQLite file to write to, created when it doesn't exist.
+	#[arg(long, value_name = "PATH", default_value = "nft-index.sqlite")]
+	pub output: PathBuf,
+
+	/// Stop after this block instead of the best block.
+	#[arg(long, value_name = "BLOCK")]
+	pub to: Option<BlockNumber>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl IndexCmd {
+	/// Index the blocks of the local database that are not indexed yet.
+	pub fn run<C, B>(&self, client: Arc<C>, backend: Arc<B>) -> sc_cli::Result<()>
+	where
+		C: HeaderBackend<Block>,
+		B: Backend<Block>,
+	{
+		let mut db = IndexDb::open(Connection::open(&self.output).map_err(db_error)?)?;
+		let info = client.info();
+		let reverted = db.revert_unfinalized()?;
+		let from = match db.last_block()? {
+			Some((number, hash)) => {
+				if client.hash(number)?.map(|known| format!("{:?}", known)) != Some(hash) {
+					return Err(format!(
+						"Block #{} of {} is not in the local database, the index belongs to \
+						another chain.",
+						number,
+						self.output.display(),
+					)
+					.into())
+				}
+				number + 1
+			},
+			None => 0,
+		};
+		let to = self.to.unwrap_or(info.best_number).min(info.best_number);
+		log::info!(
+			"Indexing blocks #{}..=#{} into {} ({} unfinalized blocks reverted)",
+			from,
+			to,
+			self.output.display(),
+			reverted,
+		);
+
+		let filter = ActivityFilter::default();
+		let mut number = from;
+		while number <= to {
+			let last = to.min(number.saturating_add(BATCH - 1));
+			let tx = db.transaction()?;
+			for number in number..=last {
+				let hash = client
+					.hash(number)?
+					.ok_or_else(|| format!("Block #{} is not in the local database", number))?;
+				let events = activity::events(&*backend, hash);
+				let activity = activity::block_activity(&events, hash, number, &filter);
+				insert_block(&tx, number, hash, number <= info.finalized_number, &activity)?;
+			}
+			tx.commit().map_err(db_error)?;
+			log::info!("Indexed up to #{}", last);
+			number = last + 1;
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for IndexCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+/// The SQLite file of the indexer.
+pub struct IndexDb {
+	conn: Connection,
+}
+
+impl IndexDb {
+	/// Creates the schema when it is missing.
+	pub fn open(conn: Connection) -> sc_cli::Result<Self> {
+		conn.execute_batch(SCHEMA).map_err(db_error)?;
+		Ok(Self { conn })
+	}
+
+	/// Number and hash (`0x` hex) of the last indexed block.
+	pub fn last_block(&self) -> sc_cli::Result<Option<(BlockNumber, String)>> {
+		self.conn
+			.query_row("SELECT number, hash FROM blocks ORDER BY number DESC LIMIT 1", [], |row| {
+				Ok((row.get(0)?, row.get(1)?))
+			})
+			.optional()
+			.map_err(db_error)
+	}
+
+	/// Removes the blocks that were not finalized when indexed, and their events, so they get
+	/// indexed again from the chain that won. Returns how many blocks were removed.
+	pub fn revert_unfinalized(&mut self) -> sc_cli::Result<usize> {
+		let tx = self.transaction()?;
+		let first: Option<BlockNumber> = tx
+			.query_row("SELECT MIN(number) FROM blocks WHERE finalized = 0", [], |row| row.get(0))
+			.map_err(db_error)?;
+		let reverted = match first {
+			Some(first) => {
+				tx.execute("DELETE FROM events WHERE block_number >= ?1", [first])
+					.map_err(db_error)?;
+				tx.execute("DELETE FROM blocks WHERE number >= ?1", [first]).map_err(db_error)?
+			},
+			None => 0,
+		};
+		tx.commit().map_err(db_error)?;
+		Ok(reverted)
+	}
+
+	fn transaction(&mut self) -> sc_cli::Result<rusqlite::Transaction<'_>> {
+		self.conn.transaction().map_err(db_error)
+	}
+}
+
+fn insert_block(
+	conn: &Connection,
+	number: BlockNumber,
+	hash: Hash,
+	finalized: bool,
+	activity: &[Activity],
+) -> sc_cli::Result<()> {
+	conn.execute(
+		"INSERT INTO blocks (number, hash, finalized) VALUES (?1, ?2, ?3)",
+		params![number, format!("{:?}", hash), finalized],
+	)
+	.map_err(db_error)?;
+	let mut insert = conn
+		.prepare_cached(
+			"INSERT INTO events (block_number, event_index, class_id, nft_id, kind, account, \
+			counterparty, amount, data) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+		)
+		.map_err(db_error)?;
+	for item in activity {
+		let data = serde_json::to_value(item).map_err(|e| e.to_string())?;
+		let (account, counterparty, amount) = columns(&item.event);
+		insert
+			.execute(params![
+				number,
+				item.event_index,
+				item.class_id,
+				item.nft_id,
+				data["type"].as_str(),
+				account.map(Ss58Codec::to_ss58check),
+				counterparty.map(Ss58Codec::to_ss58check),
+				amount,
+				data.to_string(),
+			])
+			.map_err(db_error)?;
+	}
+	Ok(())
+}
+
+/// (account, counterparty, amount) columns of an event, see the schema.
+fn columns(event: &ActivityEvent) -> (Option<&AccountId>, Option<&AccountId>, Option<&str>) {
+	match event {
+		ActivityEvent::Minted { owner } | ActivityEvent::Burned { owner } => {
+			(Some(owner), None, None)
+		},
+		ActivityEvent::Transferred { from, to } => (Some(from), Some(to), None),
+		ActivityEvent::Listed { seller, price } => (Some(seller), None, Some(price)),
+		ActivityEvent::ListingExpired { seller } => (Some(seller), None, None),
+		ActivityEvent::Sold { seller, buyer, price } => (Some(seller), Some(buyer), Some(price)),
+		ActivityEvent::AuctionCreated { seller, reserve_price, .. } => {
+			(Some(seller), None, Some(reserve_price))
+		},
+		ActivityEvent::Bid { bidder, amount } => (Some(bidder), None, Some(amount)),
+		ActivityEvent::AuctionFinished { winner, price } => (Some(winner), None, Some(price)),
+		ActivityEvent::OfferMade { buyer, amount, .. } => (Some(buyer), None, Some(amount)),
+		ActivityEvent::ListingCanceled | ActivityEvent::AuctionCanceled => (None, None, None),
+	}
+}
+
+fn db_error(e: rusqlite::Error) -> sc_cli::Error {
+	sc_cli::Error::Application(Box::new(e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::AccountKeyring;
+
+	fn alice() -> AccountId {
+		AccountKeyring::Alice.to_account_id()
+	}
+
+	fn bob() -> AccountId {
+		AccountKeyring::Bob.to_account_id()
+	}
+
+	fn activity(event_index: u32, nft_id: u64, event: ActivityEvent) -> Activity {
+		Activity {
+			block_hash: Hash::default(),
+			block_number: 0,
+			event_index,
+			class_id: 0,
+			nft_id,
+			event,
+		}
+	}
+
+	/// Alice mints token 0 in finalized block 1, then sells it to Bob in unfinalized block 2.
+	fn db() -> IndexDb {
+		let db = IndexDb::open(Connection::open_in_memory().unwrap()).unwrap();
+		insert_block(
+			&db.conn,
+			1,
+			Hash::repeat_byte(1),
+			true,
+			&[activity(0, 0, ActivityEvent::Minted { owner: alice() })],
+		)
+		.unwrap();
+		insert_block(
+			&db.conn,
+			2,
+			Hash::repeat_byte(2),
+			false,
+			&[
+				activity(3, 0, ActivityEvent::Transferred { from: alice(), to: bob() }),
+				activity(
+					4,
+					0,
+					ActivityEvent::Sold { seller: alice(), buyer: bob(), price: "100".into() },
+				),
+			],
+		)
+		.unwrap();
+		db
+	}
+
+	fn owner(db: &IndexDb) -> Option<String> {
+		db.conn
+			.query_row("SELECT owner FROM ownership WHERE class_id = 0 AND nft_id = 0", [], |row| {
+				row.get(0)
+			})
+			.optional()
+			.unwrap()
+	}
+
+	fn sales(db: &IndexDb) -> Vec<(Option<String>, String, String)> {
+		let mut stmt = db.conn.prepare("SELECT seller, buyer, price FROM sales").unwrap();
+		let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap();
+		rows.map(Result::unwrap).collect()
+	}
+
+	#[test]
+	fn ownership_and_sales_follow_the_events() {
+		let db = db();
+		assert_eq!(owner(&db), Some(bob().to_ss58check()));
+		assert_eq!(
+			sales(&db),
+			vec![(Some(alice().to_ss58check()), bob().to_ss58check(), "100".into())]
+		);
+		assert_eq!(db.last_block().unwrap(), Some((2, format!("{:?}", Hash::repeat_byte(2)))));
+
+		insert_block(
+			&db.conn,
+			3,
+			Hash::repeat_byte(3),
+			false,
+			&[activity(0, 0, ActivityEvent::Burned { owner: bob() })],
+		)
+		.unwrap();
+		assert_eq!(owner(&db), None);
+	}
+
+	#[test]
+	fn unfinalized_blocks_are_reverted() {
+		let mut db = db();
+		assert_eq!(db.revert_unfinalized().unwrap(), 1);
+		assert_eq!(db.last_block().unwrap(), Some((1, format!("{:?}", Hash::repeat_byte(1)))));
+		assert_eq!(owner(&db), Some(alice().to_ss58check()));
+		assert!(sales(&db).is_empty());
+
+		// nothing left to revert
+		assert_eq!(db.revert_unfinalized().unwrap(), 0);
+	}
+}
@@ -0,0 +1,871 @@
+//! Keystore kept by a separate signer process, used with `--keystore-uri`.
+//!
+//! The node never sees the secret keys: it asks the signer for its public keys and signatures
+//! over a Unix socket (`unix:///path/to/socket` or just the path) or a TCP connection
+//! (`tcp://host:port`). Requests and responses are JSON-RPC 2.0 objects, one per line, with
+//! named parameters. Key types are their four characters (`aura`, `gran`), cryptos one of
+//! `sr25519`, `ed25519` and `ecdsa`, and keys, messages and signatures `0x` hex strings.
+//!
+//! | method                 | params                                   | result              |
+//! |------------------------|------------------------------------------|---------------------|
+//! | `signer_publicKeys`    | `keyType`, `crypto`                      | public keys         |
+//! | `signer_generate`      | `keyType`, `crypto`, `seed` (nullable)   | public key          |
+//! | `signer_insert`        | `keyType`, `suri`, `public`              | `null`              |
+//! | `signer_hasKeys`       | `keys`: list of `{ public, keyType }`    | boolean             |
+//! | `signer_sign`          | `keyType`, `crypto`, `public`, `message` | signature or `null` |
+//! | `signer_signPrehashed` | `keyType`, `public`, `hash` (ECDSA)      | signature or `null` |
+//!
+//! The TCP connection is not encrypted, so secrets are only sent over a Unix socket: a keystore
+//! behind `tcp://` refuses `signer_insert` and `signer_generate` with a seed, that signer has to
+//! be given its keys some other way.
+//!
+//! A `null` signature means the signer doesn't have the key. VRF signatures are not part of the
+//! protocol, the chain uses Aura and GRANDPA which don't need them.
+//!
+//! Reaching the signer is blocking I/O bounded by a timeout, the asynchronous interface runs it on
+//! tokio's blocking pool so that a slow signer doesn't hold up the executor.
+
+use std::{
+	io::{self, BufRead, BufReader, Read, Write},
+	net::{TcpStream, ToSocketAddrs},
+	os::unix::net::UnixStream,
+	path::PathBuf,
+	str::FromStr,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration,
+};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::{ByteArray, CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData},
+	CryptoStore, Error, SyncCryptoStore,
+};
+
+/// Longest wait for the signer to accept a connection, read a request or answer it.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the signer listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Endpoint {
+	Unix(PathBuf),
+	Tcp(String),
+}
+
+impl FromStr for Endpoint {
+	type Err = String;
+
+	fn from_str(uri: &str) -> Result<Self, Self::Err> {
+		if let Some(path) = uri.strip_prefix("unix://") {
+			return Ok(Endpoint::Unix(path.into()))
+		}
+		if let Some(address) = uri.strip_prefix("tcp://") {
+			return Ok(Endpoint::Tcp(address.into()))
+		}
+		match uri.split_once("://") {
+			Some((scheme, _)) =>
+				Err(format!("unsupported scheme `{}`, use unix:// or tcp://", scheme)),
+			None => Ok(Endpoint::Unix(uri.into())),
+		}
+	}
+}
+
+trait Stream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Stream for T {}
+
+type Connection = BufReader<Box<dyn Stream>>;
+
+fn connect(endpoint: &Endpoint) -> io::Result<Connection> {
+	let stream: Box<dyn Stream> = match endpoint {
+		Endpoint::Unix(path) => {
+			let stream = UnixStream::connect(path)?;
+			stream.set_read_timeout(Some(TIMEOUT))?;
+			stream.set_write_timeout(Some(TIMEOUT))?;
+			Box::new(stream)
+		},
+		Endpoint::Tcp(address) => {
+			let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+				io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")
+			})?;
+			let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+			stream.set_read_timeout(Some(TIMEOUT))?;
+			stream.set_write_timeout(Some(TIMEOUT))?;
+			Box::new(stream)
+		},
+	};
+	Ok(BufReader::new(stream))
+}
+
+/// Send a request and read the line answering it.
+fn exchange(connection: &mut Connection, request: &[u8]) -> io::Result<String> {
+	connection.get_mut().write_all(request)?;
+	connection.get_mut().flush()?;
+	let mut line = String::new();
+	if connection.read_line(&mut line)? == 0 {
+		return Err(io::ErrorKind::UnexpectedEof.into())
+	}
+	Ok(line)
+}
+
+/// The request can't have been handled by the signer, it is safe to send it again.
+fn is_closed(e: &io::Error) -> bool {
+	matches!(
+		e.kind(),
+		io::ErrorKind::UnexpectedEof |
+			io::ErrorKind::BrokenPipe |
+			io::ErrorKind::ConnectionReset |
+			io::ErrorKind::ConnectionAborted |
+			io::ErrorKind::NotConnected
+	)
+}
+
+/// The connection to the signer, shared with the tasks of the blocking pool.
+struct Client {
+	endpoint: Endpoint,
+	connection: Mutex<Option<Connection>>,
+	next_id: AtomicU64,
+}
+
+impl Client {
+	fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+		let mut request = request.to_string().into_bytes();
+		request.push(b'\n');
+
+		let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+		// a connection closed by the signer is only noticed once used, so retry once on a new one
+		let mut retried = false;
+		let line = loop {
+			let result = match connection.as_mut() {
+				Some(open) => exchange(open, &request),
+				None => connect(&self.endpoint)
+					.and_then(|new| exchange(connection.insert(new), &request)),
+			};
+			match result {
+				Ok(line) => break line,
+				Err(e) => {
+					*connection = None;
+					if retried || !is_closed(&e) {
+						log::warn!(target: "keystore", "Unable to reach the signer: {}", e);
+						return Err(Error::Unavailable)
+					}
+					retried = true;
+				},
+			}
+		};
+
+		let response: Value = serde_json::from_str(&line)
+			.map_err(|e| Error::Other(format!("Invalid response of the signer: {}", e)))?;
+		if response["id"] != json!(id) {
+			// the stream is out of step with the requests, start again on a new connection
+			*connection = None;
+			return Err(Error::Other(format!("The signer answered another request than {}", id)))
+		}
+		if let Some(error) = response.get("error") {
+			let message = error["message"].as_str().unwrap_or("unknown error");
+			return Err(Error::Other(format!("The signer failed to {}: {}", method, message)))
+		}
+		Ok(response.get("result").cloned().unwrap_or(Value::Null))
+	}
+}
+
+/// A keystore whose keys are held by a signer process, see the module documentation.
+#[derive(Clone)]
+pub struct RemoteKeystore {
+	client: Arc<Client>,
+}
+
+impl RemoteKeystore {
+	/// Connect to the signer listening at `uri`.
+	pub fn open(uri: &str) -> Result<Self, String> {
+		let endpoint = uri.parse::<Endpoint>()?;
+		let connection = connect(&endpoint).map_err(|e| format!("Unable to connect: {}", e))?;
+		let client = Client {
+			endpoint,
+			connection: Mutex::new(Some(connection)),
+			next_id: AtomicU64::new(0),
+		};
+		Ok(Self { client: Arc::new(client) })
+	}
+
+	fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+		self.client.call(method, params)
+	}
+
+	/// Run `f` on the blocking pool, it waits for the signer.
+	async fn blocking<R, F>(&self, f: F) -> R
+	where
+		R: Send + 'static,
+		F: FnOnce(&Self) -> R + Send + 'static,
+	{
+		let keystore = self.clone();
+		match tokio::task::spawn_blocking(move || f(&keystore)).await {
+			Ok(result) => result,
+			Err(e) => std::panic::resume_unwind(e.into_panic()),
+		}
+	}
+
+	/// Secrets may only travel over a Unix socket, see the module documentation.
+	fn ensure_local(&self, secret: &str) -> Result<(), Error> {
+		match self.client.endpoint {
+			Endpoint::Unix(_) => Ok(()),
+			Endpoint::Tcp(_) => Err(Error::ValidationError(format!(
+				"Refusing to send {} to the signer over unencrypted TCP",
+				secret
+			))),
+		}
+	}
+
+	fn public_keys(&self, id: KeyTypeId, crypto: CryptoTypeId) -> Result<Vec<Vec<u8>>, Error> {
+		let keys = self.call(
+			"signer_publicKeys",
+			json!({ "keyType": key_type(id), "crypto": crypto_name(crypto)? }),
+		)?;
+		keys.as_array()
+			.ok_or_else(|| invalid("a list of keys"))?
+			.iter()
+			.map(bytes)
+			.collect()
+	}
+
+	fn typed_public_keys<P: ByteArray>(&self, id: KeyTypeId, crypto: CryptoTypeId) -> Vec<P> {
+		match self.public_keys(id, crypto) {
+			Ok(keys) => keys.iter().filter_map(|key| P::from_slice(key).ok()).collect(),
+			Err(e) => {
+				log::warn!(target: "keystore", "Unable to list the keys of the signer: {}", e);
+				Vec::new()
+			},
+		}
+	}
+
+	fn generate<P: ByteArray>(
+		&self,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<P, Error> {
+		if seed.is_some() {
+			self.ensure_local("a seed")?;
+		}
+		let public = self.call(
+			"signer_generate",
+			json!({ "keyType": key_type(id), "crypto": crypto_name(crypto)?, "seed": seed }),
+		)?;
+		P::from_slice(&bytes(&public)?).map_err(|()| invalid("a public key"))
+	}
+}
+
+fn key_type(id: KeyTypeId) -> String {
+	String::from_utf8_lossy(&id.0).into_owned()
+}
+
+fn crypto_name(crypto: CryptoTypeId) -> Result<&'static str, Error> {
+	match crypto {
+		sr25519::CRYPTO_ID => Ok("sr25519"),
+		ed25519::CRYPTO_ID => Ok("ed25519"),
+		ecdsa::CRYPTO_ID => Ok("ecdsa"),
+		_ => Err(Error::ValidationError(format!("unsupported crypto {:?}", crypto))),
+	}
+}
+
+fn hex(bytes: &[u8]) -> Value {
+	Value::String(to_hex(bytes, false))
+}
+
+fn bytes(value: &Value) -> Result<Vec<u8>, Error> {
+	value
+		.as_str()
+		.and_then(|hex| from_hex(hex).ok())
+		.ok_or_else(|| invalid("a hex string"))
+}
+
+fn invalid(expected: &str) -> Error {
+	Error::Other(format!("Invalid response of the signer, expected {}", expected))
+}
+
+/// `None` for a `null` result, the signer doesn't have the key.
+fn signature(value: &Value) -> Result<Option<Vec<u8>>, Error> {
+	if value.is_null() {
+		return Ok(None)
+	}
+	bytes(value).map(Some)
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.typed_public_keys(id, sr25519::CRYPTO_ID)
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		self.generate(id, sr25519::CRYPTO_ID, seed)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.typed_public_keys(id, ed25519::CRYPTO_ID)
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		self.generate(id, ed25519::CRYPTO_ID, seed)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.typed_public_keys(id, ecdsa::CRYPTO_ID)
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		self.generate(id, ecdsa::CRYPTO_ID, seed)
+	}
+
+	fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		self.ensure_local("a secret key")
+			.and_then(|()| {
+				self.call(
+					"signer_insert",
+					json!({ "keyType": key_type(id), "suri": suri, "public": hex(public) }),
+				)
+			})
+			.map(|_| ())
+			.map_err(|e| log::warn!(target: "keystore", "Unable to insert a key: {}", e))
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let known = SyncCryptoStore::keys(self, id)?;
+		Ok(keys.into_iter().filter(|key| known.contains(key)).collect())
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let mut keys = Vec::new();
+		for crypto in [sr25519::CRYPTO_ID, ed25519::CRYPTO_ID, ecdsa::CRYPTO_ID] {
+			let public_keys = self.public_keys(id, crypto)?;
+			keys.extend(public_keys.into_iter().map(|public| CryptoTypePublicPair(crypto, public)));
+		}
+		Ok(keys)
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let keys: Vec<Value> = public_keys
+			.iter()
+			.map(|(public, id)| json!({ "public": hex(public), "keyType": key_type(*id) }))
+			.collect();
+		match self.call("signer_hasKeys", json!({ "keys": keys })) {
+			Ok(Value::Bool(has_keys)) => has_keys,
+			Ok(_) => {
+				log::warn!(target: "keystore", "{}", invalid("a boolean"));
+				false
+			},
+			Err(e) => {
+				log::warn!(target: "keystore", "Unable to look up keys: {}", e);
+				false
+			},
+		}
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let result = self.call(
+			"signer_sign",
+			json!({
+				"keyType": key_type(id),
+				"crypto": crypto_name(key.0)?,
+				"public": hex(&key.1),
+				"message": hex(msg),
+			}),
+		)?;
+		signature(&result)
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		_key_type: KeyTypeId,
+		_public: &sr25519::Public,
+		_transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		Err(Error::Other("The remote signer doesn't produce VRF signatures".into()))
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let result = self.call(
+			"signer_signPrehashed",
+			json!({ "keyType": key_type(id), "public": hex(public.as_ref()), "hash": hex(msg) }),
+		)?;
+		match signature(&result)? {
+			Some(signature) => ecdsa::Signature::from_slice(&signature)
+				.map(Some)
+				.ok_or_else(|| invalid("an ECDSA signature")),
+			None => Ok(None),
+		}
+	}
+}
+
+#[async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::sr25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::sr25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ed25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ed25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ecdsa_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ecdsa_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let (suri, public) = (suri.to_owned(), public.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::insert_unknown(keystore, id, &suri, &public))
+			.await
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::supported_keys(keystore, id, keys))
+			.await
+	}
+
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::keys(keystore, id)).await
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.blocking(move |keystore| SyncCryptoStore::has_keys(keystore, &public_keys))
+			.await
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::sign_with(keystore, id, &key, &msg))
+			.await
+	}
+
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		// refused without asking the signer
+		SyncCryptoStore::sr25519_vrf_sign(self, key_type, public, transcript_data)
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let (public, msg) = (*public, *msg);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ecdsa_sign_prehashed(keystore, id, &public, &msg)
+		})
+		.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_keystore::LocalKeystore;
+	use sp_core::Pair;
+	use std::{
+		net::TcpListener,
+		os::unix::net::UnixListener,
+		path::Path,
+		sync::{
+			atomic::{AtomicU32, AtomicUsize},
+			Arc,
+		},
+		thread,
+	};
+
+	const AURA: KeyTypeId = KeyTypeId(*b"aura");
+	const GRANDPA: KeyTypeId = KeyTypeId(*b"gran");
+
+	/// Unique path in the temporary directory.
+	fn temp_path(name: &str) -> PathBuf {
+		static NEXT: AtomicU32 = AtomicU32::new(0);
+		std::env::temp_dir().join(format!(
+			"remote-keystore-{}-{}-{}",
+			std::process::id(),
+			NEXT.fetch_add(1, Ordering::SeqCst),
+			name
+		))
+	}
+
+	/// Stand-in signer keeping its keys in a local keystore.
+	struct Signer {
+		keystore: LocalKeystore,
+		/// Close the connection after this many requests.
+		requests_per_connection: Option<usize>,
+		/// Requests answered so far.
+		requests: AtomicUsize,
+	}
+
+	impl Signer {
+		fn new(requests_per_connection: Option<usize>) -> Arc<Self> {
+			Arc::new(Self {
+				keystore: LocalKeystore::open(temp_path("keys"), None).unwrap(),
+				requests_per_connection,
+				requests: AtomicUsize::new(0),
+			})
+		}
+
+		fn serve<S: Read + Write>(&self, stream: S) {
+			let mut reader = BufReader::new(stream);
+			let mut served = 0;
+			loop {
+				if Some(served) == self.requests_per_connection {
+					return
+				}
+				let mut line = String::new();
+				if reader.read_line(&mut line).unwrap_or(0) == 0 {
+					return
+				}
+				let request: Value = serde_json::from_str(&line).unwrap();
+				let response =
+					match self.handle(request["method"].as_str().unwrap(), &request["params"]) {
+						Ok(result) =>
+							json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+						Err(message) => json!({
+							"jsonrpc": "2.0",
+							"id": request["id"],
+							"error": { "code": 1, "message": message },
+						}),
+					};
+				let mut response = response.to_string().into_bytes();
+				response.push(b'\n');
+				reader.get_mut().write_all(&response).unwrap();
+				self.requests.fetch_add(1, Ordering::SeqCst);
+				served += 1;
+			}
+		}
+
+		fn handle(&self, method: &str, params: &Value) -> Result<Value, String> {
+			let keystore = &self.keystore;
+			let id = || KeyTypeId::try_from(params["keyType"].as_str().unwrap()).unwrap();
+			let crypto = || match params["crypto"].as_str().unwrap() {
+				"sr25519" => sr25519::CRYPTO_ID,
+				"ed25519" => ed25519::CRYPTO_ID,
+				_ => ecdsa::CRYPTO_ID,
+			};
+			let hex_param = |name: &str| bytes(&params[name]).unwrap();
+			match method {
+				"signer_publicKeys" => {
+					let keys = match crypto() {
+						sr25519::CRYPTO_ID => SyncCryptoStore::sr25519_public_keys(keystore, id())
+							.iter()
+							.map(ByteArray::to_raw_vec)
+							.collect(),
+						ed25519::CRYPTO_ID => SyncCryptoStore::ed25519_public_keys(keystore, id())
+							.iter()
+							.map(ByteArray::to_raw_vec)
+							.collect(),
+						_ => SyncCryptoStore::ecdsa_public_keys(keystore, id())
+							.iter()
+							.map(ByteArray::to_raw_vec)
+							.collect::<Vec<_>>(),
+					};
+					Ok(keys.iter().map(|key| hex(key)).collect())
+				},
+				"signer_generate" => {
+					let seed = params["seed"].as_str();
+					let public = match crypto() {
+						sr25519::CRYPTO_ID =>
+							SyncCryptoStore::sr25519_generate_new(keystore, id(), seed)
+								.map(|key| key.to_raw_vec()),
+						ed25519::CRYPTO_ID =>
+							SyncCryptoStore::ed25519_generate_new(keystore, id(), seed)
+								.map(|key| key.to_raw_vec()),
+						_ => SyncCryptoStore::ecdsa_generate_new(keystore, id(), seed)
+							.map(|key| key.to_raw_vec()),
+					};
+					public.map(|public| hex(&public)).map_err(|e| e.to_string())
+				},
+				"signer_insert" => SyncCryptoStore::insert_unknown(
+					keystore,
+					id(),
+					params["suri"].as_str().unwrap(),
+					&hex_param("public"),
+				)
+				.map(|()| Value::Null)
+				.map_err(|()| "invalid key".to_string()),
+				"signer_hasKeys" => {
+					let keys: Vec<(Vec<u8>, KeyTypeId)> = params["keys"]
+						.as_array()
+						.unwrap()
+						.iter()
+						.map(|key| {
+							let id = KeyTypeId::try_from(key["keyType"].as_str().unwrap()).unwrap();
+							(bytes(&key["public"]).unwrap(), id)
+						})
+						.collect();
+					Ok(Value::Bool(SyncCryptoStore::has_keys(keystore, &keys)))
+				},
+				"signer_sign" => {
+					let key = CryptoTypePublicPair(crypto(), hex_param("public"));
+					let signature =
+						SyncCryptoStore::sign_with(keystore, id(), &key, &hex_param("message"))
+							.map_err(|e| e.to_string())?;
+					Ok(signature.map(|signature| hex(&signature)).unwrap_or(Value::Null))
+				},
+				"signer_signPrehashed" => {
+					let public = ecdsa::Public::from_slice(&hex_param("public")).unwrap();
+					let hash: [u8; 32] = hex_param("hash").try_into().unwrap();
+					let signature =
+						SyncCryptoStore::ecdsa_sign_prehashed(keystore, id(), &public, &hash)
+							.map_err(|e| e.to_string())?;
+					Ok(signature.map(|signature| hex(signature.as_ref())).unwrap_or(Value::Null))
+				},
+				_ => Err(format!("unknown method {}", method)),
+			}
+		}
+	}
+
+	/// Serve `signer` on a local TCP port, one connection after the other.
+	fn tcp_signer(signer: Arc<Signer>) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				signer.serve(stream.unwrap());
+			}
+		});
+		format!("tcp://{}", address)
+	}
+
+	/// Serve `signer` on a Unix socket in the temporary directory.
+	fn unix_signer(signer: Arc<Signer>) -> String {
+		let path = temp_path("signer.sock");
+		let listener = UnixListener::bind(&path).unwrap();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				signer.serve(stream.unwrap());
+			}
+		});
+		format!("unix://{}", path.display())
+	}
+
+	#[test]
+	fn parses_endpoints() {
+		assert_eq!(
+			"unix:///run/signer.sock".parse(),
+			Ok(Endpoint::Unix("/run/signer.sock".into()))
+		);
+		assert_eq!("/run/signer.sock".parse(), Ok(Endpoint::Unix("/run/signer.sock".into())));
+		assert_eq!("tcp://127.0.0.1:9955".parse(), Ok(Endpoint::Tcp("127.0.0.1:9955".into())));
+		assert!("http://127.0.0.1:9955".parse::<Endpoint>().is_err());
+	}
+
+	#[test]
+	fn fails_to_open_without_a_signer() {
+		let path = temp_path("nobody-listens.sock");
+		assert!(!Path::new(&path).exists());
+		assert!(RemoteKeystore::open(&format!("unix://{}", path.display())).is_err());
+	}
+
+	#[tokio::test]
+	async fn serves_the_asynchronous_interface_from_the_blocking_pool() {
+		let signer = Signer::new(None);
+		let keystore = RemoteKeystore::open(&unix_signer(signer.clone())).unwrap();
+
+		let public = CryptoStore::sr25519_generate_new(&keystore, AURA, None).await.unwrap();
+		assert_eq!(CryptoStore::sr25519_public_keys(&keystore, AURA).await, vec![public]);
+		assert!(CryptoStore::has_keys(&keystore, &[(public.to_raw_vec(), AURA)]).await);
+
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, public.to_raw_vec());
+		let signature =
+			CryptoStore::sign_with(&keystore, AURA, &key, b"block").await.unwrap().unwrap();
+		let signature = sr25519::Signature::from_slice(&signature).unwrap();
+		assert!(sr25519::Pair::verify(&signature, b"block", &public));
+	}
+
+	#[test]
+	fn signs_aura_blocks_with_generated_keys() {
+		let signer = Signer::new(None);
+		let keystore = RemoteKeystore::open(&unix_signer(signer.clone())).unwrap();
+
+		let public = SyncCryptoStore::sr25519_generate_new(&keystore, AURA, None).unwrap();
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&keystore, AURA), vec![public]);
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&signer.keystore, AURA), vec![public]);
+		assert!(SyncCryptoStore::has_keys(&keystore, &[(public.to_raw_vec(), AURA)]));
+		assert!(!SyncCryptoStore::has_keys(&keystore, &[(public.to_raw_vec(), GRANDPA)]));
+
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, public.to_raw_vec());
+		let signature =
+			SyncCryptoStore::sign_with(&keystore, AURA, &key, b"block").unwrap().unwrap();
+		let signature = sr25519::Signature::from_slice(&signature).unwrap();
+		assert!(sr25519::Pair::verify(&signature, b"block", &public));
+	}
+
+	#[test]
+	fn uses_inserted_keys() {
+		let keystore = RemoteKeystore::open(&unix_signer(Signer::new(None))).unwrap();
+		let alice = ed25519::Pair::from_string("//Alice", None).unwrap().public();
+		SyncCryptoStore::insert_unknown(&keystore, GRANDPA, "//Alice", alice.as_ref()).unwrap();
+
+		assert_eq!(SyncCryptoStore::ed25519_public_keys(&keystore, GRANDPA), vec![alice]);
+		let key = CryptoTypePublicPair(ed25519::CRYPTO_ID, alice.to_raw_vec());
+		assert!(SyncCryptoStore::keys(&keystore, GRANDPA).unwrap().contains(&key));
+		assert_eq!(
+			SyncCryptoStore::supported_keys(
+				&keystore,
+				GRANDPA,
+				vec![key.clone(), CryptoTypePublicPair(sr25519::CRYPTO_ID, vec![0; 32])]
+			)
+			.unwrap(),
+			vec![key.clone()]
+		);
+
+		let signature =
+			SyncCryptoStore::sign_with(&keystore, GRANDPA, &key, b"vote").unwrap().unwrap();
+		let signature = ed25519::Signature::from_slice(&signature).unwrap();
+		assert!(ed25519::Pair::verify(&signature, b"vote", &alice));
+	}
+
+	#[test]
+	fn keeps_secrets_off_tcp() {
+		let signer = Signer::new(None);
+		let keystore = RemoteKeystore::open(&tcp_signer(signer.clone())).unwrap();
+		let alice = ed25519::Pair::from_string("//Alice", None).unwrap().public();
+		assert!(
+			SyncCryptoStore::insert_unknown(&keystore, GRANDPA, "//Alice", alice.as_ref()).is_err()
+		);
+		assert!(matches!(
+			SyncCryptoStore::sr25519_generate_new(&keystore, AURA, Some("//Bob")),
+			Err(Error::ValidationError(_))
+		));
+		assert_eq!(signer.requests.load(Ordering::SeqCst), 0);
+
+		// keys the signer generates itself never leave it
+		let public = SyncCryptoStore::sr25519_generate_new(&keystore, AURA, None).unwrap();
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&signer.keystore, AURA), vec![public]);
+	}
+
+	#[test]
+	fn unknown_keys_have_no_signature() {
+		let keystore = RemoteKeystore::open(&tcp_signer(Signer::new(None))).unwrap();
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, vec![1; 32]);
+		assert!(matches!(SyncCryptoStore::sign_with(&keystore, AURA, &key, b"block"), Ok(None)));
+	}
+
+	#[test]
+	fn signs_prehashed_messages() {
+		let keystore = RemoteKeystore::open(&unix_signer(Signer::new(None))).unwrap();
+		let public = SyncCryptoStore::ecdsa_generate_new(&keystore, AURA, Some("//Bob")).unwrap();
+		let hash = sp_core::blake2_256(b"message");
+		let signature = SyncCryptoStore::ecdsa_sign_prehashed(&keystore, AURA, &public, &hash)
+			.unwrap()
+			.unwrap();
+		assert!(ecdsa::Pair::verify_prehashed(&signature, &hash, &public));
+	}
+
+	#[test]
+	fn reconnects_when_the_signer_closes_the_connection() {
+		let signer = Signer::new(Some(1));
+		let keystore = RemoteKeystore::open(&tcp_signer(signer.clone())).unwrap();
+
+		let public = SyncCryptoStore::sr25519_generate_new(&keystore, AURA, None).unwrap();
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&keystore, AURA), vec![public]);
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&keystore, AURA), vec![public]);
+		assert_eq!(signer.requests.load(Ordering::SeqCst), 3);
+	}
+
+	#[test]
+	fn reports_signer_errors() {
+		let keystore = RemoteKeystore::open(&unix_signer(Signer::new(None))).unwrap();
+		let result = SyncCryptoStore::sr25519_generate_new(&keystore, AURA, Some("not a suri"));
+		assert!(matches!(result, Err(Error::Other(_))));
+		assert!(matches!(
+			SyncCryptoStore::sr25519_vrf_sign(
+				&keystore,
+				AURA,
+				&sr25519::Public::from_raw([0; 32]),
+				VRFTranscriptData { label: b"test", items: vec![] },
+			),
+			Err(Error::Other(_))
+		));
+	}
+}
@@ -1,3 +1,6 @@
+pub mod benchmarking;
 pub mod chain_spec;
+pub mod keystore;
 pub mod rpc;
+pub mod sealing;
 pub mod service;
@@ -0,0 +1,1135 @@
+//! `load-test` subcommand: measures how much marketplace traffic the chain sustains.
+//!
+//! A throwaway dev chain is started with instant or manual sealing instead of Aura. Alice funds
+//! `--accounts` accounts derived from `//Alice//load//<n>`, then every round the accounts send a
+//! mix of `create_class`, `mint`, `list`, `bid` and `buy` extrinsics to the local transaction
+//! pool and a block is sealed. A local copy of the classes, tokens, listings and auctions, kept
+//! up to date from the events of every block, is used to send extrinsics that can succeed: a
+//! `buy` picks a listing of someone else, a `bid` an auction that is running and so on. When
+//! nothing fits an action falls back to a simpler one (`buy` to `list` to `mint` to
+//! `create_class`), a `bid` starts an auction when there is nothing to bid on.
+//!
+//! Every block is sealed one slot after the previous one whatever the wall clock says, so the
+//! report gives both the throughput of the machine and the one of a chain producing a block
+//! every slot.
+//!
+//! Block fullness is the dispatch weight the runtime charged, and the weights of `pallet_nft` and
+//! `pallet_nft_market_place` are placeholders until they are generated with `benchmark pallet`
+//! (see their `weights.rs`). Until then it says how the extrinsics fill blocks relative to each
+//! other, not how full a block is on real hardware.
+
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	fmt,
+	pin::Pin,
+	str::FromStr,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use codec::{Decode, Encode};
+use frame_support::{dispatch::DispatchClass, traits::PalletInfoAccess, weights::Weight};
+use futures::{channel::mpsc, SinkExt, Stream};
+use node_template_runtime::{
+	opaque::Block,
+	pallet_nft_market_place::{self, TimeBase},
+	AccountId, Balance, BalancesCall, BlockNumber, BlockWeights, Hash, NftCall, NftMarketPlaceCall,
+	Runtime, RuntimeCall, RuntimeEvent, UncheckedExtrinsic, EXISTENTIAL_DEPOSIT,
+};
+use pallet_nft::Id;
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_client_api::{Backend, BlockBackend, StateBackend};
+use sc_consensus_manual_seal::rpc::EngineCommand;
+use sc_service::{BasePath, Configuration, PartialComponents, TaskManager};
+use sc_transaction_pool_api::{TransactionPool, TransactionSource};
+use sp_blockchain::HeaderBackend;
+use sp_core::{sr25519, twox_128, Pair};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{generic::BlockId, DispatchError, ModuleError};
+
+use crate::{
+	benchmarking::create_benchmark_extrinsic,
+	rpc::activity,
+	sealing::{self, unix_millis, DevClock},
+	service::{self, FullClient, FullPool},
+};
+
+/// Balance Alice gives to every load test account.
+const FUNDS: Balance = 1 << 48;
+/// Auctions started by the load test last this many slots.
+const AUCTION_SLOTS: u64 = 50;
+/// Blocks sealed after the last round to empty the pool.
+const DRAIN_BLOCKS: u32 = 10;
+
+/// Kind of extrinsic sent by the load test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+	CreateClass,
+	Mint,
+	/// Put a token up for sale.
+	List,
+	/// Bid on a running auction, or start one.
+	Bid,
+	/// Buy a listed token.
+	Buy,
+}
+
+impl Action {
+	const ALL: [Action; 5] =
+		[Action::CreateClass, Action::Mint, Action::List, Action::Bid, Action::Buy];
+
+	fn name(&self) -> &'static str {
+		match self {
+			Action::CreateClass => "create_class",
+			Action::Mint => "mint",
+			Action::List => "list",
+			Action::Bid => "bid",
+			Action::Buy => "buy",
+		}
+	}
+
+	/// Action an extrinsic of the load test was sent for.
+	fn of_call(call: &RuntimeCall) -> Option<Action> {
+		match call {
+			RuntimeCall::NftModule(NftCall::create_class { .. }) => Some(Action::CreateClass),
+			RuntimeCall::NftModule(NftCall::mint { .. }) => Some(Action::Mint),
+			RuntimeCall::NftMarketPlaceModule(NftMarketPlaceCall::create_offer { .. }) =>
+				Some(Action::List),
+			RuntimeCall::NftMarketPlaceModule(
+				NftMarketPlaceCall::create_auction { .. } | NftMarketPlaceCall::buy_auction { .. },
+			) => Some(Action::Bid),
+			RuntimeCall::NftMarketPlaceModule(NftMarketPlaceCall::buy_offer { .. }) =>
+				Some(Action::Buy),
+			_ => None,
+		}
+	}
+}
+
+impl FromStr for Action {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Action::ALL
+			.into_iter()
+			.find(|action| action.name() == s)
+			.ok_or_else(|| format!("unknown extrinsic kind `{}`", s))
+	}
+}
+
+/// Relative share of every [`Action`] in the traffic, parsed from `kind=weight,kind=weight`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrafficMix(Vec<(Action, u32)>);
+
+impl TrafficMix {
+	fn pick(&self, rng: &mut Rng) -> Action {
+		let total: u32 = self.0.iter().map(|(_, weight)| weight).sum();
+		let mut roll = rng.below(total as u64) as u32;
+		for (action, weight) in &self.0 {
+			if roll < *weight {
+				return *action
+			}
+			roll -= weight;
+		}
+		unreachable!("the roll is below the total weight; qed")
+	}
+}
+
+impl FromStr for TrafficMix {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut mix = Vec::new();
+		for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+			let (kind, weight) = match pair.split_once('=') {
+				Some(split) => split,
+				None => return Err(format!("expected `kind=weight`, got `{}`", pair)),
+			};
+			let action = kind.trim().parse::<Action>()?;
+			let weight = weight
+				.trim()
+				.parse::<u32>()
+				.map_err(|e| format!("invalid weight of `{}`: {}", kind, e))?;
+			if mix.iter().any(|(known, _)| *known == action) {
+				return Err(format!("`{}` is given twice", kind))
+			}
+			mix.push((action, weight));
+		}
+		if mix.iter().all(|(_, weight)| *weight == 0) {
+			return Err("at least one extrinsic kind needs a weight above zero".into())
+		}
+		Ok(TrafficMix(mix))
+	}
+}
+
+/// How the blocks of the load test are sealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SealMode {
+	/// A block is sealed as soon as transactions enter the pool.
+	Instant,
+	/// A block is sealed after every round of `--extrinsics-per-block` extrinsics.
+	Manual,
+}
+
+/// The `load-test` command used to measure the throughput of marketplace traffic.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct LoadTestCmd {
+	/// Accounts sending the traffic.
+	#[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+	pub accounts: u32,
+
+	/// Rounds of traffic, every round is followed by a block with manual sealing.
+	#[arg(long, default_value_t = 20)]
+	pub blocks: u32,
+
+	/// Extrinsics sent to the pool every round.
+	#[arg(long, default_value_t = 1_000)]
+	pub extrinsics_per_block: u32,
+
+	/// Share of every kind of extrinsic as `kind=weight` pairs. Kinds are `create_class`,
+	/// `mint`, `list`, `bid` and `buy`.
+	#[arg(long, default_value = "create_class=1,mint=10,list=5,bid=2,buy=4")]
+	pub mix: TrafficMix,
+
+	/// How blocks are sealed.
+	#[arg(long, value_enum, default_value_t = SealMode::Manual)]
+	pub seal: SealMode,
+
+	/// Seed of the traffic, the same seed sends the same traffic.
+	#[arg(long, default_value_t = 42)]
+	pub seed: u64,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl LoadTestCmd {
+	/// Start the chain and return the future running the load test.
+	pub fn run(
+		&self,
+		config: Configuration,
+	) -> sc_cli::Result<(impl futures::Future<Output = sc_cli::Result<()>>, TaskManager)> {
+		let PartialComponents {
+			client, backend, task_manager, select_chain, transaction_pool, ..
+		} = service::new_partial(&config)?;
+
+		// `spawn_tasks` is not used, included transactions must still leave the pool
+		task_manager.spawn_essential_handle().spawn(
+			"txpool-notifications",
+			Some("transaction-pool"),
+			sc_transaction_pool::notification_future(client.clone(), transaction_pool.clone()),
+		);
+
+		// every block is one slot after the previous one, however fast it is sealed
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+		let clock = Arc::new(DevClock::new(slot_duration.as_millis(), unix_millis(), false));
+		let (commands, seal): (Pin<Box<dyn Stream<Item = EngineCommand<Hash>> + Send>>, _) =
+			match self.seal {
+				SealMode::Instant =>
+					(Box::pin(sealing::instant_commands(&*transaction_pool)), None),
+				SealMode::Manual => {
+					let (sink, commands) = mpsc::channel(16);
+					(Box::pin(commands), Some(sink))
+				},
+			};
+		service::start_dev_sealing(
+			&task_manager,
+			client.clone(),
+			transaction_pool.clone(),
+			select_chain,
+			config.prometheus_registry(),
+			None,
+			clock.clone(),
+			commands,
+		);
+
+		let driver = Driver {
+			cmd: self.clone(),
+			client,
+			backend,
+			pool: transaction_pool,
+			seal,
+			clock,
+			slot_millis: slot_duration.as_millis(),
+			last_block: 0,
+		};
+		Ok((driver.run(), task_manager))
+	}
+}
+
+impl CliConfiguration for LoadTestCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn chain_id(&self, _is_dev: bool) -> sc_cli::Result<String> {
+		Ok(self.shared_params.chain.clone().unwrap_or_else(|| "dev".into()))
+	}
+
+	fn base_path(&self) -> sc_cli::Result<Option<BasePath>> {
+		// a fresh chain for every run unless a path is given
+		match self.shared_params.base_path()? {
+			Some(path) => Ok(Some(path)),
+			None => Ok(Some(BasePath::new_temp_dir()?)),
+		}
+	}
+}
+
+type FullBackend = sc_service::TFullBackend<Block>;
+
+/// Sends the traffic and collects the report.
+struct Driver {
+	cmd: LoadTestCmd,
+	client: Arc<FullClient>,
+	backend: Arc<FullBackend>,
+	pool: Arc<FullPool>,
+	/// Commands of the manual sealing, `None` with instant sealing.
+	seal: Option<mpsc::Sender<EngineCommand<Hash>>>,
+	/// Timestamps of the sealed blocks.
+	clock: Arc<DevClock>,
+	slot_millis: u64,
+	/// Last block whose events were read.
+	last_block: BlockNumber,
+}
+
+impl Driver {
+	async fn run(mut self) -> sc_cli::Result<()> {
+		let accounts: Vec<sr25519::Pair> = (0..self.cmd.accounts)
+			.map(|n| {
+				sr25519::Pair::from_string(&format!("//Alice//load//{}", n), None)
+					.expect("static values are valid; qed")
+			})
+			.collect();
+		let ids: Vec<AccountId> = accounts.iter().map(|pair| pair.public().into()).collect();
+		let mut market = Market::default();
+		let mut report = Report::default();
+
+		log::info!("💸 Funding {} accounts", ids.len());
+		let alice = Sr25519Keyring::Alice.pair();
+		let funding = ids
+			.iter()
+			.enumerate()
+			.map(|(nonce, dest)| {
+				let call = BalancesCall::transfer_keep_alive { dest: dest.clone(), value: FUNDS };
+				self.sign(&alice, call.into(), nonce as u32)
+			})
+			.collect();
+		for result in self.submit(funding).await? {
+			if let Err(e) = result {
+				return Err(format!("Unable to fund the load test accounts: {}", e).into())
+			}
+		}
+		self.settle().await?;
+		// nothing to report for the funding blocks
+		self.read_blocks(&mut market, &mut Report::default())?;
+
+		log::info!(
+			"🚚 Sending {} rounds of {} extrinsics from {} accounts",
+			self.cmd.blocks,
+			self.cmd.extrinsics_per_block,
+			ids.len()
+		);
+		let mut rng = Rng::new(self.cmd.seed);
+		let mut nonces = vec![0_u32; ids.len()];
+		let mut next_account = 0;
+		let started = Instant::now();
+		for round in 0..self.cmd.blocks {
+			let mut sent = 0;
+			while sent < self.cmd.extrinsics_per_block {
+				// one extrinsic per account at a time, so a rejected one leaves no nonce gap
+				let wave = (self.cmd.extrinsics_per_block - sent).min(ids.len() as u32);
+				let mut planned = Vec::new();
+				let mut extrinsics = Vec::new();
+				let next_time = self.clock.earliest_next();
+				for _ in 0..wave {
+					let who = next_account;
+					next_account = (next_account + 1) % ids.len();
+					let action = self.cmd.mix.pick(&mut rng);
+					let (action, call) =
+						market.plan(&ids[who], action, &mut rng, next_time, self.slot_millis);
+					extrinsics.push(self.sign(&accounts[who], call, nonces[who]));
+					planned.push((who, action));
+				}
+				for ((who, action), result) in
+					planned.into_iter().zip(self.submit(extrinsics).await?)
+				{
+					report.sent(action);
+					match result {
+						Ok(()) => nonces[who] += 1,
+						Err(reason) => report.rejected(action, format!("pool: {}", reason)),
+					}
+				}
+				sent += wave;
+			}
+			self.seal_round().await?;
+			self.read_blocks(&mut market, &mut report)?;
+			log::info!("🧱 Round {} done, best block #{}", round + 1, self.last_block);
+		}
+		self.settle().await?;
+		self.read_blocks(&mut market, &mut report)?;
+		report.elapsed = started.elapsed();
+		report.slot = Duration::from_millis(self.slot_millis);
+
+		println!("{}", report);
+		Ok(())
+	}
+
+	fn sign(&self, pair: &sr25519::Pair, call: RuntimeCall, nonce: u32) -> UncheckedExtrinsic {
+		create_benchmark_extrinsic(&self.client, pair.clone(), call, nonce)
+	}
+
+	/// Submit extrinsics to the pool, an error per rejected one.
+	async fn submit(
+		&self,
+		extrinsics: Vec<UncheckedExtrinsic>,
+	) -> sc_cli::Result<Vec<Result<(), String>>> {
+		let at = BlockId::Hash(self.client.info().best_hash);
+		let results = self
+			.pool
+			.submit_at(
+				&at,
+				TransactionSource::External,
+				extrinsics.into_iter().map(Into::into).collect(),
+			)
+			.await
+			.map_err(|e| format!("Unable to submit to the transaction pool: {}", e))?;
+		Ok(results
+			.into_iter()
+			.map(|result| result.map(|_| ()).map_err(|e| e.to_string()))
+			.collect())
+	}
+
+	/// Seal the block of a round, with instant sealing wait until the pool is empty.
+	async fn seal_round(&mut self) -> sc_cli::Result<()> {
+		match &mut self.seal {
+			Some(seal) => {
+				let (sender, receiver) = futures::channel::oneshot::channel();
+				seal.send(EngineCommand::SealNewBlock {
+					create_empty: true,
+					finalize: true,
+					parent_hash: None,
+					sender: Some(sender),
+				})
+				.await
+				.map_err(|e| format!("Manual sealing stopped: {}", e))?;
+				match receiver.await {
+					Ok(Ok(_)) => Ok(()),
+					Ok(Err(e)) => Err(format!("Unable to seal a block: {}", e).into()),
+					Err(_) => Err("Manual sealing stopped".into()),
+				}
+			},
+			None => self.wait_for_empty_pool().await,
+		}
+	}
+
+	/// Seal until every ready transaction is in a block.
+	async fn settle(&mut self) -> sc_cli::Result<()> {
+		if self.seal.is_none() {
+			return self.wait_for_empty_pool().await
+		}
+		for _ in 0..DRAIN_BLOCKS {
+			if self.pool.status().ready == 0 {
+				break
+			}
+			self.seal_round().await?;
+		}
+		Ok(())
+	}
+
+	async fn wait_for_empty_pool(&self) -> sc_cli::Result<()> {
+		let best = self.client.info().best_number;
+		let deadline = Instant::now() + Duration::from_secs(60);
+		while self.pool.status().ready > 0 {
+			if Instant::now() > deadline {
+				return Err(format!(
+					"{} transactions are still waiting in the pool after a minute, best block #{} \
+					was #{} before",
+					self.pool.status().ready,
+					self.client.info().best_number,
+					best,
+				)
+				.into())
+			}
+			futures_timer::Delay::new(Duration::from_millis(20)).await;
+		}
+		Ok(())
+	}
+
+	/// Read the blocks sealed since the last call into the market and the report.
+	fn read_blocks(&mut self, market: &mut Market, report: &mut Report) -> sc_cli::Result<()> {
+		let best = self.client.info().best_number;
+		let max_weight = BlockWeights::get()
+			.get(DispatchClass::Normal)
+			.max_total
+			.unwrap_or(BlockWeights::get().max_block);
+		for number in self.last_block + 1..=best {
+			let hash = self
+				.client
+				.hash(number)?
+				.ok_or_else(|| format!("Block #{} is not in the database", number))?;
+			let actions: Vec<Option<Action>> = self
+				.client
+				.block_body(hash)?
+				.unwrap_or_default()
+				.iter()
+				.map(|extrinsic| {
+					UncheckedExtrinsic::decode(&mut &extrinsic.encode()[..])
+						.ok()
+						.and_then(|extrinsic| Action::of_call(&extrinsic.function))
+				})
+				.collect();
+			let weight = storage::<frame_system::ConsumedWeight>(
+				&*self.backend,
+				hash,
+				&[twox_128(b"System"), twox_128(b"BlockWeight")].concat(),
+			)
+			.map(|consumed| *consumed.get(DispatchClass::Normal))
+			.unwrap_or_else(Weight::zero);
+
+			let mut block = BlockStats {
+				number,
+				extrinsics: actions.iter().flatten().count() as u32,
+				failed: 0,
+				fullness: weight.ref_time() as f64 / max_weight.ref_time().max(1) as f64,
+			};
+			for record in activity::events(&*self.backend, hash) {
+				let action = match record.phase {
+					frame_system::Phase::ApplyExtrinsic(index) =>
+						actions.get(index as usize).copied().flatten(),
+					_ => None,
+				};
+				match (&record.event, action) {
+					(
+						RuntimeEvent::System(frame_system::Event::ExtrinsicSuccess { .. }),
+						Some(action),
+					) => report.succeeded(action),
+					(
+						RuntimeEvent::System(frame_system::Event::ExtrinsicFailed {
+							dispatch_error,
+							..
+						}),
+						Some(action),
+					) => {
+						block.failed += 1;
+						report.failed(action, failure_reason(dispatch_error));
+					},
+					_ => {},
+				}
+				market.apply(&record.event);
+			}
+			log::debug!(
+				"#{}: {} extrinsics, {} failed, {:.1}% full",
+				number,
+				block.extrinsics,
+				block.failed,
+				block.fullness * 100.0
+			);
+			report.blocks.push(block);
+		}
+		self.last_block = best;
+		market.busy.clear();
+		Ok(())
+	}
+}
+
+fn storage<T: Decode>(backend: &FullBackend, hash: Hash, key: &[u8]) -> Option<T> {
+	let state = backend.state_at(hash).ok()?;
+	let raw = state.storage(key).ok()??;
+	T::decode(&mut &raw[..]).ok()
+}
+
+/// `Pallet::Error` of a failed extrinsic.
+fn failure_reason(error: &DispatchError) -> String {
+	if let DispatchError::Module(ModuleError { index, error, .. }) = error {
+		let index = *index as usize;
+		let name = if index == <node_template_runtime::NftModule as PalletInfoAccess>::index() {
+			pallet_nft::Error::<Runtime>::decode(&mut &error[..])
+				.ok()
+				.map(|e| ("NftModule", <&'static str>::from(e)))
+		} else if index ==
+			<node_template_runtime::NftMarketPlaceModule as PalletInfoAccess>::index()
+		{
+			pallet_nft_market_place::Error::<Runtime>::decode(&mut &error[..])
+				.ok()
+				.map(|e| ("NftMarketPlaceModule", <&'static str>::from(e)))
+		} else if index == <node_template_runtime::Balances as PalletInfoAccess>::index() {
+			pallet_balances::Error::<Runtime>::decode(&mut &error[..])
+				.ok()
+				.map(|e| ("Balances", <&'static str>::from(e)))
+		} else {
+			None
+		};
+		if let Some((pallet, error)) = name {
+			return format!("{}::{}", pallet, error)
+		}
+	}
+	format!("{:?}", error)
+}
+
+/// Running auction as seen by the load test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AuctionState {
+	owner: AccountId,
+	reserve: Balance,
+	highest: Balance,
+	start: u64,
+	end: u64,
+}
+
+/// Classes, tokens, listings and auctions of the chain, built from its events.
+#[derive(Debug, Default)]
+struct Market {
+	classes: Vec<Id>,
+	owners: BTreeMap<(Id, Id), AccountId>,
+	tokens: HashMap<AccountId, BTreeSet<(Id, Id)>>,
+	listings: BTreeMap<(Id, Id), (AccountId, Balance)>,
+	auctions: BTreeMap<(Id, Id), AuctionState>,
+	/// Tokens an extrinsic was planned for since the last block, so that two extrinsics of the
+	/// same block don't race for them.
+	busy: BTreeSet<(Id, Id)>,
+	minted: u64,
+}
+
+impl Market {
+	fn apply(&mut self, event: &RuntimeEvent) {
+		use pallet_nft_market_place::Event as MarketEvent;
+		match event {
+			RuntimeEvent::NftModule(pallet_nft::Event::ClassCreated { class_id, .. }) =>
+				self.classes.push(*class_id),
+			RuntimeEvent::NftModule(pallet_nft::Event::Minted {
+				class_id, nft_id, owner, ..
+			}) => self.set_owner((*class_id, *nft_id), Some(owner.clone())),
+			RuntimeEvent::NftModule(pallet_nft::Event::TransferNft {
+				class_id, nft_id, to, ..
+			}) |
+			RuntimeEvent::NftMarketPlaceModule(MarketEvent::TransferNft {
+				class_id, nft_id, to, ..
+			}) => self.set_owner((*class_id, *nft_id), Some(to.clone())),
+			RuntimeEvent::NftModule(pallet_nft::Event::Burned { class_id, nft_id, .. }) =>
+				self.set_owner((*class_id, *nft_id), None),
+			RuntimeEvent::NftMarketPlaceModule(MarketEvent::OfferCreated {
+				class_id,
+				nft_id,
+				owner,
+				amount,
+			}) => {
+				self.listings.insert((*class_id, *nft_id), (owner.clone(), *amount));
+			},
+			RuntimeEvent::NftMarketPlaceModule(
+				MarketEvent::OfferBuyed { class_id, nft_id, .. } |
+				MarketEvent::OfferCanceled { class_id, nft_id } |
+				MarketEvent::OfferExpired { class_id, nft_id, .. },
+			) => {
+				self.listings.remove(&(*class_id, *nft_id));
+			},
+			RuntimeEvent::NftMarketPlaceModule(MarketEvent::AuctionCreated {
+				class_id,
+				nft_id,
+				owner,
+				amount,
+				start_time,
+				end_time,
+				time_base: TimeBase::UnixMs,
+				..
+			}) => {
+				let auction = AuctionState {
+					owner: owner.clone(),
+					reserve: *amount,
+					highest: 0,
+					start: *start_time,
+					end: *end_time,
+				};
+				self.auctions.insert((*class_id, *nft_id), auction);
+			},
+			RuntimeEvent::NftMarketPlaceModule(MarketEvent::NewUserAuctioner {
+				class_id,
+				nft_id,
+				amount,
+				..
+			}) =>
+				if let Some(auction) = self.auctions.get_mut(&(*class_id, *nft_id)) {
+					auction.highest = *amount;
+				},
+			RuntimeEvent::NftMarketPlaceModule(
+				MarketEvent::AuctionFinished { class_id, nft_id, .. } |
+				MarketEvent::AuctionCanceled { class_id, nft_id },
+			) => {
+				self.auctions.remove(&(*class_id, *nft_id));
+			},
+			_ => {},
+		}
+	}
+
+	fn set_owner(&mut self, token: (Id, Id), owner: Option<AccountId>) {
+		if let Some(old) = self.owners.remove(&token) {
+			if let Some(tokens) = self.tokens.get_mut(&old) {
+				tokens.remove(&token);
+			}
+		}
+		self.listings.remove(&token);
+		self.auctions.remove(&token);
+		if let Some(owner) = owner {
+			self.tokens.entry(owner.clone()).or_default().insert(token);
+			self.owners.insert(token, owner);
+		}
+	}
+
+	/// Token of `who` that is neither listed, auctioned nor used by another extrinsic.
+	fn free_token(&self, who: &AccountId, rng: &mut Rng) -> Option<(Id, Id)> {
+		let free: Vec<_> = self
+			.tokens
+			.get(who)?
+			.iter()
+			.filter(|token| {
+				!self.listings.contains_key(token) &&
+					!self.auctions.contains_key(token) &&
+					!self.busy.contains(token)
+			})
+			.copied()
+			.collect();
+		pick(&free, rng).copied()
+	}
+
+	/// Call for `action`, or for a simpler action when nothing fits. `now` is the timestamp
+	/// the next block is expected to have.
+	fn plan(
+		&mut self,
+		who: &AccountId,
+		action: Action,
+		rng: &mut Rng,
+		now: u64,
+		slot_millis: u64,
+	) -> (Action, RuntimeCall) {
+		match action {
+			Action::CreateClass => {
+				let name = format!("Load class {}", self.classes.len()).into_bytes();
+				let call = NftCall::create_class {
+					name,
+					url: b"https://example.com/load".to_vec(),
+					description: b"Class created by the load test".to_vec(),
+					external_url: b"https://example.com".to_vec(),
+				};
+				(Action::CreateClass, call.into())
+			},
+			Action::Mint => {
+				let class_id = match pick(&self.classes, rng) {
+					Some(class_id) => *class_id,
+					None => return self.plan(who, Action::CreateClass, rng, now, slot_millis),
+				};
+				self.minted += 1;
+				let call = NftCall::mint {
+					class_id,
+					name: format!("Load token {}", self.minted).into_bytes(),
+					description: b"Token minted by the load test".to_vec(),
+					url: format!("https://example.com/load/{}", self.minted).into_bytes(),
+					external_url: b"https://example.com".to_vec(),
+					metadata_hash: None,
+					media_hash: None,
+				};
+				(Action::Mint, call.into())
+			},
+			Action::List => {
+				let (class_id, nft_id) = match self.free_token(who, rng) {
+					Some(token) => token,
+					None => return self.plan(who, Action::Mint, rng, now, slot_millis),
+				};
+				self.busy.insert((class_id, nft_id));
+				let amount = random_price(rng);
+				let call =
+					NftMarketPlaceCall::create_offer { class_id, nft_id, amount, expires_at: None };
+				(Action::List, call.into())
+			},
+			Action::Bid => {
+				let running: Vec<_> = self
+					.auctions
+					.iter()
+					.filter(|(token, auction)| {
+						&auction.owner != who &&
+							auction.start <= now && auction.end > now + slot_millis &&
+							!self.busy.contains(token)
+					})
+					.map(|(token, auction)| {
+						(*token, auction.reserve.max(auction.highest + EXISTENTIAL_DEPOSIT))
+					})
+					.collect();
+				if let Some(((class_id, nft_id), amount)) = pick(&running, rng).copied() {
+					self.busy.insert((class_id, nft_id));
+					let call = NftMarketPlaceCall::buy_auction { nft_id, class_id, amount };
+					return (Action::Bid, call.into())
+				}
+				let (class_id, nft_id) = match self.free_token(who, rng) {
+					Some(token) => token,
+					None => return self.plan(who, Action::Mint, rng, now, slot_millis),
+				};
+				self.busy.insert((class_id, nft_id));
+				let call = NftMarketPlaceCall::create_auction {
+					class_id,
+					nft_id,
+					amount: random_price(rng),
+					start_time: now,
+					end_time: now + AUCTION_SLOTS * slot_millis,
+					time_base: TimeBase::UnixMs,
+				};
+				(Action::Bid, call.into())
+			},
+			Action::Buy => {
+				let listings: Vec<_> = self
+					.listings
+					.iter()
+					.filter(|(token, (seller, _))| seller != who && !self.busy.contains(token))
+					.map(|(token, (_, amount))| (*token, *amount))
+					.collect();
+				let ((class_id, nft_id), amount) = match pick(&listings, rng).copied() {
+					Some(listing) => listing,
+					None => return self.plan(who, Action::List, rng, now, slot_millis),
+				};
+				self.busy.insert((class_id, nft_id));
+				(Action::Buy, NftMarketPlaceCall::buy_offer { nft_id, class_id, amount }.into())
+			},
+		}
+	}
+}
+
+fn random_price(rng: &mut Rng) -> Balance {
+	EXISTENTIAL_DEPOSIT * (1 + rng.below(100) as Balance)
+}
+
+fn pick<'a, T>(items: &'a [T], rng: &mut Rng) -> Option<&'a T> {
+	if items.is_empty() {
+		return None
+	}
+	items.get(rng.below(items.len() as u64) as usize)
+}
+
+/// Small xorshift generator, the traffic only has to be varied and repeatable.
+struct Rng(u64);
+
+impl Rng {
+	fn new(seed: u64) -> Self {
+		// xorshift gets stuck on zero
+		Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+	}
+
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	/// Number in `0..n`, `n` must not be zero.
+	fn below(&mut self, n: u64) -> u64 {
+		self.next() % n
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BlockStats {
+	number: BlockNumber,
+	/// Extrinsics of the load test in the block.
+	extrinsics: u32,
+	failed: u32,
+	/// Share of the normal dispatch weight used.
+	fullness: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct ActionStats {
+	sent: u32,
+	rejected: u32,
+	succeeded: u32,
+	failed: u32,
+}
+
+/// Outcome of the load test.
+#[derive(Debug, Default)]
+struct Report {
+	blocks: Vec<BlockStats>,
+	actions: BTreeMap<Action, ActionStats>,
+	/// Number of failed or rejected extrinsics per reason.
+	failures: BTreeMap<String, u32>,
+	elapsed: Duration,
+	slot: Duration,
+}
+
+impl Report {
+	fn sent(&mut self, action: Action) {
+		self.actions.entry(action).or_default().sent += 1;
+	}
+
+	fn rejected(&mut self, action: Action, reason: String) {
+		self.actions.entry(action).or_default().rejected += 1;
+		*self.failures.entry(format!("{}: {}", action.name(), reason)).or_default() += 1;
+	}
+
+	fn succeeded(&mut self, action: Action) {
+		self.actions.entry(action).or_default().succeeded += 1;
+	}
+
+	fn failed(&mut self, action: Action, reason: String) {
+		self.actions.entry(action).or_default().failed += 1;
+		*self.failures.entry(format!("{}: {}", action.name(), reason)).or_default() += 1;
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let total =
+			|field: fn(&ActionStats) -> u32| -> u32 { self.actions.values().map(field).sum() };
+		let succeeded = total(|stats| stats.succeeded);
+		let included: u32 = self.blocks.iter().map(|block| block.extrinsics).sum();
+		let blocks = self.blocks.len().max(1) as f64;
+		let fullness = self.blocks.iter().map(|block| block.fullness).sum::<f64>() / blocks;
+		let max_fullness = self.blocks.iter().map(|block| block.fullness).fold(0.0, f64::max);
+
+		writeln!(
+			f,
+			"Load test: {} blocks in {:.1}s",
+			self.blocks.len(),
+			self.elapsed.as_secs_f64()
+		)?;
+		writeln!(
+			f,
+			"  extrinsics: {} sent, {} rejected by the pool, {} included, {} succeeded, {} failed",
+			total(|stats| stats.sent),
+			total(|stats| stats.rejected),
+			included,
+			succeeded,
+			total(|stats| stats.failed),
+		)?;
+		writeln!(
+			f,
+			"  throughput: {:.1} tx/s wall clock, {:.1} tx/block, {:.1} tx/s at one block every {}s",
+			succeeded as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON),
+			included as f64 / blocks,
+			included as f64 / blocks / self.slot.as_secs_f64().max(f64::EPSILON),
+			self.slot.as_secs(),
+		)?;
+		writeln!(
+			f,
+			"  block fullness: {:.1}% on average, {:.1}% at most (placeholder pallet weights)",
+			fullness * 100.0,
+			max_fullness * 100.0
+		)?;
+		writeln!(
+			f,
+			"  {:<14}{:>9}{:>10}{:>11}{:>8}",
+			"kind", "sent", "rejected", "succeeded", "failed"
+		)?;
+		for (action, stats) in &self.actions {
+			writeln!(
+				f,
+				"  {:<14}{:>9}{:>10}{:>11}{:>8}",
+				action.name(),
+				stats.sent,
+				stats.rejected,
+				stats.succeeded,
+				stats.failed
+			)?;
+		}
+		if !self.failures.is_empty() {
+			writeln!(f, "  failure reasons:")?;
+			let mut failures: Vec<_> = self.failures.iter().collect();
+			failures.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+			for (reason, count) in failures {
+				writeln!(f, "  {:>8}  {}", count, reason)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn account(seed: u8) -> AccountId {
+		AccountId::new([seed; 32])
+	}
+
+	fn market_event(event: pallet_nft_market_place::Event<Runtime>) -> RuntimeEvent {
+		RuntimeEvent::NftMarketPlaceModule(event)
+	}
+
+	fn minted(class_id: Id, nft_id: Id, owner: AccountId) -> RuntimeEvent {
+		RuntimeEvent::NftModule(pallet_nft::Event::Minted {
+			class_id,
+			nft_id,
+			owner,
+			name: vec![],
+			url: vec![],
+			external_url: vec![],
+			description: vec![],
+			metadata_hash: None,
+			media_hash: None,
+		})
+	}
+
+	#[test]
+	fn parses_traffic_mix() {
+		let mix: TrafficMix = "mint=3, buy=1,create_class=0".parse().unwrap();
+		assert_eq!(
+			mix,
+			TrafficMix(vec![(Action::Mint, 3), (Action::Buy, 1), (Action::CreateClass, 0)])
+		);
+		assert!("mint".parse::<TrafficMix>().is_err());
+		assert!("burn=1".parse::<TrafficMix>().is_err());
+		assert!("mint=1,mint=2".parse::<TrafficMix>().is_err());
+		assert!("mint=0".parse::<TrafficMix>().is_err());
+	}
+
+	#[test]
+	fn mix_never_picks_a_kind_without_weight() {
+		let mix: TrafficMix = "mint=0,buy=1,list=0".parse().unwrap();
+		let mut rng = Rng::new(7);
+		assert!((0..1_000).all(|_| mix.pick(&mut rng) == Action::Buy));
+	}
+
+	#[test]
+	fn actions_fall_back_until_something_fits() {
+		let (alice, bob) = (account(1), account(2));
+		let mut market = Market::default();
+		let mut rng = Rng::new(1);
+
+		// nothing exists yet, a buy ends up creating a class
+		assert_eq!(market.plan(&alice, Action::Buy, &mut rng, 0, 6_000).0, Action::CreateClass);
+		market.apply(&RuntimeEvent::NftModule(pallet_nft::Event::ClassCreated {
+			owner: alice.clone(),
+			name: vec![],
+			url: vec![],
+			external_url: vec![],
+			class_id: 0,
+			description: vec![],
+		}));
+		assert_eq!(market.plan(&alice, Action::List, &mut rng, 0, 6_000).0, Action::Mint);
+
+		market.apply(&minted(0, 7, alice.clone()));
+		let (action, call) = market.plan(&alice, Action::List, &mut rng, 0, 6_000);
+		assert_eq!(action, Action::List);
+		assert!(matches!(
+			call,
+			RuntimeCall::NftMarketPlaceModule(NftMarketPlaceCall::create_offer {
+				class_id: 0,
+				nft_id: 7,
+				..
+			})
+		));
+		// the token is taken until the next block
+		assert_eq!(market.plan(&alice, Action::List, &mut rng, 0, 6_000).0, Action::Mint);
+
+		market.busy.clear();
+		market.apply(&market_event(pallet_nft_market_place::Event::OfferCreated {
+			class_id: 0,
+			nft_id: 7,
+			owner: alice.clone(),
+			amount: 1_000,
+		}));
+		// the seller can't buy its own listing
+		assert_eq!(market.plan(&alice, Action::Buy, &mut rng, 0, 6_000).0, Action::Mint);
+		let (action, call) = market.plan(&bob, Action::Buy, &mut rng, 0, 6_000);
+		assert_eq!(action, Action::Buy);
+		assert!(matches!(
+			call,
+			RuntimeCall::NftMarketPlaceModule(NftMarketPlaceCall::buy_offer {
+				class_id: 0,
+				nft_id: 7,
+				amount: 1_000,
+			})
+		));
+	}
+
+	#[test]
+	fn bids_outbid_the_highest_bid_of_running_auctions() {
+		let (alice, bob) = (account(1), account(2));
+		let mut market = Market::default();
+		let mut rng = Rng::new(3);
+		market.apply(&minted(0, 1, alice.clone()));
+		market.apply(&market_event(pallet_nft_market_place::Event::AuctionCreated {
+			class_id: 0,
+			nft_id: 1,
+			owner: alice.clone(),
+			min_amount: 0,
+			amount: 1_000,
+			start_time: 6_000,
+			end_time: 60_000,
+			time_base: TimeBase::UnixMs,
+		}));
+		market.apply(&market_event(pallet_nft_market_place::Event::NewUserAuctioner {
+			nft_id: 1,
+			class_id: 0,
+			auctioner: account(3),
+			amount: 2_000,
+		}));
+
+		let (action, call) = market.plan(&bob, Action::Bid, &mut rng, 12_000, 6_000);
+		assert_eq!(action, Action::Bid);
+		assert!(matches!(
+			call,
+			RuntimeCall::NftMarketPlaceModule(NftMarketPlaceCall::buy_auction {
+				class_id: 0,
+				nft_id: 1,
+				amount,
+			}) if amount == 2_000 + EXISTENTIAL_DEPOSIT
+		));
+
+		// once the auction is over the bidder starts an auction of its own token instead
+		market.busy.clear();
+		market.apply(&minted(0, 2, bob.clone()));
+		let (_, call) = market.plan(&bob, Action::Bid, &mut rng, 60_000, 6_000);
+		assert!(matches!(
+			call,
+			RuntimeCall::NftMarketPlaceModule(NftMarketPlaceCall::create_auction {
+				class_id: 0,
+				nft_id: 2,
+				start_time: 60_000,
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn transfers_move_tokens_and_drop_their_listings() {
+		let (alice, bob) = (account(1), account(2));
+		let mut market = Market::default();
+		market.apply(&minted(0, 1, alice.clone()));
+		market.apply(&market_event(pallet_nft_market_place::Event::OfferCreated {
+			class_id: 0,
+			nft_id: 1,
+			owner: alice.clone(),
+			amount: 1_000,
+		}));
+		market.apply(&market_event(pallet_nft_market_place::Event::TransferNft {
+			from: alice.clone(),
+			to: bob.clone(),
+			nft_id: 1,
+			class_id: 0,
+		}));
+		assert!(market.listings.is_empty());
+		assert_eq!(market.owners.get(&(0, 1)), Some(&bob));
+		assert!(market.tokens[&alice].is_empty());
+		assert!(market.tokens[&bob].contains(&(0, 1)));
+	}
+
+	#[test]
+	fn report_sorts_failure_reasons_by_count() {
+		let mut report = Report::default();
+		report.sent(Action::Buy);
+		report.sent(Action::Buy);
+		report.sent(Action::Mint);
+		report.failed(Action::Buy, "NftMarketPlaceModule::OfferNotFound".into());
+		report.failed(Action::Buy, "NftMarketPlaceModule::OfferNotFound".into());
+		report.rejected(Action::Mint, "pool: Invalid Transaction".into());
+		report
+			.blocks
+			.push(BlockStats { number: 1, extrinsics: 2, failed: 2, fullness: 0.5 });
+		report.elapsed = Duration::from_secs(2);
+		report.slot = Duration::from_secs(6);
+
+		let text = report.to_string();
+		assert!(text.contains("3 sent, 1 rejected by the pool, 2 included, 0 succeeded, 2 failed"));
+		assert!(text.contains("50.0% on average"));
+		let buy = text.find("buy: NftMarketPlaceModule::OfferNotFound").unwrap();
+		let mint = text.find("mint: pool: Invalid Transaction").unwrap();
+		assert!(buy < mint);
+	}
+}
@@ -7,7 +7,12 @@ mod service;
 mod benchmarking;
 mod cli;
 mod command;
+mod indexer;
+mod keystore;
+mod load_test;
 mod rpc;
+mod sealing;
+mod snapshot;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
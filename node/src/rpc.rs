@@ -7,46 +7,90 @@
 
 use std::sync::Arc;
 
+use futures::channel::mpsc;
 use jsonrpsee::RpcModule;
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_template_runtime::{opaque::Block, AccountId, Balance, Hash, Index};
+use sc_client_api::{Backend, BlockchainEvents};
+use sc_consensus_manual_seal::rpc::EngineCommand;
+use sc_rpc::SubscriptionTaskExecutor;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 
+use crate::sealing::DevClock;
+
 pub use sc_rpc_api::DenyUnsafe;
 
+pub mod activity;
+pub mod dev;
+pub mod nft;
+
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, B> {
 	/// The client instance to use.
 	pub client: Arc<C>,
+	/// The backend the client reads its state from.
+	pub backend: Arc<B>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Executor that drives the subscriptions.
+	pub subscription_executor: SubscriptionTaskExecutor,
+	/// Channel of the `engine_*` methods, set when the node runs with `--sealing`.
+	pub command_sink: Option<mpsc::Sender<EngineCommand<Hash>>>,
+	/// Clock of the sealed blocks, set when the node runs with `--sealing`.
+	pub dev_clock: Option<Arc<DevClock>>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(
-	deps: FullDeps<C, P>,
+pub fn create_full<C, P, B>(
+	deps: FullDeps<C, P, B>,
 ) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
 where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: BlockchainEvents<Block>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: pallet_nft_runtime_api::NftApi<Block, AccountId, Balance>,
+	C::Api: pallet_nft_market_place_runtime_api::MarketplaceApi<Block, Balance>,
 	P: TransactionPool + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
 {
+	use activity::{ActivityApiServer, MarketActivity};
+	use dev::{Dev, DevApiServer};
+	use nft::{Market, MarketApiServer, Nft, NftApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
 
 	let mut module = RpcModule::new(());
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps {
+		client,
+		backend,
+		pool,
+		deny_unsafe,
+		subscription_executor,
+		command_sink,
+		dev_clock,
+	} = deps;
 
 	module.merge(System::new(client.clone(), pool.clone(), deny_unsafe).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	module.merge(Nft::new(client.clone()).into_rpc())?;
+	module.merge(Market::new(client.clone()).into_rpc())?;
+	module.merge(MarketActivity::new(client, backend, subscription_executor).into_rpc())?;
+
+	if let Some(command_sink) = command_sink {
+		module.merge(ManualSeal::new(command_sink).into_rpc())?;
+	}
+	if let Some(clock) = dev_clock {
+		module.merge(Dev::new(clock, deny_unsafe).into_rpc())?;
+	}
 
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
@@ -0,0 +1,448 @@
+//! `market_subscribeActivity` subscription.
+//!
+//! Pushes the `pallet_nft` and `pallet_nft_market_place` events of every new best (or finalized)
+//! block to the subscriber, decoded and flattened into [`Activity`] items. The events are read
+//! from `System::Events` and decoded with the native runtime types, so a node has to run a
+//! matching binary after a runtime upgrade that changes the events.
+
+use std::sync::Arc;
+
+use codec::Decode;
+use futures::{future, stream, FutureExt, StreamExt};
+use jsonrpsee::{
+	core::{async_trait, SubscriptionResult},
+	proc_macros::rpc,
+	SubscriptionSink,
+};
+use node_template_runtime::{
+	opaque::Block,
+	pallet_nft::{self, Id},
+	pallet_nft_market_place, AccountId, BlockNumber, Hash, Runtime, RuntimeEvent,
+};
+use sc_client_api::{Backend, BlockchainEvents, StateBackend};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_core::twox_128;
+use sp_runtime::traits::Header as _;
+
+type EventRecord = frame_system::EventRecord<RuntimeEvent, Hash>;
+
+/// What a subscriber wants to be told about, everything when empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ActivityFilter {
+	/// Only activity of this class.
+	pub class_id: Option<Id>,
+	/// Only activity this account takes part in.
+	pub account: Option<AccountId>,
+	/// Wait for blocks to be finalized instead of following the best block.
+	pub finalized: bool,
+}
+
+impl ActivityFilter {
+	fn matches(&self, activity: &Activity) -> bool {
+		self.class_id.map_or(true, |class_id| activity.class_id == class_id)
+			&& self.account.as_ref().map_or(true, |account| activity.event.involves(account))
+	}
+}
+
+/// One marketplace or NFT event of a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Activity {
+	pub block_hash: Hash,
+	pub block_number: BlockNumber,
+	/// Position of the event in the events of the block.
+	pub event_index: u32,
+	pub class_id: Id,
+	pub nft_id: Id,
+	#[serde(flatten)]
+	pub event: ActivityEvent,
+}
+
+/// Decoded event, balances are strings so they don't lose precision in JavaScript.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ActivityEvent {
+	Minted {
+		owner: AccountId,
+	},
+	Transferred {
+		from: AccountId,
+		to: AccountId,
+	},
+	Burned {
+		owner: AccountId,
+	},
+	Listed {
+		seller: AccountId,
+		price: String,
+	},
+	ListingCanceled,
+	ListingExpired {
+		seller: AccountId,
+	},
+	Sold {
+		seller: AccountId,
+		buyer: AccountId,
+		price: String,
+	},
+	#[serde(rename_all = "camelCase")]
+	AuctionCreated {
+		seller: AccountId,
+		reserve_price: String,
+		start_time: u64,
+		end_time: u64,
+		time_base: pallet_nft_market_place::TimeBase,
+	},
+	Bid {
+		bidder: AccountId,
+		amount: String,
+	},
+	AuctionFinished {
+		winner: AccountId,
+		price: String,
+	},
+	AuctionCanceled,
+	#[serde(rename_all = "camelCase")]
+	OfferMade {
+		buyer: AccountId,
+		amount: String,
+		expires_at: Option<u64>,
+	},
+}
+
+impl ActivityEvent {
+	/// Turns a runtime event into (class id, nft id, event), `None` for events that are not
+	/// marketplace activity.
+	pub fn from_runtime_event(event: RuntimeEvent) -> Option<(Id, Id, Self)> {
+		use pallet_nft::Event as Nft;
+		use pallet_nft_market_place::Event as Market;
+
+		let activity = match event {
+			RuntimeEvent::NftModule(event) => match event {
+				Nft::<Runtime>::Minted { class_id, nft_id, owner, .. } => {
+					(class_id, nft_id, Self::Minted { owner })
+				},
+				Nft::TransferNft { from, to, nft_id, class_id } => {
+					(class_id, nft_id, Self::Transferred { from, to })
+				},
+				Nft::Burned { nft_id, class_id, owner } => {
+					(class_id, nft_id, Self::Burned { owner })
+				},
+				_ => return None,
+			},
+			RuntimeEvent::NftMarketPlaceModule(event) => match event {
+				Market::<Runtime>::OfferCreated { class_id, nft_id, owner, amount } => {
+					(class_id, nft_id, Self::Listed { seller: owner, price: amount.to_string() })
+				},
+				Market::OfferCanceled { class_id, nft_id } => {
+					(class_id, nft_id, Self::ListingCanceled)
+				},
+				Market::OfferExpired { class_id, nft_id, owner } => {
+					(class_id, nft_id, Self::ListingExpired { seller: owner })
+				},
+				Market::OfferBuyed { buyer, sender, amount, nft_id, class_id } => (
+					class_id,
+					nft_id,
+					Self::Sold { seller: sender, buyer, price: amount.to_string() },
+				),
+				Market::BatchItemBought { class_id, nft_id, seller, buyer, price } => {
+					(class_id, nft_id, Self::Sold { seller, buyer, price: price.to_string() })
+				},
+				Market::BuyerOfferAccepted { class_id, nft_id, seller, buyer, amount } => {
+					(class_id, nft_id, Self::Sold { seller, buyer, price: amount.to_string() })
+				},
+				Market::AuctionCreated {
+					class_id,
+					nft_id,
+					owner,
+					amount,
+					start_time,
+					end_time,
+					time_base,
+					..
+				} => (
+					class_id,
+					nft_id,
+					Self::AuctionCreated {
+						seller: owner,
+						reserve_price: amount.to_string(),
+						start_time,
+						end_time,
+						time_base,
+					},
+				),
+				Market::NewUserAuctioner { nft_id, class_id, auctioner, amount } => {
+					(class_id, nft_id, Self::Bid { bidder: auctioner, amount: amount.to_string() })
+				},
+				Market::AuctionFinished { nft_id, class_id, spender, amount } => (
+					class_id,
+					nft_id,
+					Self::AuctionFinished { winner: spender, price: amount.to_string() },
+				),
+				Market::AuctionCanceled { class_id, nft_id } => {
+					(class_id, nft_id, Self::AuctionCanceled)
+				},
+				Market::BuyerOfferCreated { class_id, nft_id, buyer, amount, expires_at } => (
+					class_id,
+					nft_id,
+					Self::OfferMade { buyer, amount: amount.to_string(), expires_at },
+				),
+				Market::TransferNft { from, to, nft_id, class_id } => {
+					(class_id, nft_id, Self::Transferred { from, to })
+				},
+				_ => return None,
+			},
+			_ => return None,
+		};
+		Some(activity)
+	}
+
+	/// Whether `account` takes part in the event, cancellations name nobody.
+	pub fn involves(&self, account: &AccountId) -> bool {
+		match self {
+			Self::Minted { owner } | Self::Burned { owner } => owner == account,
+			Self::Transferred { from, to } => from == account || to == account,
+			Self::Listed { seller, .. }
+			| Self::ListingExpired { seller }
+			| Self::AuctionCreated { seller, .. } => seller == account,
+			Self::Sold { seller, buyer, .. } => seller == account || buyer == account,
+			Self::Bid { bidder, .. } => bidder == account,
+			Self::AuctionFinished { winner, .. } => winner == account,
+			Self::OfferMade { buyer, .. } => buyer == account,
+			Self::ListingCanceled | Self::AuctionCanceled => false,
+		}
+	}
+}
+
+#[rpc(server)]
+pub trait ActivityApi {
+	/// Marketplace activity (listings, bids, sales, transfers, burns) of new blocks.
+	#[subscription(
+		name = "market_subscribeActivity" => "market_activity",
+		unsubscribe = "market_unsubscribeActivity",
+		item = Activity,
+	)]
+	fn subscribe_activity(&self, filter: Option<ActivityFilter>);
+}
+
+/// Implements [`ActivityApiServer`].
+pub struct MarketActivity<C, B> {
+	client: Arc<C>,
+	backend: Arc<B>,
+	executor: SubscriptionTaskExecutor,
+}
+
+impl<C, B> MarketActivity<C, B> {
+	/// Creates a new [`Self`], subscriptions are driven by `executor`.
+	pub fn new(client: Arc<C>, backend: Arc<B>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, backend, executor }
+	}
+}
+
+#[async_trait]
+impl<C, B> ActivityApiServer for MarketActivity<C, B>
+where
+	C: BlockchainEvents<Block> + Send + Sync + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	fn subscribe_activity(
+		&self,
+		mut sink: SubscriptionSink,
+		filter: Option<ActivityFilter>,
+	) -> SubscriptionResult {
+		let filter = filter.unwrap_or_default();
+		// without `finalized` only the new best blocks are followed, so blocks of a fork that
+		// never becomes best are not reported
+		let blocks = if filter.finalized {
+			self.client
+				.finality_notification_stream()
+				.map(|notification| (notification.hash, *notification.header.number()))
+				.boxed()
+		} else {
+			self.client
+				.import_notification_stream()
+				.filter(|notification| future::ready(notification.is_new_best))
+				.map(|notification| (notification.hash, *notification.header.number()))
+				.boxed()
+		};
+		let backend = self.backend.clone();
+		let activity = blocks.flat_map(move |(hash, number)| {
+			stream::iter(block_activity(&events(&*backend, hash), hash, number, &filter))
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(activity).await;
+		};
+		self.executor.spawn("market-activity-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
+
+/// Events of a block, nothing when they can't be read or decoded.
+pub(crate) fn events<B: Backend<Block>>(backend: &B, hash: Hash) -> Vec<EventRecord> {
+	let key = [twox_128(b"System"), twox_128(b"Events")].concat();
+	let raw = backend
+		.state_at(hash)
+		.map_err(|e| e.to_string())
+		.and_then(|state| state.storage(&key).map_err(|e| e.to_string()));
+	match raw {
+		Ok(Some(raw)) => Vec::<EventRecord>::decode(&mut &raw[..]).unwrap_or_else(|e| {
+			log::warn!(target: "rpc", "Unable to decode the events of block {:?}: {}", hash, e);
+			Vec::new()
+		}),
+		Ok(None) => Vec::new(),
+		Err(e) => {
+			log::warn!(target: "rpc", "Unable to read the events of block {:?}: {}", hash, e);
+			Vec::new()
+		},
+	}
+}
+
+/// Activity of a block that passes `filter`.
+pub(crate) fn block_activity(
+	events: &[EventRecord],
+	block_hash: Hash,
+	block_number: BlockNumber,
+	filter: &ActivityFilter,
+) -> Vec<Activity> {
+	events
+		.iter()
+		.enumerate()
+		.filter_map(|(index, record)| {
+			let (class_id, nft_id, event) =
+				ActivityEvent::from_runtime_event(record.event.clone())?;
+			Some(Activity {
+				block_hash,
+				block_number,
+				event_index: index as u32,
+				class_id,
+				nft_id,
+				event,
+			})
+		})
+		.filter(|activity| filter.matches(activity))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_system::Phase;
+	use serde_json::json;
+	use sp_keyring::AccountKeyring;
+
+	fn alice() -> AccountId {
+		AccountKeyring::Alice.to_account_id()
+	}
+
+	fn bob() -> AccountId {
+		AccountKeyring::Bob.to_account_id()
+	}
+
+	fn record(event: RuntimeEvent) -> EventRecord {
+		EventRecord { phase: Phase::ApplyExtrinsic(1), event, topics: Vec::new() }
+	}
+
+	/// A sale of token 0 of class 1 from Alice to Bob, then a mint of class 2 by Bob.
+	fn events() -> Vec<EventRecord> {
+		vec![
+			record(frame_system::Event::<Runtime>::CodeUpdated.into()),
+			record(
+				pallet_nft_market_place::Event::<Runtime>::TransferNft {
+					from: alice(),
+					to: bob(),
+					nft_id: 0,
+					class_id: 1,
+				}
+				.into(),
+			),
+			record(
+				pallet_nft_market_place::Event::<Runtime>::OfferBuyed {
+					buyer: bob(),
+					sender: alice(),
+					amount: 1_000_000_000_000_000_000_000,
+					nft_id: 0,
+					class_id: 1,
+				}
+				.into(),
+			),
+			record(
+				pallet_nft::Event::<Runtime>::Minted {
+					class_id: 2,
+					nft_id: 7,
+					owner: bob(),
+					name: b"name".to_vec(),
+					url: Vec::new(),
+					external_url: Vec::new(),
+					description: Vec::new(),
+					metadata_hash: None,
+					media_hash: None,
+				}
+				.into(),
+			),
+			record(
+				pallet_nft_market_place::Event::<Runtime>::OfferCanceled { class_id: 2, nft_id: 7 }
+					.into(),
+			),
+		]
+	}
+
+	fn kinds(activity: &[Activity]) -> Vec<(u32, Id, Id)> {
+		activity.iter().map(|a| (a.event_index, a.class_id, a.nft_id)).collect()
+	}
+
+	#[test]
+	fn only_nft_and_market_events_are_reported() {
+		let activity = block_activity(&events(), Hash::default(), 5, &ActivityFilter::default());
+		assert_eq!(kinds(&activity), vec![(1, 1, 0), (2, 1, 0), (3, 2, 7), (4, 2, 7)]);
+		assert_eq!(
+			activity[1].event,
+			ActivityEvent::Sold {
+				seller: alice(),
+				buyer: bob(),
+				price: "1000000000000000000000".into()
+			}
+		);
+	}
+
+	#[test]
+	fn activity_is_filtered_by_class_and_account() {
+		let by_class = ActivityFilter { class_id: Some(2), ..Default::default() };
+		let activity = block_activity(&events(), Hash::default(), 5, &by_class);
+		assert_eq!(kinds(&activity), vec![(3, 2, 7), (4, 2, 7)]);
+
+		// the cancellation names nobody so it is left out
+		let by_account = ActivityFilter { account: Some(bob()), ..Default::default() };
+		let activity = block_activity(&events(), Hash::default(), 5, &by_account);
+		assert_eq!(kinds(&activity), vec![(1, 1, 0), (2, 1, 0), (3, 2, 7)]);
+
+		let both = ActivityFilter { class_id: Some(1), account: Some(alice()), finalized: true };
+		let activity = block_activity(&events(), Hash::default(), 5, &both);
+		assert_eq!(kinds(&activity), vec![(1, 1, 0), (2, 1, 0)]);
+	}
+
+	#[test]
+	fn activity_is_flat_json() {
+		let activity = block_activity(&events(), Hash::default(), 5, &ActivityFilter::default());
+		assert_eq!(
+			serde_json::to_value(&activity[1]).unwrap(),
+			json!({
+				"blockHash": Hash::default(),
+				"blockNumber": 5,
+				"eventIndex": 2,
+				"classId": 1,
+				"nftId": 0,
+				"type": "sold",
+				"seller": alice(),
+				"buyer": bob(),
+				"price": "1000000000000000000000",
+			})
+		);
+		assert_eq!(serde_json::to_value(&activity[3]).unwrap()["type"], json!("listingCanceled"));
+
+		let filter: ActivityFilter = serde_json::from_value(json!({ "classId": 3 })).unwrap();
+		assert_eq!(filter, ActivityFilter { class_id: Some(3), ..Default::default() });
+	}
+}
@@ -0,0 +1,75 @@
+//! `dev_*` RPC methods of a node started with `--sealing`.
+
+use std::sync::Arc;
+
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorCode, ErrorObject},
+};
+use sc_rpc_api::DenyUnsafe;
+
+use crate::sealing::DevClock;
+
+#[rpc(server)]
+pub trait DevApi {
+	/// Timestamp of the next sealed block in milliseconds, it must be at least one slot after
+	/// the last block.
+	#[method(name = "dev_setNextBlockTimestamp")]
+	fn set_next_block_timestamp(&self, timestamp: u64) -> RpcResult<()>;
+
+	/// Earliest timestamp the next sealed block can have, in milliseconds.
+	#[method(name = "dev_earliestNextBlockTimestamp")]
+	fn earliest_next_block_timestamp(&self) -> RpcResult<u64>;
+}
+
+/// Implements [`DevApiServer`].
+pub struct Dev {
+	clock: Arc<DevClock>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl Dev {
+	/// Creates a new [`Self`] from the clock of the sealed blocks.
+	pub fn new(clock: Arc<DevClock>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { clock, deny_unsafe }
+	}
+}
+
+#[async_trait]
+impl DevApiServer for Dev {
+	fn set_next_block_timestamp(&self, timestamp: u64) -> RpcResult<()> {
+		self.deny_unsafe.check_if_safe()?;
+		self.clock.set_next(timestamp).map_err(|e| {
+			CallError::Custom(ErrorObject::owned(
+				ErrorCode::InvalidParams.code(),
+				e,
+				Some(self.clock.earliest_next()),
+			))
+			.into()
+		})
+	}
+
+	fn earliest_next_block_timestamp(&self) -> RpcResult<u64> {
+		Ok(self.clock.earliest_next())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rejects_timestamps_before_the_next_slot() {
+		let dev = Dev::new(Arc::new(DevClock::new(6_000, 12_000, false)), DenyUnsafe::No);
+		assert_eq!(dev.earliest_next_block_timestamp().unwrap(), 18_000);
+		assert!(dev.set_next_block_timestamp(17_999).is_err());
+		dev.set_next_block_timestamp(18_000).unwrap();
+	}
+
+	#[test]
+	fn setting_the_timestamp_is_unsafe() {
+		let dev = Dev::new(Arc::new(DevClock::new(6_000, 0, false)), DenyUnsafe::Yes);
+		assert!(dev.set_next_block_timestamp(60_000).is_err());
+	}
+}
@@ -0,0 +1,816 @@
+//! `nft_*` and `market_*` RPC methods.
+//!
+//! Thin wrappers around the `NftApi` and `MarketplaceApi` runtime APIs that turn their SCALE
+//! types into JSON a front end can use directly: metadata is decoded to UTF-8 strings (or hex
+//! when it is not valid UTF-8), balances are strings so they don't lose precision in
+//! JavaScript, and list methods are paginated with a cursor.
+
+use std::sync::Arc;
+
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorCode, ErrorObject},
+};
+use node_template_runtime::{
+	opaque::Block,
+	pallet_nft::{Class, ContentHash, ContentKind, Id, Nft},
+	pallet_nft_market_place::{Auction, Offer, TimeBase},
+	AccountId, Balance, Hash,
+};
+use pallet_nft_market_place_runtime_api::MarketplaceApi as MarketplaceRuntimeApi;
+use pallet_nft_runtime_api::NftApi as NftRuntimeApi;
+use serde::{Deserialize, Serialize};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::generic::BlockId;
+
+/// Largest page returned by the paginated methods.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Error code of a failed runtime API call.
+const RUNTIME_ERROR: i32 = 1;
+
+/// Error code of a method the runtime at the queried block doesn't implement yet.
+const UNSUPPORTED_ERROR: i32 = 2;
+
+/// Version of the `MarketplaceApi` converting between blocks and unix time.
+const TIME_CONVERSION_VERSION: u32 = 2;
+
+/// A token with its metadata decoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftInfo {
+	pub class_id: Id,
+	pub nft_id: Id,
+	pub owner: AccountId,
+	pub name: String,
+	pub description: String,
+	pub url: String,
+	pub external_url: String,
+	/// Multihash of the metadata document as `0x` hex.
+	#[serde(default)]
+	pub metadata_hash: Option<String>,
+	/// Multihash of the media file as `0x` hex.
+	#[serde(default)]
+	pub media_hash: Option<String>,
+}
+
+impl NftInfo {
+	pub(crate) fn new(nft_id: Id, nft: Nft<AccountId, Vec<u8>>) -> Self {
+		Self {
+			class_id: nft.class_id,
+			nft_id,
+			owner: nft.owner,
+			name: bytes_to_string(nft.name),
+			description: bytes_to_string(nft.description),
+			url: bytes_to_string(nft.url),
+			external_url: bytes_to_string(nft.external_url),
+			metadata_hash: nft.metadata_hash.as_ref().map(multihash_to_string),
+			media_hash: nft.media_hash.as_ref().map(multihash_to_string),
+		}
+	}
+}
+
+/// A class with its metadata decoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassInfo {
+	pub class_id: Id,
+	pub owner: AccountId,
+	pub name: String,
+	pub description: String,
+	pub url: String,
+	pub external_url: String,
+}
+
+impl ClassInfo {
+	pub(crate) fn new(class_id: Id, class: Class<AccountId, Vec<u8>>) -> Self {
+		Self {
+			class_id,
+			owner: class.owner,
+			name: bytes_to_string(class.name),
+			description: bytes_to_string(class.description),
+			url: bytes_to_string(class.url),
+			external_url: bytes_to_string(class.external_url),
+		}
+	}
+}
+
+/// A fixed price listing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingInfo {
+	pub class_id: Id,
+	pub nft_id: Id,
+	pub seller: AccountId,
+	pub price: String,
+	/// Unix time in milliseconds.
+	pub expires_at: Option<u64>,
+}
+
+impl From<Offer<AccountId, Balance>> for ListingInfo {
+	fn from(offer: Offer<AccountId, Balance>) -> Self {
+		Self {
+			class_id: offer.class_id,
+			nft_id: offer.nft_id,
+			seller: offer.owner,
+			price: offer.amount.to_string(),
+			expires_at: offer.expires_at,
+		}
+	}
+}
+
+/// An auction, the highest bid is `None` until somebody bids.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionInfo {
+	pub class_id: Id,
+	pub nft_id: Id,
+	pub seller: AccountId,
+	pub reserve_price: String,
+	pub highest_bidder: Option<AccountId>,
+	pub highest_bid: Option<String>,
+	/// Unix time in milliseconds or block number, see `time_base`.
+	pub start_time: u64,
+	/// Unix time in milliseconds or block number, see `time_base`.
+	pub end_time: u64,
+	#[serde(default)]
+	pub time_base: TimeBase,
+}
+
+impl From<Auction<AccountId, Balance>> for AuctionInfo {
+	fn from(auction: Auction<AccountId, Balance>) -> Self {
+		let has_bid = auction.spender != auction.owner;
+		Self {
+			class_id: auction.class_id,
+			nft_id: auction.nft_id,
+			reserve_price: auction.amount.to_string(),
+			highest_bidder: has_bid.then(|| auction.spender),
+			highest_bid: has_bid.then(|| auction.min_amount.to_string()),
+			seller: auction.owner,
+			start_time: auction.start_time,
+			end_time: auction.end_time,
+			time_base: auction.time_base,
+		}
+	}
+}
+
+/// An entry of the order book of a class.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingEntry {
+	pub nft_id: Id,
+	pub price: String,
+}
+
+/// Position in the order book of a class, the last entry of the previous page.
+pub type ListingCursor = ListingEntry;
+
+/// One page of a paginated method, `next_cursor` is set when there may be more items.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T, Cursor> {
+	pub items: Vec<T>,
+	pub next_cursor: Option<Cursor>,
+}
+
+impl<T, Cursor> Page<T, Cursor> {
+	fn new(items: Vec<T>, limit: u32, cursor: impl Fn(&T) -> Cursor) -> Self {
+		let next_cursor =
+			if items.len() == limit as usize { items.last().map(cursor) } else { None };
+		Self { items, next_cursor }
+	}
+}
+
+#[rpc(server)]
+pub trait NftApi {
+	/// Current owner of a token.
+	#[method(name = "nft_ownerOf")]
+	fn owner_of(&self, class_id: Id, nft_id: Id, at: Option<Hash>) -> RpcResult<Option<AccountId>>;
+
+	/// Account allowed to use a token without owning it, until its right expires.
+	#[method(name = "nft_userOf")]
+	fn user_of(&self, class_id: Id, nft_id: Id, at: Option<Hash>) -> RpcResult<Option<AccountId>>;
+
+	/// Owner and metadata of a token.
+	#[method(name = "nft_nftInfo")]
+	fn nft_info(&self, class_id: Id, nft_id: Id, at: Option<Hash>) -> RpcResult<Option<NftInfo>>;
+
+	/// Owner and metadata of a class.
+	#[method(name = "nft_classInfo")]
+	fn class_info(&self, class_id: Id, at: Option<Hash>) -> RpcResult<Option<ClassInfo>>;
+
+	/// Tokens of an account in a class, at most `limit` (capped at [`MAX_PAGE_SIZE`]).
+	#[method(name = "nft_tokensOfOwner")]
+	fn tokens_of_owner(
+		&self,
+		account: AccountId,
+		class_id: Id,
+		cursor: Option<Id>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> RpcResult<Page<NftInfo, Id>>;
+
+	/// Whether `data` is the metadata document or media file a token committed to at mint.
+	///
+	/// `null` when the token doesn't exist or has no content hash of that kind.
+	#[method(name = "nft_verifyContent")]
+	fn verify_content(
+		&self,
+		class_id: Id,
+		nft_id: Id,
+		kind: ContentKind,
+		data: Bytes,
+		at: Option<Hash>,
+	) -> RpcResult<Option<bool>>;
+}
+
+#[rpc(server)]
+pub trait MarketApi {
+	/// Fixed price listing of a token.
+	#[method(name = "market_listing")]
+	fn listing(&self, class_id: Id, nft_id: Id, at: Option<Hash>)
+		-> RpcResult<Option<ListingInfo>>;
+
+	/// Auction of a token.
+	#[method(name = "market_auction")]
+	fn auction(&self, class_id: Id, nft_id: Id, at: Option<Hash>)
+		-> RpcResult<Option<AuctionInfo>>;
+
+	/// Cheapest listed price of a class.
+	#[method(name = "market_floorPrice")]
+	fn floor_price(&self, class_id: Id, at: Option<Hash>) -> RpcResult<Option<String>>;
+
+	/// Order book of a class from the cheapest, at most `limit` (capped at [`MAX_PAGE_SIZE`]).
+	#[method(name = "market_listings")]
+	fn listings(
+		&self,
+		class_id: Id,
+		cursor: Option<ListingCursor>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> RpcResult<Page<ListingEntry, ListingCursor>>;
+
+	/// Unix time in milliseconds at which a block is expected, estimated with the block time.
+	///
+	/// Fails on blocks of runtimes older than version 2 of the `MarketplaceApi`.
+	#[method(name = "market_blockToUnixMs")]
+	fn block_to_unix_ms(&self, block: u64, at: Option<Hash>) -> RpcResult<u64>;
+
+	/// First block expected at or after a unix time in milliseconds.
+	///
+	/// Fails on blocks of runtimes older than version 2 of the `MarketplaceApi`.
+	#[method(name = "market_unixMsToBlock")]
+	fn unix_ms_to_block(&self, time: u64, at: Option<Hash>) -> RpcResult<u64>;
+}
+
+/// Implements [`NftApiServer`].
+pub struct Nft<C> {
+	client: Arc<C>,
+}
+
+impl<C> Nft<C> {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+/// Implements [`MarketApiServer`].
+pub struct Market<C> {
+	client: Arc<C>,
+}
+
+impl<C> Market<C> {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+#[async_trait]
+impl<C> NftApiServer for Nft<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: NftRuntimeApi<Block, AccountId, Balance>,
+{
+	fn owner_of(&self, class_id: Id, nft_id: Id, at: Option<Hash>) -> RpcResult<Option<AccountId>> {
+		let at = block_id(&*self.client, at);
+		self.client.runtime_api().owner_of(&at, class_id, nft_id).map_err(runtime_error)
+	}
+
+	fn user_of(&self, class_id: Id, nft_id: Id, at: Option<Hash>) -> RpcResult<Option<AccountId>> {
+		let at = block_id(&*self.client, at);
+		self.client.runtime_api().user_of(&at, class_id, nft_id).map_err(runtime_error)
+	}
+
+	fn nft_info(&self, class_id: Id, nft_id: Id, at: Option<Hash>) -> RpcResult<Option<NftInfo>> {
+		let at = block_id(&*self.client, at);
+		let nft = self
+			.client
+			.runtime_api()
+			.nft_info(&at, class_id, nft_id)
+			.map_err(runtime_error)?;
+		Ok(nft.map(|nft| NftInfo::new(nft_id, nft)))
+	}
+
+	fn class_info(&self, class_id: Id, at: Option<Hash>) -> RpcResult<Option<ClassInfo>> {
+		let at = block_id(&*self.client, at);
+		let class = self.client.runtime_api().class_info(&at, class_id).map_err(runtime_error)?;
+		Ok(class.map(|class| ClassInfo::new(class_id, class)))
+	}
+
+	fn tokens_of_owner(
+		&self,
+		account: AccountId,
+		class_id: Id,
+		cursor: Option<Id>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> RpcResult<Page<NftInfo, Id>> {
+		let at = block_id(&*self.client, at);
+		let limit = page_size(limit);
+		let tokens = self
+			.client
+			.runtime_api()
+			.tokens_of_owner(&at, account, class_id, cursor, limit)
+			.map_err(runtime_error)?;
+		let items = tokens.into_iter().map(|(nft_id, nft)| NftInfo::new(nft_id, nft)).collect();
+		Ok(Page::new(items, limit, |nft: &NftInfo| nft.nft_id))
+	}
+
+	fn verify_content(
+		&self,
+		class_id: Id,
+		nft_id: Id,
+		kind: ContentKind,
+		data: Bytes,
+		at: Option<Hash>,
+	) -> RpcResult<Option<bool>> {
+		let at = block_id(&*self.client, at);
+		self.client
+			.runtime_api()
+			.verify_content(&at, class_id, nft_id, kind, data.0)
+			.map_err(runtime_error)
+	}
+}
+
+#[async_trait]
+impl<C> MarketApiServer for Market<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: NftRuntimeApi<Block, AccountId, Balance>,
+	C::Api: MarketplaceRuntimeApi<Block, Balance>,
+{
+	fn listing(
+		&self,
+		class_id: Id,
+		nft_id: Id,
+		at: Option<Hash>,
+	) -> RpcResult<Option<ListingInfo>> {
+		let at = block_id(&*self.client, at);
+		let offer = self
+			.client
+			.runtime_api()
+			.listing_of(&at, class_id, nft_id)
+			.map_err(runtime_error)?;
+		Ok(offer.map(Into::into))
+	}
+
+	fn auction(
+		&self,
+		class_id: Id,
+		nft_id: Id,
+		at: Option<Hash>,
+	) -> RpcResult<Option<AuctionInfo>> {
+		let at = block_id(&*self.client, at);
+		let auction = self
+			.client
+			.runtime_api()
+			.auction_of(&at, class_id, nft_id)
+			.map_err(runtime_error)?;
+		Ok(auction.map(Into::into))
+	}
+
+	fn floor_price(&self, class_id: Id, at: Option<Hash>) -> RpcResult<Option<String>> {
+		let at = block_id(&*self.client, at);
+		let price = self.client.runtime_api().floor_price(&at, class_id).map_err(runtime_error)?;
+		Ok(price.map(|price| price.to_string()))
+	}
+
+	fn listings(
+		&self,
+		class_id: Id,
+		cursor: Option<ListingCursor>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> RpcResult<Page<ListingEntry, ListingCursor>> {
+		let at = block_id(&*self.client, at);
+		let limit = page_size(limit);
+		let cursor = cursor
+			.map(|cursor| Ok::<_, JsonRpseeError>((parse_balance(&cursor.price)?, cursor.nft_id)))
+			.transpose()?;
+		let listings = self
+			.client
+			.runtime_api()
+			.listings(&at, class_id, limit, cursor)
+			.map_err(runtime_error)?;
+		let items = listings
+			.into_iter()
+			.map(|(price, nft_id)| ListingEntry { nft_id, price: price.to_string() })
+			.collect();
+		Ok(Page::new(items, limit, Clone::clone))
+	}
+
+	fn block_to_unix_ms(&self, block: u64, at: Option<Hash>) -> RpcResult<u64> {
+		let at = block_id(&*self.client, at);
+		ensure_time_conversion(&*self.client, &at)?;
+		self.client.runtime_api().block_to_unix_ms(&at, block).map_err(runtime_error)
+	}
+
+	fn unix_ms_to_block(&self, time: u64, at: Option<Hash>) -> RpcResult<u64> {
+		let at = block_id(&*self.client, at);
+		ensure_time_conversion(&*self.client, &at)?;
+		self.client.runtime_api().unix_ms_to_block(&at, time).map_err(runtime_error)
+	}
+}
+
+fn block_id<C: HeaderBackend<Block>>(client: &C, at: Option<Hash>) -> BlockId<Block> {
+	BlockId::hash(at.unwrap_or_else(|| client.info().best_hash))
+}
+
+/// Older runtimes don't have the conversions between blocks and unix time of the `MarketplaceApi`.
+fn ensure_time_conversion<C>(client: &C, at: &BlockId<Block>) -> RpcResult<()>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: MarketplaceRuntimeApi<Block, Balance>,
+{
+	let version = client
+		.runtime_api()
+		.api_version::<dyn MarketplaceRuntimeApi<Block, Balance>>(at)
+		.map_err(runtime_error)?
+		.unwrap_or(0);
+	if version >= TIME_CONVERSION_VERSION {
+		return Ok(())
+	}
+	Err(CallError::Custom(ErrorObject::owned(
+		UNSUPPORTED_ERROR,
+		"The runtime at this block can't convert between blocks and unix time.",
+		Some(format!(
+			"MarketplaceApi version {}, the conversions need version {}",
+			version, TIME_CONVERSION_VERSION
+		)),
+	))
+	.into())
+}
+
+fn page_size(limit: Option<u32>) -> u32 {
+	limit.unwrap_or(MAX_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
+/// Content hashes are shown as the hex of their multihash, the form IPFS tools print them in.
+fn multihash_to_string(hash: &ContentHash) -> String {
+	sp_core::bytes::to_hex(&hash.to_multihash(), false)
+}
+
+/// Metadata is free-form bytes, show it as text when it is UTF-8 and as hex otherwise.
+fn bytes_to_string(bytes: Vec<u8>) -> String {
+	String::from_utf8(bytes).unwrap_or_else(|e| sp_core::bytes::to_hex(e.as_bytes(), false))
+}
+
+fn parse_balance(value: &str) -> Result<Balance, JsonRpseeError> {
+	value.parse().map_err(|_| {
+		CallError::Custom(ErrorObject::owned(
+			ErrorCode::InvalidParams.code(),
+			"Balance must be a decimal number in a string.",
+			Some(value),
+		))
+		.into()
+	})
+}
+
+fn runtime_error(e: impl std::fmt::Display) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(
+		RUNTIME_ERROR,
+		"Unable to query the runtime.",
+		Some(e.to_string()),
+	))
+	.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use node_template_runtime::{opaque::Header, BlockNumber};
+	use sp_api::ApiRef;
+	use sp_blockchain::{BlockStatus, Info};
+	use sp_keyring::AccountKeyring;
+
+	/// Price that does not fit in a JavaScript number.
+	const BIG_PRICE: Balance = 1_000_000_000_000_000_000_000;
+
+	fn alice() -> AccountId {
+		AccountKeyring::Alice.to_account_id()
+	}
+
+	fn bob() -> AccountId {
+		AccountKeyring::Bob.to_account_id()
+	}
+
+	fn nft(nft_id: Id) -> Nft<AccountId, Vec<u8>> {
+		Nft {
+			owner: alice(),
+			description: b"a punk".to_vec(),
+			name: format!("Punk #{}", nft_id).into_bytes(),
+			url: vec![0xff, 0x00],
+			external_url: b"https://punks.example".to_vec(),
+			class_id: 0,
+			metadata_hash: None,
+			media_hash: Some(ContentHash::Blake2_256(sp_core::blake2_256(PUNK))),
+		}
+	}
+
+	/// Media file of every punk.
+	const PUNK: &[u8] = b"punk.png";
+
+	/// Book of class 0 as (price, nft id), cheapest first.
+	const BOOK: [(Balance, Id); 3] = [(100, 2), (200, 3), (300, 4)];
+
+	#[derive(Clone)]
+	struct TestClient;
+
+	struct RuntimeApi;
+
+	impl ProvideRuntimeApi<Block> for TestClient {
+		type Api = RuntimeApi;
+
+		fn runtime_api(&self) -> ApiRef<'_, Self::Api> {
+			RuntimeApi.into()
+		}
+	}
+
+	impl HeaderBackend<Block> for TestClient {
+		fn header(&self, _id: BlockId<Block>) -> sp_blockchain::Result<Option<Header>> {
+			Ok(None)
+		}
+
+		fn info(&self) -> Info<Block> {
+			Info {
+				best_hash: Hash::default(),
+				best_number: 0,
+				genesis_hash: Hash::default(),
+				finalized_hash: Hash::default(),
+				finalized_number: 0,
+				finalized_state: None,
+				number_leaves: 1,
+				block_gap: None,
+			}
+		}
+
+		fn status(&self, _id: BlockId<Block>) -> sp_blockchain::Result<BlockStatus> {
+			Ok(BlockStatus::InChain)
+		}
+
+		fn number(&self, _hash: Hash) -> sp_blockchain::Result<Option<BlockNumber>> {
+			Ok(Some(0))
+		}
+
+		fn hash(&self, _number: BlockNumber) -> sp_blockchain::Result<Option<Hash>> {
+			Ok(Some(Hash::default()))
+		}
+	}
+
+	sp_api::mock_impl_runtime_apis! {
+		impl NftRuntimeApi<Block, AccountId, Balance> for RuntimeApi {
+			fn owner_of(class_id: Id, nft_id: Id) -> Option<AccountId> {
+				(class_id == 0 && nft_id < 5).then(alice)
+			}
+
+			fn user_of(class_id: Id, nft_id: Id) -> Option<AccountId> {
+				(class_id == 0 && nft_id == 4).then(bob)
+			}
+
+			fn nft_info(class_id: Id, nft_id: Id) -> Option<Nft<AccountId, Vec<u8>>> {
+				(class_id == 0 && nft_id < 5).then(|| nft(nft_id))
+			}
+
+			fn class_info(class_id: Id) -> Option<Class<AccountId, Vec<u8>>> {
+				(class_id == 0).then(|| Class {
+					owner: alice(),
+					description: b"some punks".to_vec(),
+					name: b"Punks".to_vec(),
+					url: Vec::new(),
+					external_url: Vec::new(),
+				})
+			}
+
+			fn tokens_of_owner(
+				account: AccountId,
+				class_id: Id,
+				cursor: Option<Id>,
+				limit: u32,
+			) -> Vec<(Id, Nft<AccountId, Vec<u8>>)> {
+				if account != alice() || class_id != 0 {
+					return Vec::new()
+				}
+				(cursor.map_or(0, |c| c + 1)..5).take(limit as usize).map(|id| (id, nft(id))).collect()
+			}
+
+			fn listing_of(class_id: Id, nft_id: Id) -> Option<Offer<AccountId, Balance>> {
+				(class_id == 0 && nft_id == 0).then(|| Offer {
+					nft_id,
+					class_id,
+					amount: BIG_PRICE,
+					owner: alice(),
+					expires_at: Some(1_000),
+				})
+			}
+
+			fn auction_of(class_id: Id, nft_id: Id) -> Option<Auction<AccountId, Balance>> {
+				(class_id == 0 && nft_id == 1).then(|| Auction {
+					owner: alice(),
+					nft_id,
+					spender: bob(),
+					class_id,
+					min_amount: 150,
+					amount: 100,
+					start_time: 10,
+					end_time: 20,
+					time_base: TimeBase::Block,
+				})
+			}
+
+			fn verify_content(
+				class_id: Id,
+				nft_id: Id,
+				kind: ContentKind,
+				data: Vec<u8>,
+			) -> Option<bool> {
+				let nft = (class_id == 0 && nft_id < 5).then(|| nft(nft_id))?;
+				nft.content_hash(kind).map(|hash| hash.verify(&data))
+			}
+		}
+
+		impl MarketplaceRuntimeApi<Block, Balance> for RuntimeApi {
+			fn floor_price(class_id: Id) -> Option<Balance> {
+				(class_id == 0).then(|| BOOK[0].0)
+			}
+
+			fn listings(class_id: Id, limit: u32, cursor: Option<(Balance, Id)>) -> Vec<(Balance, Id)> {
+				if class_id != 0 {
+					return Vec::new()
+				}
+				BOOK.into_iter()
+					.filter(|entry| cursor.map_or(true, |cursor| *entry > cursor))
+					.take(limit as usize)
+					.collect()
+			}
+
+			fn block_to_unix_ms(block: u64) -> u64 {
+				block * 6_000
+			}
+
+			fn unix_ms_to_block(time: u64) -> u64 {
+				(time + 5_999) / 6_000
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn nft_info_decodes_metadata() {
+		let rpc = Nft::new(Arc::new(TestClient)).into_rpc();
+
+		let owner: Option<AccountId> = rpc.call("nft_ownerOf", [0, 3]).await.unwrap();
+		assert_eq!(owner, Some(alice()));
+		let user: Option<AccountId> = rpc.call("nft_userOf", [0, 4]).await.unwrap();
+		assert_eq!(user, Some(bob()));
+
+		let info: Option<NftInfo> = rpc.call("nft_nftInfo", [0, 3]).await.unwrap();
+		assert_eq!(
+			info,
+			Some(NftInfo {
+				class_id: 0,
+				nft_id: 3,
+				owner: alice(),
+				name: "Punk #3".into(),
+				description: "a punk".into(),
+				url: "0xff00".into(),
+				external_url: "https://punks.example".into(),
+				metadata_hash: None,
+				media_hash: Some(multihash_to_string(&ContentHash::Blake2_256(
+					sp_core::blake2_256(PUNK)
+				))),
+			})
+		);
+
+		let info: Option<NftInfo> = rpc.call("nft_nftInfo", [1, 3]).await.unwrap();
+		assert_eq!(info, None);
+
+		let class: Option<ClassInfo> = rpc.call("nft_classInfo", [0]).await.unwrap();
+		assert_eq!(class.map(|class| class.name), Some("Punks".to_string()));
+	}
+
+	#[tokio::test]
+	async fn verifies_content_against_the_commitment() {
+		let rpc = Nft::new(Arc::new(TestClient)).into_rpc();
+		let verify = |kind, data: &[u8], class_id| {
+			rpc.call::<_, Option<bool>>(
+				"nft_verifyContent",
+				(class_id, 3, kind, Bytes(data.to_vec())),
+			)
+		};
+
+		assert_eq!(verify(ContentKind::Media, PUNK, 0).await.unwrap(), Some(true));
+		assert_eq!(verify(ContentKind::Media, b"swapped.png", 0).await.unwrap(), Some(false));
+		assert_eq!(verify(ContentKind::Metadata, PUNK, 0).await.unwrap(), None);
+		assert_eq!(verify(ContentKind::Media, PUNK, 1).await.unwrap(), None);
+	}
+
+	#[tokio::test]
+	async fn tokens_of_owner_is_paginated() {
+		let rpc = Nft::new(Arc::new(TestClient)).into_rpc();
+
+		let page: Page<NftInfo, Id> =
+			rpc.call("nft_tokensOfOwner", (alice(), 0, None::<Id>, 3)).await.unwrap();
+		assert_eq!(page.items.iter().map(|nft| nft.nft_id).collect::<Vec<_>>(), vec![0, 1, 2]);
+		assert_eq!(page.next_cursor, Some(2));
+
+		let page: Page<NftInfo, Id> =
+			rpc.call("nft_tokensOfOwner", (alice(), 0, page.next_cursor, 3)).await.unwrap();
+		assert_eq!(page.items.iter().map(|nft| nft.nft_id).collect::<Vec<_>>(), vec![3, 4]);
+		assert_eq!(page.next_cursor, None);
+
+		let page: Page<NftInfo, Id> = rpc
+			.call("nft_tokensOfOwner", (bob(), 0, None::<Id>, None::<u32>))
+			.await
+			.unwrap();
+		assert!(page.items.is_empty());
+	}
+
+	#[tokio::test]
+	async fn market_balances_are_strings() {
+		let rpc = Market::new(Arc::new(TestClient)).into_rpc();
+
+		let listing: Option<ListingInfo> = rpc.call("market_listing", [0, 0]).await.unwrap();
+		assert_eq!(
+			listing,
+			Some(ListingInfo {
+				class_id: 0,
+				nft_id: 0,
+				seller: alice(),
+				price: "1000000000000000000000".into(),
+				expires_at: Some(1_000),
+			})
+		);
+
+		let auction: Option<AuctionInfo> = rpc.call("market_auction", [0, 1]).await.unwrap();
+		let auction = auction.unwrap();
+		assert_eq!(auction.reserve_price, "100");
+		assert_eq!(auction.highest_bid, Some("150".into()));
+		assert_eq!(auction.highest_bidder, Some(bob()));
+		assert_eq!(auction.time_base, TimeBase::Block);
+
+		let floor: Option<String> = rpc.call("market_floorPrice", [0]).await.unwrap();
+		assert_eq!(floor, Some("100".into()));
+		let floor: Option<String> = rpc.call("market_floorPrice", [1]).await.unwrap();
+		assert_eq!(floor, None);
+	}
+
+	#[tokio::test]
+	async fn listings_are_paginated() {
+		let rpc = Market::new(Arc::new(TestClient)).into_rpc();
+
+		let page: Page<ListingEntry, ListingCursor> =
+			rpc.call("market_listings", (0, None::<ListingCursor>, 2)).await.unwrap();
+		assert_eq!(
+			page.items,
+			vec![
+				ListingEntry { nft_id: 2, price: "100".into() },
+				ListingEntry { nft_id: 3, price: "200".into() },
+			]
+		);
+		assert_eq!(page.next_cursor, Some(ListingEntry { nft_id: 3, price: "200".into() }));
+
+		let page: Page<ListingEntry, ListingCursor> =
+			rpc.call("market_listings", (0, page.next_cursor, 2)).await.unwrap();
+		assert_eq!(page.items, vec![ListingEntry { nft_id: 4, price: "300".into() }]);
+		assert_eq!(page.next_cursor, None);
+
+		let bad_cursor = ListingCursor { nft_id: 3, price: "two hundred".into() };
+		let res: Result<Page<ListingEntry, ListingCursor>, _> =
+			rpc.call("market_listings", (0, Some(bad_cursor), 2)).await;
+		assert!(res.is_err());
+	}
+
+	#[tokio::test]
+	async fn converts_between_blocks_and_unix_ms() {
+		let rpc = Market::new(Arc::new(TestClient)).into_rpc();
+
+		let time: u64 = rpc.call("market_blockToUnixMs", [10]).await.unwrap();
+		assert_eq!(time, 60_000);
+		let block: u64 = rpc.call("market_unixMsToBlock", [60_001]).await.unwrap();
+		assert_eq!(block, 11);
+	}
+}
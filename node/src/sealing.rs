@@ -0,0 +1,251 @@
+//! Development sealing: blocks are sealed on demand instead of by Aura and GRANDPA.
+//!
+//! With `--sealing` the node seals a block with `sc-consensus-manual-seal` whenever it gets a
+//! command, finalizing it right away:
+//!
+//! - `instant`: a block for every transaction that enters the pool,
+//! - `manual`: a block for every `engine_createBlock` RPC call,
+//! - `interval`: a block every `--sealing-interval` milliseconds.
+//!
+//! `engine_createBlock` and `engine_finalizeBlock` are available in every mode, and
+//! `dev_setNextBlockTimestamp` sets the timestamp of the next block so that time-dependent
+//! logic such as the end of an auction can be tested without waiting for the wall clock.
+
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::{Stream, StreamExt};
+use node_template_runtime::Hash;
+use sc_consensus::{BlockCheckParams, BlockImport, BlockImportParams, ImportResult};
+use sc_consensus_manual_seal::rpc::EngineCommand;
+use sc_transaction_pool_api::TransactionPool;
+use sp_consensus::CacheKeyId;
+use sp_consensus_aura::SlotDuration;
+use sp_runtime::traits::Block as BlockT;
+
+/// How a development node seals its blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Sealing {
+	/// A block as soon as a transaction enters the pool.
+	Instant,
+	/// A block for every `engine_createBlock` RPC call.
+	Manual,
+	/// A block every `--sealing-interval` milliseconds.
+	Interval,
+}
+
+/// Timestamps of the blocks sealed by a development node.
+///
+/// Aura needs every block to be in a later slot than its parent, so a block is always at least
+/// one slot after the previous one. When following the wall clock it is not earlier than the
+/// current time either. [`DevClock::set_next`] picks the timestamp of the next block only.
+///
+/// The clock only moves once the block is imported, see [`ClockBlockImport`]: when sealing fails
+/// the next attempt gets the same timestamp, or a later one when following the wall clock.
+#[derive(Debug)]
+pub struct DevClock {
+	slot_millis: u64,
+	follow_wall_clock: bool,
+	state: Mutex<ClockState>,
+}
+
+#[derive(Debug)]
+struct ClockState {
+	/// Timestamp of the last sealed block.
+	last: u64,
+	/// Timestamp requested for the next block.
+	next: Option<u64>,
+	/// Timestamp given to the block being sealed.
+	sealing: Option<u64>,
+}
+
+impl DevClock {
+	/// Clock whose last block has the timestamp `last`, in milliseconds.
+	pub fn new(slot_millis: u64, last: u64, follow_wall_clock: bool) -> Self {
+		let state = ClockState { last, next: None, sealing: None };
+		Self { slot_millis, follow_wall_clock, state: Mutex::new(state) }
+	}
+
+	/// Shortest time between two blocks.
+	pub fn slot_duration(&self) -> SlotDuration {
+		SlotDuration::from_millis(self.slot_millis)
+	}
+
+	/// Earliest timestamp the next block can have.
+	pub fn earliest_next(&self) -> u64 {
+		self.state().last.saturating_add(self.slot_millis)
+	}
+
+	/// Give the next block the timestamp `timestamp`, it must be one slot after the last block.
+	pub fn set_next(&self, timestamp: u64) -> Result<(), String> {
+		let mut state = self.state();
+		let earliest = state.last.saturating_add(self.slot_millis);
+		if timestamp < earliest {
+			return Err(format!(
+				"The next block can't be before {}, one slot after the last block",
+				earliest
+			))
+		}
+		state.next = Some(timestamp);
+		Ok(())
+	}
+
+	/// Timestamp of the block being sealed, the clock moves to it once [`DevClock::sealed`].
+	pub fn next_timestamp(&self) -> u64 {
+		let mut state = self.state();
+		let earliest = state.last.saturating_add(self.slot_millis);
+		let timestamp = match state.next {
+			Some(next) => next,
+			None if self.follow_wall_clock => unix_millis().max(earliest),
+			None => earliest,
+		};
+		state.sealing = Some(timestamp);
+		timestamp
+	}
+
+	/// The block given the last [`DevClock::next_timestamp`] is sealed.
+	pub fn sealed(&self) {
+		let mut state = self.state();
+		if let Some(timestamp) = state.sealing.take() {
+			state.last = timestamp;
+			if state.next.map_or(false, |next| next <= timestamp) {
+				state.next = None;
+			}
+		}
+	}
+
+	fn state(&self) -> std::sync::MutexGuard<'_, ClockState> {
+		// the state is consistent after every statement, a panic can't corrupt it
+		self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+pub(crate) fn unix_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or(0)
+}
+
+/// Block import that moves `clock` to the timestamp of every block it imports.
+pub(crate) struct ClockBlockImport<I> {
+	inner: I,
+	clock: Arc<DevClock>,
+}
+
+impl<I> ClockBlockImport<I> {
+	pub(crate) fn new(inner: I, clock: Arc<DevClock>) -> Self {
+		Self { inner, clock }
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, I> BlockImport<B> for ClockBlockImport<I>
+where
+	B: BlockT,
+	I: BlockImport<B> + Send,
+{
+	type Error = I::Error;
+	type Transaction = I::Transaction;
+
+	async fn check_block(
+		&mut self,
+		block: BlockCheckParams<B>,
+	) -> Result<ImportResult, Self::Error> {
+		self.inner.check_block(block).await
+	}
+
+	async fn import_block(
+		&mut self,
+		block: BlockImportParams<B, Self::Transaction>,
+		cache: HashMap<CacheKeyId, Vec<u8>>,
+	) -> Result<ImportResult, Self::Error> {
+		let result = self.inner.import_block(block, cache).await;
+		if let Ok(ImportResult::Imported(_)) = result {
+			self.clock.sealed();
+		}
+		result
+	}
+}
+
+/// A command sealing and finalizing a block on top of the best block.
+pub(crate) fn seal_command(create_empty: bool) -> EngineCommand<Hash> {
+	EngineCommand::SealNewBlock { create_empty, finalize: true, parent_hash: None, sender: None }
+}
+
+/// A command for every transaction imported into `pool`.
+pub(crate) fn instant_commands<P: TransactionPool>(
+	pool: &P,
+) -> impl Stream<Item = EngineCommand<Hash>> + Unpin + Send {
+	pool.import_notification_stream().map(|_| seal_command(false))
+}
+
+/// A command every `interval`.
+pub(crate) fn interval_commands(
+	interval: Duration,
+) -> impl Stream<Item = EngineCommand<Hash>> + Unpin + Send {
+	Box::pin(futures::stream::unfold((), move |()| async move {
+		futures_timer::Delay::new(interval).await;
+		Some((seal_command(true), ()))
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn seal(clock: &DevClock) -> u64 {
+		let timestamp = clock.next_timestamp();
+		clock.sealed();
+		timestamp
+	}
+
+	#[test]
+	fn blocks_are_one_slot_apart_at_least() {
+		let clock = DevClock::new(6_000, 1_000, false);
+		assert_eq!(clock.earliest_next(), 7_000);
+		assert_eq!(seal(&clock), 7_000);
+		assert_eq!(seal(&clock), 13_000);
+		assert_eq!(clock.earliest_next(), 19_000);
+	}
+
+	#[test]
+	fn next_timestamp_applies_to_one_block() {
+		let clock = DevClock::new(6_000, 0, false);
+		assert!(clock.set_next(5_999).is_err());
+		clock.set_next(60_000).unwrap();
+		assert_eq!(seal(&clock), 60_000);
+		assert_eq!(seal(&clock), 66_000);
+	}
+
+	#[test]
+	fn clock_stays_when_sealing_fails() {
+		let clock = DevClock::new(6_000, 0, false);
+		assert_eq!(clock.next_timestamp(), 6_000);
+		assert_eq!(clock.next_timestamp(), 6_000);
+		assert_eq!(clock.earliest_next(), 6_000);
+
+		// the requested timestamp is kept until a block has it
+		clock.set_next(60_000).unwrap();
+		assert_eq!(clock.next_timestamp(), 60_000);
+		assert_eq!(seal(&clock), 60_000);
+		assert_eq!(seal(&clock), 66_000);
+	}
+
+	#[test]
+	fn follows_the_wall_clock() {
+		let clock = DevClock::new(6_000, 0, true);
+		let before = unix_millis();
+		assert!(seal(&clock) >= before);
+
+		// a timestamp set in the future is used as is, the wall clock can't catch up
+		let future = clock.earliest_next() + 3_600_000;
+		clock.set_next(future).unwrap();
+		assert_eq!(seal(&clock), future);
+		assert_eq!(seal(&clock), future + 6_000);
+	}
+}
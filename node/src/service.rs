@@ -1,15 +1,27 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::BlockBackend;
+use codec::Decode;
+use futures::{channel::mpsc, Stream};
+use node_template_runtime::{self, opaque::Block, Hash, RuntimeApi};
+use prometheus_endpoint::Registry;
+use sc_client_api::{BlockBackend, StorageProvider};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_consensus_manual_seal::{
+	consensus::aura::AuraConsensusDataProvider, rpc::EngineCommand, ManualSealParams,
+};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
-use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
-use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorker};
+use sp_blockchain::HeaderBackend;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use sp_core::{storage::StorageKey, twox_128};
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use crate::{
+	keystore::RemoteKeystore,
+	sealing::{self, DevClock, Sealing},
+};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -35,6 +47,7 @@ pub(crate) type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+pub(crate) type FullPool = sc_transaction_pool::FullPool<Block, FullClient>;
 
 pub fn new_partial(
 	config: &Configuration,
@@ -44,7 +57,7 @@ pub fn new_partial(
 		FullBackend,
 		FullSelectChain,
 		sc_consensus::DefaultImportQueue<Block, FullClient>,
-		sc_transaction_pool::FullPool<Block, FullClient>,
+		FullPool,
 		(
 			sc_finality_grandpa::GrandpaBlockImport<
 				FullBackend,
@@ -58,10 +71,6 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	if config.keystore_remote.is_some() {
-		return Err(ServiceError::Other("Remote Keystores are not supported.".into()))
-	}
-
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
@@ -147,11 +156,8 @@ pub fn new_partial(
 	})
 }
 
-fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
-	// FIXME: here would the concrete keystore be built,
-	//        must return a concrete type (NOT `LocalKeystore`) that
-	//        implements `CryptoStore` and `SyncCryptoStore`
-	Err("Remote Keystore not supported.")
+fn remote_keystore(url: &str) -> Result<Arc<RemoteKeystore>, String> {
+	RemoteKeystore::open(url).map(Arc::new)
 }
 
 /// Builds a new service for a full client.
@@ -221,11 +227,19 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 
 	let rpc_extensions_builder = {
 		let client = client.clone();
+		let backend = backend.clone();
 		let pool = transaction_pool.clone();
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				backend: backend.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				subscription_executor,
+				command_sink: None,
+				dev_clock: None,
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
@@ -338,3 +352,189 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	network_starter.start_network();
 	Ok(task_manager)
 }
+
+/// Builds a new service for a development node that seals blocks according to `sealing`
+/// instead of running Aura and GRANDPA.
+pub fn new_dev(
+	config: Configuration,
+	sealing: Sealing,
+	interval: Duration,
+) -> Result<TaskManager, ServiceError> {
+	let sc_service::PartialComponents {
+		client,
+		backend,
+		mut task_manager,
+		mut keystore_container,
+		select_chain,
+		transaction_pool,
+		other: (_, _, mut telemetry),
+		..
+	} = new_partial(&config)?;
+
+	if let Some(url) = &config.keystore_remote {
+		match remote_keystore(url) {
+			Ok(k) => keystore_container.set_remote_keystore(k),
+			Err(e) =>
+				return Err(ServiceError::Other(format!(
+					"Error hooking up remote keystore for {}: {}",
+					url, e
+				))),
+		};
+	}
+
+	// the Aura import queue of `new_partial` would reject the blocks of the other dev nodes
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(client.clone()),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	);
+
+	let (network, system_rpc_tx, tx_handler_controller, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			block_announce_validator_builder: None,
+			warp_sync: None,
+		})?;
+
+	if config.offchain_worker.enabled {
+		sc_service::build_offchain_workers(
+			&config,
+			task_manager.spawn_handle(),
+			client.clone(),
+			network.clone(),
+		);
+	}
+
+	let prometheus_registry = config.prometheus_registry().cloned();
+	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+	let clock = Arc::new(DevClock::new(slot_duration.as_millis(), best_timestamp(&client)?, true));
+	let (command_sink, rpc_commands) = mpsc::channel(1024);
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let backend = backend.clone();
+		let pool = transaction_pool.clone();
+		let clock = clock.clone();
+
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				backend: backend.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				subscription_executor,
+				command_sink: Some(command_sink.clone()),
+				dev_clock: Some(clock.clone()),
+			};
+			crate::rpc::create_full(deps).map_err(Into::into)
+		})
+	};
+
+	let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		network,
+		client: client.clone(),
+		keystore: keystore_container.sync_keystore(),
+		task_manager: &mut task_manager,
+		transaction_pool: transaction_pool.clone(),
+		rpc_builder: rpc_extensions_builder,
+		backend,
+		system_rpc_tx,
+		tx_handler_controller,
+		config,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	// `engine_createBlock` works in every mode
+	let commands: Pin<Box<dyn Stream<Item = EngineCommand<Hash>> + Send>> = match sealing {
+		Sealing::Instant => Box::pin(futures::stream::select(
+			rpc_commands,
+			sealing::instant_commands(&*transaction_pool),
+		)),
+		Sealing::Manual => Box::pin(rpc_commands),
+		Sealing::Interval =>
+			Box::pin(futures::stream::select(rpc_commands, sealing::interval_commands(interval))),
+	};
+	start_dev_sealing(
+		&task_manager,
+		client,
+		transaction_pool,
+		select_chain,
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|x| x.handle()),
+		clock,
+		commands,
+	);
+
+	network_starter.start_network();
+	Ok(task_manager)
+}
+
+/// Seal a block for every command of `commands_stream`, with the timestamps of `clock`.
+pub(crate) fn start_dev_sealing<CS>(
+	task_manager: &TaskManager,
+	client: Arc<FullClient>,
+	transaction_pool: Arc<FullPool>,
+	select_chain: FullSelectChain,
+	prometheus_registry: Option<&Registry>,
+	telemetry: Option<TelemetryHandle>,
+	clock: Arc<DevClock>,
+	commands_stream: CS,
+) where
+	CS: Stream<Item = EngineCommand<Hash>> + Unpin + Send + 'static,
+{
+	let env = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry,
+		telemetry,
+	);
+	let slot_duration = clock.slot_duration();
+
+	let authorship = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import: sealing::ClockBlockImport::new(client.clone(), clock.clone()),
+		env,
+		client: client.clone(),
+		pool: transaction_pool,
+		commands_stream,
+		select_chain,
+		consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client))),
+		create_inherent_data_providers: move |_, ()| {
+			let timestamp = clock.next_timestamp();
+			async move {
+				let timestamp = sp_timestamp::InherentDataProvider::new(timestamp.into());
+
+				let slot =
+					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+						*timestamp,
+						slot_duration,
+					);
+
+				Ok((slot, timestamp))
+			}
+		},
+	});
+
+	// the sealing task is considered essential, i.e. if it
+	// fails we take down the service with it.
+	task_manager.spawn_essential_handle().spawn_blocking(
+		"manual-seal",
+		Some("block-authoring"),
+		authorship,
+	);
+}
+
+/// Timestamp of the best block, `Timestamp::Now` is not set in the genesis block.
+fn best_timestamp(client: &FullClient) -> Result<u64, ServiceError> {
+	let key = StorageKey([twox_128(b"Timestamp"), twox_128(b"Now")].concat());
+	let raw = client.storage(client.info().best_hash, &key)?;
+	match raw {
+		Some(raw) => u64::decode(&mut &raw.0[..])
+			.map_err(|e| ServiceError::Other(format!("Unable to decode the timestamp: {}", e))),
+		None => Ok(0),
+	}
+}
@@ -0,0 +1,624 @@
+//! `export-nfts` and `import-nfts` subcommands.
+//!
+//! `export-nfts` dumps the classes, tokens, listings and auctions of a block to JSON (one
+//! [`NftSnapshot`] document) or CSV (one file per table in a directory). Metadata is written as
+//! text when it is UTF-8 and as `0x` hex otherwise, balances as decimal strings and accounts in
+//! SS58, the same way the `nft_*` and `market_*` RPCs show them.
+//!
+//! `import-nfts` reads a JSON snapshot and writes the genesis configs of `pallet_nft` and
+//! `pallet_nft_market_place` seeding it as a chain spec fragment:
+//!
+//! ```json
+//! { "genesis": { "runtime": { "nftModule": { .. }, "nftMarketPlaceModule": { .. } } } }
+//! ```
+//!
+//! to replace the same sections of a chain spec that is not raw (`build-spec` without `--raw`),
+//! so the genesis of the pallets checks the snapshot when the spec is built. The marketplace gets
+//! its default parameters and no royalties, metadata verifiers are left empty. Bids of running
+//! auctions are dropped by the import since the funds backing them are not carried over, each one
+//! is reported with a warning.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs::{self, File},
+	io::{self, Write},
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use codec::Decode;
+use frame_support::storage::StoragePrefixedMap;
+use node_template_runtime::{
+	opaque::Block,
+	pallet_nft::{self, Class, ClassOf, ContentHash, Id, Nft, NftOf},
+	pallet_nft_market_place::{self, AuctionOf, OfferOf},
+	AccountId, Balance, BlockNumber, Hash, NftMarketPlaceModuleConfig, NftModuleConfig, Runtime,
+};
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_client_api::{Backend, StateBackend};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{bytes, crypto::Ss58Codec};
+use sp_runtime::traits::BlakeTwo256;
+
+use crate::rpc::nft::{AuctionInfo, ClassInfo, ListingInfo, NftInfo};
+
+/// NFT state of a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftSnapshot {
+	pub block_hash: Hash,
+	pub block_number: BlockNumber,
+	pub classes: Vec<ClassInfo>,
+	pub nfts: Vec<NftInfo>,
+	pub listings: Vec<ListingInfo>,
+	pub auctions: Vec<AuctionInfo>,
+}
+
+/// Output format of `export-nfts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SnapshotFormat {
+	/// One JSON document.
+	Json,
+	/// `classes.csv`, `nfts.csv`, `listings.csv` and `auctions.csv` in a directory.
+	Csv,
+}
+
+/// The `export-nfts` command used to dump the NFT state of a block.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportNftsCmd {
+	/// Hash of the block to export, the best block by default.
+	#[arg(long, value_name = "HASH")]
+	pub at: Option<Hash>,
+
+	/// Format of the snapshot.
+	#[arg(long, value_enum, default_value_t = SnapshotFormat::Json)]
+	pub format: SnapshotFormat,
+
+	/// File to write JSON to (stdout by default) or directory to write CSV files to.
+	#[arg(long, value_name = "PATH")]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ExportNftsCmd {
+	/// Export the NFT state of the block.
+	pub fn run<C, B>(&self, client: Arc<C>, backend: Arc<B>) -> sc_cli::Result<()>
+	where
+		C: HeaderBackend<Block>,
+		B: Backend<Block>,
+	{
+		let hash = self.at.unwrap_or_else(|| client.info().best_hash);
+		let number = client
+			.number(hash)?
+			.ok_or_else(|| format!("Block {:?} is not in the local database", hash))?;
+		let state = backend.state_at(hash)?;
+		let snapshot = export_snapshot(|prefix| prefix_pairs(&state, prefix), hash, number)?;
+		log::info!(
+			"Exporting {} classes, {} nfts, {} listings and {} auctions of block #{}",
+			snapshot.classes.len(),
+			snapshot.nfts.len(),
+			snapshot.listings.len(),
+			snapshot.auctions.len(),
+			number,
+		);
+
+		match (self.format, &self.output) {
+			(SnapshotFormat::Json, Some(path)) => {
+				serde_json::to_writer_pretty(File::create(path)?, &snapshot)
+					.map_err(|e| e.to_string())?
+			},
+			(SnapshotFormat::Json, None) => {
+				serde_json::to_writer_pretty(io::stdout(), &snapshot).map_err(|e| e.to_string())?
+			},
+			(SnapshotFormat::Csv, Some(dir)) => write_csv(dir, &snapshot)?,
+			(SnapshotFormat::Csv, None) => {
+				return Err("`--output` must name a directory for CSV".into())
+			},
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportNftsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+/// The `import-nfts` command used to turn a JSON snapshot into genesis configs.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ImportNftsCmd {
+	/// JSON snapshot written by `export-nfts`.
+	#[arg(value_name = "SNAPSHOT")]
+	pub input: PathBuf,
+
+	/// File to write the chain spec fragment to, stdout by default.
+	#[arg(long, value_name = "PATH")]
+	pub output: Option<PathBuf>,
+}
+
+impl ImportNftsCmd {
+	/// Write the chain spec fragment seeding the snapshot.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let snapshot: NftSnapshot =
+			serde_json::from_reader(File::open(&self.input)?).map_err(|e| e.to_string())?;
+		let (nft_module, nft_market_place_module) = genesis_config(&snapshot)?;
+		for auction in &snapshot.auctions {
+			if let (Some(bidder), Some(bid)) = (&auction.highest_bidder, &auction.highest_bid) {
+				log::warn!(
+					"Dropping the bid of {} by {} on the auction of nft {}/{}",
+					bid,
+					bidder.to_ss58check(),
+					auction.class_id,
+					auction.nft_id,
+				);
+			}
+		}
+		// written without going through `serde_json::Value`, which can't hold balances above
+		// `u64::MAX`
+		let fragment = Fragment {
+			genesis: FragmentGenesis {
+				runtime: FragmentRuntime { nft_module, nft_market_place_module },
+			},
+		};
+		match &self.output {
+			Some(path) => serde_json::to_writer_pretty(File::create(path)?, &fragment),
+			None => serde_json::to_writer_pretty(io::stdout(), &fragment),
+		}
+		.map_err(|e| e.to_string())?;
+		Ok(())
+	}
+}
+
+/// Chain spec fragment written by `import-nfts`.
+#[derive(Serialize)]
+struct Fragment {
+	genesis: FragmentGenesis,
+}
+
+#[derive(Serialize)]
+struct FragmentGenesis {
+	runtime: FragmentRuntime,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FragmentRuntime {
+	nft_module: NftModuleConfig,
+	nft_market_place_module: NftMarketPlaceModuleConfig,
+}
+
+/// Snapshot of the state whose `(key, value)` pairs under a prefix are given by `pairs`.
+fn export_snapshot(
+	pairs: impl Fn([u8; 32]) -> Vec<(Vec<u8>, Vec<u8>)>,
+	block_hash: Hash,
+	block_number: BlockNumber,
+) -> sc_cli::Result<NftSnapshot> {
+	let classes = decode_entries::<ClassOf<Runtime>>(
+		pairs(pallet_nft::Classes::<Runtime>::final_prefix()),
+		1,
+	)?;
+	let nfts =
+		decode_entries::<NftOf<Runtime>>(pairs(pallet_nft::Nfts::<Runtime>::final_prefix()), 2)?;
+	let listings = decode_entries::<OfferOf<Runtime>>(
+		pairs(pallet_nft_market_place::Offers::<Runtime>::final_prefix()),
+		2,
+	)?;
+	let auctions = decode_entries::<AuctionOf<Runtime>>(
+		pairs(pallet_nft_market_place::Auctions::<Runtime>::final_prefix()),
+		2,
+	)?;
+	Ok(NftSnapshot {
+		block_hash,
+		block_number,
+		classes: classes.into_iter().map(|(ids, class)| ClassInfo::new(ids[0], class)).collect(),
+		nfts: nfts.into_iter().map(|(ids, nft)| NftInfo::new(ids[1], nft)).collect(),
+		listings: listings.into_iter().map(|(_, offer)| offer.into()).collect(),
+		auctions: auctions.into_iter().map(|(_, auction)| auction.into()).collect(),
+	})
+}
+
+fn prefix_pairs<S: StateBackend<BlakeTwo256>>(
+	state: &S,
+	prefix: [u8; 32],
+) -> Vec<(Vec<u8>, Vec<u8>)> {
+	let mut pairs = Vec::new();
+	state.for_key_values_with_prefix(&prefix, |key, value| {
+		pairs.push((key.to_vec(), value.to_vec()))
+	});
+	pairs
+}
+
+/// Decodes the entries of a map whose `keys` keys are all `Twox64Concat` ids, sorted by ids.
+fn decode_entries<V: Decode>(
+	pairs: Vec<(Vec<u8>, Vec<u8>)>,
+	keys: usize,
+) -> sc_cli::Result<Vec<(Vec<Id>, V)>> {
+	let mut entries = pairs
+		.into_iter()
+		.map(|(key, value)| {
+			if key.len() != 32 + keys * 16 {
+				return Err(format!("Unexpected storage key {}", bytes::to_hex(&key, false)).into())
+			}
+			let ids = (0..keys)
+				.map(|i| Id::decode(&mut &key[32 + i * 16 + 8..32 + i * 16 + 16]))
+				.collect::<Result<_, _>>()
+				.map_err(|e| e.to_string())?;
+			let value = V::decode(&mut &value[..]).map_err(|e| e.to_string())?;
+			Ok((ids, value))
+		})
+		.collect::<sc_cli::Result<Vec<_>>>()?;
+	entries.sort_by(|a, b| a.0.cmp(&b.0));
+	Ok(entries)
+}
+
+/// Genesis configs of `pallet_nft` and `pallet_nft_market_place` seeding the snapshot.
+///
+/// The marketplace keeps its default parameters and no royalties, they are not part of a snapshot.
+fn genesis_config(
+	snapshot: &NftSnapshot,
+) -> Result<(NftModuleConfig, NftMarketPlaceModuleConfig), String> {
+	let mut class_ids = BTreeSet::new();
+	let mut classes = Vec::new();
+	for class in &snapshot.classes {
+		let value = Class {
+			owner: class.owner.clone(),
+			description: string_to_bytes(&class.description),
+			name: string_to_bytes(&class.name),
+			url: string_to_bytes(&class.url),
+			external_url: string_to_bytes(&class.external_url),
+		};
+		classes.push((class.class_id, value));
+		class_ids.insert(class.class_id);
+	}
+
+	let mut owners = BTreeMap::new();
+	let mut nfts = Vec::new();
+	for nft in &snapshot.nfts {
+		if !class_ids.contains(&nft.class_id) {
+			return Err(format!("Nft {}/{} has no class", nft.class_id, nft.nft_id))
+		}
+		let value = Nft {
+			owner: nft.owner.clone(),
+			description: string_to_bytes(&nft.description),
+			name: string_to_bytes(&nft.name),
+			url: string_to_bytes(&nft.url),
+			external_url: string_to_bytes(&nft.external_url),
+			class_id: nft.class_id,
+			metadata_hash: parse_content_hash(&nft.metadata_hash)?,
+			media_hash: parse_content_hash(&nft.media_hash)?,
+		};
+		nfts.push((nft.nft_id, value));
+		owners.insert((nft.class_id, nft.nft_id), nft.owner.clone());
+	}
+
+	let ensure_owner = |class_id: Id, nft_id: Id, seller: &AccountId| match owners
+		.get(&(class_id, nft_id))
+	{
+		Some(owner) if owner == seller => Ok(()),
+		Some(_) => {
+			Err(format!("Nft {}/{} is not owned by {}", class_id, nft_id, seller.to_ss58check()))
+		},
+		None => Err(format!("Nft {}/{} does not exist", class_id, nft_id)),
+	};
+
+	let mut listed = BTreeSet::new();
+	let mut listings = Vec::new();
+	for listing in &snapshot.listings {
+		let (class_id, nft_id) = (listing.class_id, listing.nft_id);
+		ensure_owner(class_id, nft_id, &listing.seller)?;
+		listings.push((class_id, nft_id, parse_balance(&listing.price)?, listing.expires_at));
+		listed.insert((class_id, nft_id));
+	}
+
+	let mut auctions = Vec::new();
+	for auction in &snapshot.auctions {
+		let (class_id, nft_id) = (auction.class_id, auction.nft_id);
+		ensure_owner(class_id, nft_id, &auction.seller)?;
+		if listed.contains(&(class_id, nft_id)) {
+			return Err(format!("Nft {}/{} is both listed and auctioned", class_id, nft_id))
+		}
+		auctions.push((
+			class_id,
+			nft_id,
+			parse_balance(&auction.reserve_price)?,
+			auction.start_time,
+			auction.end_time,
+			auction.time_base,
+		));
+	}
+
+	Ok((
+		NftModuleConfig { classes, nfts, metadata_verifiers: Vec::new() },
+		NftMarketPlaceModuleConfig {
+			parameters: Default::default(),
+			royalties: Vec::new(),
+			listings,
+			auctions,
+		},
+	))
+}
+
+/// Inverse of how metadata is exported: hex is only used for bytes that are not UTF-8, so a
+/// `0x` string that decodes to UTF-8 is text.
+fn string_to_bytes(value: &str) -> Vec<u8> {
+	match bytes::from_hex(value) {
+		Ok(raw) if value.starts_with("0x") && std::str::from_utf8(&raw).is_err() => raw,
+		_ => value.as_bytes().to_vec(),
+	}
+}
+
+/// Content hashes are exported as `0x` multihashes.
+fn parse_content_hash(value: &Option<String>) -> Result<Option<ContentHash>, String> {
+	value
+		.as_ref()
+		.map(|hex| {
+			bytes::from_hex(hex)
+				.ok()
+				.and_then(|raw| ContentHash::from_multihash(&raw))
+				.filter(ContentHash::is_supported)
+				.ok_or_else(|| format!("Invalid content hash {:?}", hex))
+		})
+		.transpose()
+}
+
+fn parse_balance(value: &str) -> Result<Balance, String> {
+	value.parse().map_err(|_| format!("Invalid balance {:?}", value))
+}
+
+fn write_csv(dir: &Path, snapshot: &NftSnapshot) -> io::Result<()> {
+	fs::create_dir_all(dir)?;
+	let account = |account: &AccountId| account.to_ss58check();
+	let optional = |value: Option<String>| value.unwrap_or_default();
+
+	write_table(
+		&dir.join("classes.csv"),
+		&["class_id", "owner", "name", "description", "url", "external_url"],
+		snapshot.classes.iter().map(|class| {
+			vec![
+				class.class_id.to_string(),
+				account(&class.owner),
+				class.name.clone(),
+				class.description.clone(),
+				class.url.clone(),
+				class.external_url.clone(),
+			]
+		}),
+	)?;
+	write_table(
+		&dir.join("nfts.csv"),
+		&[
+			"class_id",
+			"nft_id",
+			"owner",
+			"name",
+			"description",
+			"url",
+			"external_url",
+			"metadata_hash",
+			"media_hash",
+		],
+		snapshot.nfts.iter().map(|nft| {
+			vec![
+				nft.class_id.to_string(),
+				nft.nft_id.to_string(),
+				account(&nft.owner),
+				nft.name.clone(),
+				nft.description.clone(),
+				nft.url.clone(),
+				nft.external_url.clone(),
+				optional(nft.metadata_hash.clone()),
+				optional(nft.media_hash.clone()),
+			]
+		}),
+	)?;
+	write_table(
+		&dir.join("listings.csv"),
+		&["class_id", "nft_id", "seller", "price", "expires_at"],
+		snapshot.listings.iter().map(|listing| {
+			vec![
+				listing.class_id.to_string(),
+				listing.nft_id.to_string(),
+				account(&listing.seller),
+				listing.price.clone(),
+				optional(listing.expires_at.map(|at| at.to_string())),
+			]
+		}),
+	)?;
+	write_table(
+		&dir.join("auctions.csv"),
+		&[
+			"class_id",
+			"nft_id",
+			"seller",
+			"reserve_price",
+			"highest_bidder",
+			"highest_bid",
+			"start_time",
+			"end_time",
+			"time_base",
+		],
+		snapshot.auctions.iter().map(|auction| {
+			vec![
+				auction.class_id.to_string(),
+				auction.nft_id.to_string(),
+				account(&auction.seller),
+				auction.reserve_price.clone(),
+				optional(auction.highest_bidder.as_ref().map(account)),
+				optional(auction.highest_bid.clone()),
+				auction.start_time.to_string(),
+				auction.end_time.to_string(),
+				format!("{:?}", auction.time_base),
+			]
+		}),
+	)
+}
+
+fn write_table(
+	path: &Path,
+	header: &[&str],
+	rows: impl Iterator<Item = Vec<String>>,
+) -> io::Result<()> {
+	let mut file = io::BufWriter::new(File::create(path)?);
+	writeln!(file, "{}", header.join(","))?;
+	for row in rows {
+		let row: Vec<_> = row.iter().map(|field| csv_field(field)).collect();
+		writeln!(file, "{}", row.join(","))?;
+	}
+	file.flush()
+}
+
+fn csv_field(field: &str) -> String {
+	if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use node_template_runtime::{pallet_nft_market_place::OrderKey, BuildStorage, GenesisConfig};
+	use sp_keyring::AccountKeyring;
+
+	fn alice() -> AccountId {
+		AccountKeyring::Alice.to_account_id()
+	}
+
+	fn bob() -> AccountId {
+		AccountKeyring::Bob.to_account_id()
+	}
+
+	fn nft(nft_id: Id, owner: AccountId) -> NftInfo {
+		NftInfo {
+			class_id: 0,
+			nft_id,
+			owner,
+			name: format!("Punk #{}", nft_id),
+			description: "a punk".into(),
+			url: "0xff00".into(),
+			external_url: "0x41".into(),
+			metadata_hash: None,
+			// a sha2-256 multihash
+			media_hash: (nft_id == 1).then(|| format!("0x1220{}", "ab".repeat(32))),
+		}
+	}
+
+	fn snapshot() -> NftSnapshot {
+		NftSnapshot {
+			block_hash: Hash::repeat_byte(1),
+			block_number: 10,
+			classes: vec![ClassInfo {
+				class_id: 0,
+				owner: alice(),
+				name: "Punks".into(),
+				description: String::new(),
+				url: String::new(),
+				external_url: String::new(),
+			}],
+			nfts: vec![nft(0, alice()), nft(1, alice()), nft(2, bob())],
+			listings: vec![
+				ListingInfo {
+					class_id: 0,
+					nft_id: 0,
+					seller: alice(),
+					price: "5".into(),
+					expires_at: None,
+				},
+				ListingInfo {
+					class_id: 0,
+					nft_id: 1,
+					seller: alice(),
+					price: "3".into(),
+					expires_at: Some(1_000),
+				},
+			],
+			auctions: vec![AuctionInfo {
+				class_id: 0,
+				nft_id: 2,
+				seller: bob(),
+				reserve_price: "100".into(),
+				highest_bidder: Some(alice()),
+				highest_bid: Some("150".into()),
+				start_time: 10,
+				end_time: 20,
+				time_base: pallet_nft_market_place::TimeBase::Block,
+			}],
+		}
+	}
+
+	/// Storage of a chain started from the genesis configs of `snapshot`.
+	fn genesis_storage(snapshot: &NftSnapshot) -> Result<BTreeMap<Vec<u8>, Vec<u8>>, String> {
+		let (nft_module, nft_market_place_module) = genesis_config(snapshot)?;
+		let genesis = GenesisConfig { nft_module, nft_market_place_module, ..Default::default() };
+		Ok(genesis.build_storage()?.top)
+	}
+
+	fn export(storage: &BTreeMap<Vec<u8>, Vec<u8>>) -> NftSnapshot {
+		let pairs = |prefix: [u8; 32]| {
+			storage
+				.iter()
+				.filter(|(key, _)| key.starts_with(&prefix))
+				.map(|(key, value)| (key.clone(), value.clone()))
+				.collect()
+		};
+		export_snapshot(pairs, Hash::repeat_byte(1), 10).unwrap()
+	}
+
+	#[test]
+	fn imported_storage_exports_the_same_snapshot() {
+		let storage = genesis_storage(&snapshot()).unwrap();
+
+		let mut expected = snapshot();
+		// bids are not imported
+		expected.auctions[0].highest_bidder = None;
+		expected.auctions[0].highest_bid = None;
+		assert_eq!(export(&storage), expected);
+
+		let prefix = pallet_nft_market_place::OrderBook::<Runtime>::final_prefix();
+		let book: Vec<_> = storage
+			.keys()
+			.filter(|key| key.starts_with(&prefix))
+			.map(|key| OrderKey::decode(&mut &key[key.len() - 24..]).unwrap())
+			.map(|key| (key.price::<Balance>(), key.nft_id()))
+			.collect();
+		assert_eq!(book, vec![(3, 1), (5, 0)]);
+		let next_nft_id = &storage[&pallet_nft::NextNftId::<Runtime>::hashed_key().to_vec()];
+		assert_eq!(Id::decode(&mut &next_nft_id[..]).unwrap(), 3);
+		assert!(storage
+			.contains_key(&pallet_nft::TokensByOwner::<Runtime>::hashed_key_for((bob(), 0), 2)));
+	}
+
+	#[test]
+	fn inconsistent_snapshots_are_rejected() {
+		let mut not_owner = snapshot();
+		not_owner.listings[0].seller = bob();
+		assert!(genesis_config(&not_owner).is_err());
+
+		let mut listed_and_auctioned = snapshot();
+		listed_and_auctioned.auctions[0].nft_id = 1;
+		listed_and_auctioned.auctions[0].seller = alice();
+		assert!(genesis_config(&listed_and_auctioned).is_err());
+
+		let mut no_class = snapshot();
+		no_class.classes.clear();
+		assert!(genesis_config(&no_class).is_err());
+
+		let mut unsupported_hash = snapshot();
+		unsupported_hash.nfts[0].metadata_hash = Some("0x1301ab".into());
+		assert!(genesis_config(&unsupported_hash).is_err());
+	}
+
+	#[test]
+	fn metadata_hex_is_only_used_for_binary() {
+		assert_eq!(string_to_bytes("0xff00"), vec![0xff, 0x00]);
+		assert_eq!(string_to_bytes("0x41"), b"0x41".to_vec());
+		assert_eq!(string_to_bytes("Punk"), b"Punk".to_vec());
+		assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+	}
+}
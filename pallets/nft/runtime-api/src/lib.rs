@@ -0,0 +1,58 @@
+//! Runtime API definition for the NFT pallet.
+//!
+//! Gives clients decoded owners, metadata, listings and auctions so they don't have to read and
+//! decode the raw storage of `pallet_nft` and `pallet_nft_market_place` themselves.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_nft::{Class, ContentKind, Id, Nft};
+use pallet_nft_market_place::{Auction, Offer};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait NftApi<AccountId, Balance>
+	where
+		AccountId: Codec,
+		Balance: Codec,
+	{
+		/// Current owner of a token.
+		fn owner_of(class_id: Id, nft_id: Id) -> Option<AccountId>;
+
+		/// Account allowed to use a token it doesn't own, `None` when nobody is or the right
+		/// expired.
+		fn user_of(class_id: Id, nft_id: Id) -> Option<AccountId>;
+
+		/// Owner and metadata of a token.
+		fn nft_info(class_id: Id, nft_id: Id) -> Option<Nft<AccountId, Vec<u8>>>;
+
+		/// Owner and metadata of a class.
+		fn class_info(class_id: Id) -> Option<Class<AccountId, Vec<u8>>>;
+
+		/// Up to `limit` tokens of `account` in a class.
+		///
+		/// Pass the last nft id of the previous page as `cursor` to get the next page.
+		fn tokens_of_owner(
+			account: AccountId,
+			class_id: Id,
+			cursor: Option<Id>,
+			limit: u32,
+		) -> Vec<(Id, Nft<AccountId, Vec<u8>>)>;
+
+		/// Fixed price listing of a token.
+		fn listing_of(class_id: Id, nft_id: Id) -> Option<Offer<AccountId, Balance>>;
+
+		/// Auction of a token.
+		fn auction_of(class_id: Id, nft_id: Id) -> Option<Auction<AccountId, Balance>>;
+
+		/// Whether `data` is the file the content hash of a token commits to.
+		///
+		/// `None` when the token doesn't exist or has no content hash of that kind.
+		fn verify_content(
+			class_id: Id,
+			nft_id: Id,
+			kind: ContentKind,
+			data: Vec<u8>,
+		) -> Option<bool>;
+	}
+}
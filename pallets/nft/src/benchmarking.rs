@@ -2,17 +2,169 @@ use super::*;
 
 #[allow(unused)]
 use crate::Pallet as Nft;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use codec::Encode;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::traits::EnsureOrigin;
+use frame_system::offchain::{AppCrypto, SigningTypes};
 use frame_system::RawOrigin;
+use sp_runtime::RuntimeAppPublic;
+use sp_std::vec;
+use sp_std::vec::Vec;
+
+///longest total metadata (name, description, url and external url) the benchmarks try
+const MAX_METADATA: u32 = 4096;
+
+///`n` bytes of metadata split over the four fields
+fn metadata(n: u32) -> [Vec<u8>; 4] {
+	let quarter = (n / 4) as usize;
+	let rest = n as usize - 3 * quarter;
+	[vec![b'x'; quarter], vec![b'x'; quarter], vec![b'x'; quarter], vec![b'x'; rest]]
+}
+
+fn new_class<T: Config>(owner: &T::AccountId) -> Id {
+	let class_id = NextClassId::<T>::get().unwrap_or(0);
+	let [name, url, description, external_url] = metadata(MAX_METADATA);
+	Nft::<T>::create_class(
+		RawOrigin::Signed(owner.clone()).into(),
+		name,
+		url,
+		description,
+		external_url,
+	)
+	.unwrap();
+	class_id
+}
+
+fn new_nft<T: Config>(owner: &T::AccountId, class_id: Id) -> Id {
+	let nft_id = NextNftId::<T>::get().unwrap_or(0);
+	let [name, description, url, external_url] = metadata(MAX_METADATA);
+	Nft::<T>::mint(
+		RawOrigin::Signed(owner.clone()).into(),
+		class_id,
+		name,
+		description,
+		url,
+		external_url,
+		None,
+		None,
+	)
+	.unwrap();
+	nft_id
+}
+
+///a user whose right already lapsed, transfers, burns and new users remove it
+fn expired_user<T: Config>(class_id: Id, nft_id: Id) {
+	let user = TokenUser { user: account("user", 1, 0), expires: 0u32.into() };
+	Users::<T>::insert(class_id, nft_id, user);
+}
+
+///supported content hash, the benchmarks commit to both files with it
+fn content_hash() -> Option<ContentHash> {
+	let digest = vec![0; 32].try_into().unwrap();
+	Some(ContentHash::Multihash { code: crate::content::SHA2_256, digest })
+}
+
+type AuthorityIdOf<T> = <T as Config>::AuthorityId;
+type AppPublicOf<T> = <AuthorityIdOf<T> as AppCrypto<
+	<T as SigningTypes>::Public,
+	<T as SigningTypes>::Signature,
+>>::RuntimeAppPublic;
+type GenericPublicOf<T> = <AuthorityIdOf<T> as AppCrypto<
+	<T as SigningTypes>::Public,
+	<T as SigningTypes>::Signature,
+>>::GenericPublic;
+
+///new key of the metadata off-chain worker
+fn metadata_key<T: Config>() -> T::Public {
+	GenericPublicOf::<T>::from(AppPublicOf::<T>::generate_pair(None)).into()
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	create_class {
+		let n in 0 .. MAX_METADATA;
+		let caller: T::AccountId = whitelisted_caller();
+		let class_id = NextClassId::<T>::get().unwrap_or(0);
+		let [name, url, description, external_url] = metadata(n);
+	}: _(RawOrigin::Signed(caller.clone()), name, url, description, external_url)
+	verify {
+		assert_eq!(Classes::<T>::get(class_id).map(|class| class.owner), Some(caller));
+	}
+
+	mint {
+		let n in 0 .. MAX_METADATA;
+		let caller: T::AccountId = whitelisted_caller();
+		let class_id = new_class::<T>(&caller);
+		let nft_id = NextNftId::<T>::get().unwrap_or(0);
+		let [name, description, url, external_url] = metadata(n);
+		let (metadata_hash, media_hash) = (content_hash(), content_hash());
+	}: _(
+		RawOrigin::Signed(caller.clone()),
+		class_id,
+		name,
+		description,
+		url,
+		external_url,
+		metadata_hash,
+		media_hash
+	)
+	verify {
+		assert_eq!(Nfts::<T>::get(class_id, nft_id).map(|nft| nft.owner), Some(caller));
+	}
+
+	transfer {
+		let caller: T::AccountId = whitelisted_caller();
+		let to: T::AccountId = account("to", 0, 0);
+		let class_id = new_class::<T>(&caller);
+		let nft_id = new_nft::<T>(&caller, class_id);
+		expired_user::<T>(class_id, nft_id);
+	}: _(RawOrigin::Signed(caller), to.clone(), nft_id, class_id)
+	verify {
+		assert_eq!(Nfts::<T>::get(class_id, nft_id).map(|nft| nft.owner), Some(to));
+	}
+
+	burn {
+		let caller: T::AccountId = whitelisted_caller();
+		let class_id = new_class::<T>(&caller);
+		let nft_id = new_nft::<T>(&caller, class_id);
+		expired_user::<T>(class_id, nft_id);
+	}: _(RawOrigin::Signed(caller), nft_id, class_id)
+	verify {
+		assert!(!Nfts::<T>::contains_key(class_id, nft_id));
+	}
+
+	report_metadata {
+		let caller: T::AccountId = whitelisted_caller();
+		let class_id = new_class::<T>(&caller);
+		let nft_id = new_nft::<T>(&caller, class_id);
+		let public = metadata_key::<T>();
+		let report = MetadataReport { class_id, nft_id, status: MetadataStatus::Verified, public };
+		let signature =
+			AuthorityIdOf::<T>::sign(&report.encode(), report.public.clone()).unwrap();
+	}: _(RawOrigin::None, report, signature)
+	verify {
+		assert_eq!(MetadataStatusOf::<T>::get(class_id, nft_id), Some(MetadataStatus::Verified));
+	}
+
+	set_metadata_verifiers {
+		let n in 0 .. T::MaxMetadataVerifiers::get();
+		let verifiers: Vec<T::AccountId> = (0..n).map(|i| account("verifier", i, 0)).collect();
+		let origin = T::VerifierOrigin::successful_origin();
+	}: _<T::RuntimeOrigin>(origin, verifiers.clone())
+	verify {
+		assert_eq!(MetadataVerifiers::<T>::get().into_inner(), verifiers);
+	}
+
+	set_user {
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		let user: T::AccountId = account("user", 0, 0);
+		let class_id = new_class::<T>(&caller);
+		let nft_id = new_nft::<T>(&caller, class_id);
+		expired_user::<T>(class_id, nft_id);
+		let expires = frame_system::Pallet::<T>::block_number() + 10u32.into();
+	}: _(RawOrigin::Signed(caller), class_id, nft_id, user.clone(), expires)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!(Nft::<T>::user_of(class_id, nft_id), Some(user));
 	}
 
 	impl_benchmark_test_suite!(Nft, crate::mock::new_test_ext(), crate::mock::Test);
-}
\ No newline at end of file
+}
@@ -0,0 +1,117 @@
+//! content hashes a token commits to at mint, they let a buyer check that the metadata document
+//! or the media file behind a url are the ones the token was minted with
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::ConstU32, BoundedVec};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+///longest multihash digest
+pub const MAX_DIGEST_LEN: u32 = 64;
+
+///multihash code of sha2-256
+pub const SHA2_256: u64 = 0x12;
+///multihash code of keccak-256
+pub const KECCAK_256: u64 = 0x1b;
+///multihash code of blake2b-256
+pub const BLAKE2B_256: u64 = 0xb220;
+
+///hash of the bytes of a file
+#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum ContentHash {
+	Blake2_256([u8; 32]),
+	///a multihash split in its hash function code and digest, sha2-256, keccak-256 and
+	///blake2b-256 are supported
+	Multihash {
+		code: u64,
+		digest: BoundedVec<u8, ConstU32<MAX_DIGEST_LEN>>,
+	},
+}
+
+///which file of a token a content hash commits to
+#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum ContentKind {
+	///the metadata document behind `url`
+	Metadata,
+	///the media file, usually the `image` of the metadata document
+	Media,
+}
+
+impl ContentHash {
+	///whether the hash function is supported and the digest has its length
+	pub fn is_supported(&self) -> bool {
+		match self {
+			ContentHash::Blake2_256(_) => true,
+			ContentHash::Multihash { code, digest } =>
+				digest_of(*code, &[]).map_or(false, |expected| expected.len() == digest.len()),
+		}
+	}
+
+	///whether `data` hashes to this hash
+	pub fn verify(&self, data: &[u8]) -> bool {
+		match self {
+			ContentHash::Blake2_256(hash) => sp_io::hashing::blake2_256(data) == *hash,
+			ContentHash::Multihash { code, digest } =>
+				digest_of(*code, data).map_or(false, |actual| actual[..] == digest[..]),
+		}
+	}
+
+	///parses a multihash: varint code, varint digest length and the digest
+	pub fn from_multihash(bytes: &[u8]) -> Option<Self> {
+		let (code, rest) = read_varint(bytes)?;
+		let (len, digest) = read_varint(rest)?;
+		if digest.len() as u64 != len {
+			return None
+		}
+		let digest = BoundedVec::try_from(digest.to_vec()).ok()?;
+		Some(ContentHash::Multihash { code, digest })
+	}
+
+	///this hash as a multihash, blake2-256 is the blake2b-256 multihash
+	pub fn to_multihash(&self) -> Vec<u8> {
+		let (code, digest) = match self {
+			ContentHash::Blake2_256(hash) => (BLAKE2B_256, &hash[..]),
+			ContentHash::Multihash { code, digest } => (*code, &digest[..]),
+		};
+		let mut bytes = Vec::with_capacity(digest.len() + 4);
+		write_varint(code, &mut bytes);
+		write_varint(digest.len() as u64, &mut bytes);
+		bytes.extend_from_slice(digest);
+		bytes
+	}
+}
+
+fn digest_of(code: u64, data: &[u8]) -> Option<[u8; 32]> {
+	match code {
+		SHA2_256 => Some(sp_io::hashing::sha2_256(data)),
+		KECCAK_256 => Some(sp_io::hashing::keccak_256(data)),
+		BLAKE2B_256 => Some(sp_io::hashing::blake2_256(data)),
+		_ => None,
+	}
+}
+
+///unsigned LEB128 as used by multihash, at most 9 bytes
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+	let mut value = 0u64;
+	for (i, byte) in bytes.iter().enumerate().take(9) {
+		value |= u64::from(byte & 0x7f) << (7 * i);
+		if byte & 0x80 == 0 {
+			return Some((value, &bytes[i + 1..]))
+		}
+	}
+	None
+}
+
+fn write_varint(mut value: u64, bytes: &mut Vec<u8>) {
+	while value >= 0x80 {
+		bytes.push((value as u8 & 0x7f) | 0x80);
+		value >>= 7;
+	}
+	bytes.push(value as u8);
+}
@@ -5,29 +5,180 @@ mod mock;
 
 #[cfg(test)]
 mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod content;
+pub mod metadata;
+pub mod migrations;
+pub mod weights;
+use frame_support::weights::Weight;
+pub use content::{ContentHash, ContentKind};
+pub use metadata::{MetadataError, MetadataStatus};
+pub use weights::WeightInfo;
+
+///key type of the keys the off-chain worker signs its metadata reports with
+pub const KEY_TYPE: sp_core::crypto::KeyTypeId = sp_core::crypto::KeyTypeId(*b"nftm");
+
+///sr25519 keys of the metadata off-chain worker, insert one with `author_insertKey` and add its
+///account to `MetadataVerifiers`
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::{Public as Sr25519Public, Signature as Sr25519Signature};
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct MetadataAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for MetadataAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericPublic = Sr25519Public;
+		type GenericSignature = Sr25519Signature;
+	}
+}
+
+///lets other pallets react when a token changes hands, e.g. to drop listings made by the old owner
+pub trait OnTransfer<AccountId> {
+	fn on_transfer(class_id: Id, nft_id: Id, from: &AccountId, to: &AccountId);
+
+	///worst case weight of `on_transfer`, charged on top of the transfer itself
+	fn on_transfer_weight() -> Weight {
+		Weight::zero()
+	}
+}
+
+///lets other pallets react when a token is burned
+pub trait OnBurn<AccountId> {
+	fn on_burn(class_id: Id, nft_id: Id, owner: &AccountId);
+
+	///worst case weight of `on_burn`, charged on top of the burn itself
+	fn on_burn_weight() -> Weight {
+		Weight::zero()
+	}
+}
+
+///lets other pallets keep a token where it is, e.g. while it is the collateral of a loan
+pub trait TokenLock {
+	fn is_locked(class_id: Id, nft_id: Id) -> bool;
+
+	///the owner can't give the token a user, e.g. while it is up for sale. defaults to `is_locked`
+	fn is_user_locked(class_id: Id, nft_id: Id) -> bool {
+		Self::is_locked(class_id, nft_id)
+	}
+
+	///worst case weight of `is_user_locked`, charged on top of `set_user`
+	fn is_user_locked_weight() -> Weight {
+		Weight::zero()
+	}
+}
+
+impl TokenLock for () {
+	fn is_locked(_class_id: Id, _nft_id: Id) -> bool {
+		false
+	}
+}
+
+impl<AccountId> OnTransfer<AccountId> for () {
+	fn on_transfer(_class_id: Id, _nft_id: Id, _from: &AccountId, _to: &AccountId) {}
+}
+
+impl<AccountId> OnBurn<AccountId> for () {
+	fn on_burn(_class_id: Id, _nft_id: Id, _owner: &AccountId) {}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use codec::MaxEncodedLen;
+	use crate::{
+		metadata, ContentHash, ContentKind, MetadataError, MetadataStatus, OnBurn, OnTransfer,
+		TokenLock, WeightInfo,
+	};
 	use frame_support::pallet_prelude::*;
 	use frame_support::traits::Currency;
+	use frame_system::offchain::{
+		AppCrypto, SendTransactionTypes, SendUnsignedTransaction, SignedPayload, Signer,
+		SigningTypes,
+	};
 	use frame_system::pallet_prelude::*;
+	#[cfg(feature = "std")]
+	use serde::{Deserialize, Serialize};
+	use sp_runtime::offchain::{
+		storage::{StorageRetrievalError, StorageValueRef},
+		StorageKind,
+	};
+	use sp_runtime::traits::{IdentifyAccount, Saturating};
 	use sp_std::vec::Vec;
+
+	const LOG_TARGET: &str = "runtime::nft";
+
+	///offchain storage key of the gateway `ipfs://` urls are fetched through, overrides
+	///`Config::IpfsGateway` on this node
+	pub const IPFS_GATEWAY_KEY: &[u8] = b"nft::ipfs-gateway";
+
+	///offchain storage prefix of the block a token's metadata was last checked at
+	const METADATA_CHECK_KEY: &[u8] = b"nft::metadata-check";
+
+	///offchain storage prefix of the number of checks in a row a token's metadata could not be
+	///fetched in, see `metadata::MAX_FETCH_FAILURES`
+	pub const METADATA_FAILURES_KEY: &[u8] = b"nft::metadata-failures";
+
+	///blocks before the worker checks a token again when its report didn't make it in a block
+	const RECHECK_AFTER: u32 = 10;
+
+	///blocks a metadata report stays valid in the pool
+	const REPORT_LONGEVITY: u64 = 5;
+
+	///version of the storage layout, see `migrations.rs`
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
-///create weight for every function instead of supplie 0 as weight
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config:
+		frame_system::Config + SigningTypes + SendTransactionTypes<Call<Self>>
+	{
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		type Currency: Currency<Self::AccountId>;
+		///called after a token moved to a new owner
+		type OnTransfer: OnTransfer<Self::AccountId>;
+		///called after a token has been burned
+		type OnBurn: OnBurn<Self::AccountId>;
+		///tokens that can be neither transferred nor burned for now
+		type TokenLock: TokenLock;
+		///weight of the calls, see `weights.rs`
+		type WeightInfo: WeightInfo;
+		///key the off-chain worker signs its metadata reports with, see [`crate::crypto`]
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		///gateway `ipfs://` urls are fetched through, e.g. `https://ipfs.io`. a node can use
+		///another one by writing it to the `nft::ipfs-gateway` persistent offchain storage
+		type IpfsGateway: Get<&'static [u8]>;
+		///most tokens the off-chain worker checks per block
+		#[pallet::constant]
+		type MaxMetadataChecks: Get<u32>;
+		///most accounts allowed to report on metadata
+		#[pallet::constant]
+		type MaxMetadataVerifiers: Get<u32>;
+		///can change the accounts allowed to report on metadata
+		type VerifierOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		///pool priority of the metadata reports
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
 	}
 	///id must be supplied from the runtime like currency
 	pub type Id = u64;
 	pub type NftOf<T> = Nft<<T as frame_system::Config>::AccountId, Vec<u8>>;
 	pub type ClassOf<T> = Class<<T as frame_system::Config>::AccountId, Vec<u8>>;
+	pub type TokenUserOf<T> =
+		TokenUser<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
 
 	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 	pub struct Nft<AccountId, Body> {
 		pub owner: AccountId,
 		pub description: Body,
@@ -35,9 +186,24 @@ pub mod pallet {
 		pub url: Body,
 		pub external_url: Body,
 		pub class_id: Id,
+		///hash of the metadata document behind `url`, fixed at mint like the rest of the metadata
+		pub metadata_hash: Option<ContentHash>,
+		///hash of the media file of the token, fixed at mint
+		pub media_hash: Option<ContentHash>,
+	}
+
+	impl<AccountId, Body> Nft<AccountId, Body> {
+		///content hash the token committed to for `kind`
+		pub fn content_hash(&self, kind: ContentKind) -> Option<&ContentHash> {
+			match kind {
+				ContentKind::Metadata => self.metadata_hash.as_ref(),
+				ContentKind::Media => self.media_hash.as_ref(),
+			}
+		}
 	}
 
 	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 	pub struct Class<AccountId, Body> {
 		pub owner: AccountId,
 		pub description: Body,
@@ -47,6 +213,35 @@ pub mod pallet {
 		///nft number
 	}
 
+	///account allowed to use a token without owning it, like the user of ERC-4907. the right
+	///lapses at block `expires`, until then the token can't change hands
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub struct TokenUser<AccountId, BlockNumber> {
+		pub user: AccountId,
+		pub expires: BlockNumber,
+	}
+
+	impl<AccountId, BlockNumber: PartialOrd> TokenUser<AccountId, BlockNumber> {
+		pub fn is_active(&self, now: BlockNumber) -> bool {
+			now < self.expires
+		}
+	}
+
+	///what the off-chain worker of `public` found behind the url of a token
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct MetadataReport<Public> {
+		pub class_id: Id,
+		pub nft_id: Id,
+		pub status: MetadataStatus,
+		pub public: Public,
+	}
+
+	impl<T: SigningTypes> SignedPayload<T> for MetadataReport<T::Public> {
+		fn public(&self) -> T::Public {
+			self.public.clone()
+		}
+	}
+
 	// collection => nft => classId
 
 	#[pallet::storage]
@@ -71,6 +266,97 @@ pub mod pallet {
 		Id,       // nft id
 		NftOf<T>, // nfts
 	>;
+
+	///tokens of an account per class, lets clients list what someone owns without scanning `Nfts`
+	#[pallet::storage]
+	pub type TokensByOwner<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(T::AccountId, Id), // owner, class id
+		Twox64Concat,
+		Id, // nft id
+		(),
+		OptionQuery,
+	>;
+
+	///user of a token, kept after it expired until the token changes hands or gets a new user,
+	///see `user_of` for the current user
+	#[pallet::storage]
+	#[pallet::getter(fn users)]
+	pub type Users<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, Id, Twox64Concat, Id, TokenUserOf<T>>;
+
+	///tokens minted since, waiting for the off-chain worker to report on their metadata
+	#[pallet::storage]
+	pub type PendingMetadata<T> = StorageDoubleMap<_, Twox64Concat, Id, Twox64Concat, Id, ()>;
+
+	///what the off-chain worker found behind the url of a token, nothing until it reported
+	#[pallet::storage]
+	#[pallet::getter(fn metadata_status)]
+	pub type MetadataStatusOf<T> =
+		StorageDoubleMap<_, Twox64Concat, Id, Twox64Concat, Id, MetadataStatus>;
+
+	///accounts whose off-chain worker can report on metadata
+	#[pallet::storage]
+	#[pallet::getter(fn metadata_verifiers)]
+	pub type MetadataVerifiers<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxMetadataVerifiers>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		///classes that exist from the first block
+		pub classes: Vec<(Id, ClassOf<T>)>,
+		///tokens minted at genesis, their class must be in `classes`
+		pub nfts: Vec<(Id, NftOf<T>)>,
+		///accounts whose off-chain worker can report on metadata
+		pub metadata_verifiers: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { classes: Vec::new(), nfts: Vec::new(), metadata_verifiers: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (class_id, class) in &self.classes {
+				assert!(
+					!<Classes<T>>::contains_key(class_id),
+					"class {} is defined twice",
+					class_id
+				);
+				<Classes<T>>::insert(class_id, class);
+			}
+			for (nft_id, nft) in &self.nfts {
+				assert!(
+					<Classes<T>>::contains_key(nft.class_id),
+					"nft {} is minted in the unknown class {}",
+					nft_id,
+					nft.class_id
+				);
+				assert!(
+					!<Nfts<T>>::contains_key(nft.class_id, nft_id),
+					"nft {} is minted twice",
+					nft_id
+				);
+				<Nfts<T>>::insert(nft.class_id, nft_id, nft);
+				<TokensByOwner<T>>::insert((nft.owner.clone(), nft.class_id), nft_id, ());
+			}
+			if let Some(last) = self.classes.iter().map(|(class_id, _)| *class_id).max() {
+				<NextClassId<T>>::put(last + 1);
+			}
+			if let Some(last) = self.nfts.iter().map(|(nft_id, _)| *nft_id).max() {
+				<NextNftId<T>>::put(last + 1);
+			}
+			let verifiers = BoundedVec::try_from(self.metadata_verifiers.clone())
+				.expect("more metadata verifiers than `MaxMetadataVerifiers`");
+			<MetadataVerifiers<T>>::put(verifiers);
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -84,16 +370,20 @@ pub mod pallet {
 		},
 		Minted {
 			class_id: Id,
+			nft_id: Id,
 			owner: T::AccountId,
 			name: Vec<u8>,
 			url: Vec<u8>,
 			external_url: Vec<u8>,
 			description: Vec<u8>,
+			metadata_hash: Option<ContentHash>,
+			media_hash: Option<ContentHash>,
 		},
 		///must know who the one who burn the nft
 		Burned {
 			nft_id: Id,
 			class_id: Id,
+			owner: T::AccountId,
 		},
 		TransferNft {
 			from: T::AccountId,
@@ -101,6 +391,27 @@ pub mod pallet {
 			nft_id: Id,
 			class_id: Id,
 		},
+		///the off-chain worker found valid ERC-721 metadata behind the url of the token
+		MetadataVerified {
+			class_id: Id,
+			nft_id: Id,
+		},
+		///the url of the token or the document behind it is broken
+		MetadataBroken {
+			class_id: Id,
+			nft_id: Id,
+			reason: crate::MetadataError,
+		},
+		MetadataVerifiersSet {
+			verifiers: Vec<T::AccountId>,
+		},
+		///`user` can use the token until block `expires`
+		UserSet {
+			class_id: Id,
+			nft_id: Id,
+			user: T::AccountId,
+			expires: T::BlockNumber,
+		},
 	}
 
 	#[pallet::error]
@@ -108,12 +419,71 @@ pub mod pallet {
 		ClassNotFound,
 		NotOwner,
 		NftNotFound,
+		///the metadata of the token was reported on already or the token is gone
+		MetadataNotPending,
+		///more accounts than `MaxMetadataVerifiers`
+		TooManyVerifiers,
+		///the hash function of a content hash is not supported or its digest has the wrong length
+		UnsupportedContentHash,
+		///the token has a user whose right has not expired yet
+		TokenInUse,
+		///the user right would expire before the next block
+		ExpiryInPast,
+		///another pallet holds on to the token, e.g. as the collateral of a loan or while it is
+		///listed
+		TokenLocked,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		///fetches the metadata of the tokens minted recently and reports on it
+		fn offchain_worker(block_number: T::BlockNumber) {
+			if let Err(e) = Self::check_pending_metadata(block_number) {
+				log::debug!(target: LOG_TARGET, "metadata not checked: {}", e);
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		///only reports signed by a verifier on tokens still pending get in the pool, one per token
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (report, signature) = match call {
+				Call::report_metadata { report, signature } => (report, signature),
+				_ => return InvalidTransaction::Call.into(),
+			};
+			if !<PendingMetadata<T>>::contains_key(report.class_id, report.nft_id) {
+				return InvalidTransaction::Stale.into()
+			}
+			let reporter = report.public.clone().into_account();
+			if !Self::metadata_verifiers().contains(&reporter) {
+				return InvalidTransaction::BadSigner.into()
+			}
+			if !SignedPayload::<T>::verify::<T::AuthorityId>(report, signature.clone()) {
+				return InvalidTransaction::BadProof.into()
+			}
+			ValidTransaction::with_tag_prefix("NftMetadata")
+				.priority(T::UnsignedPriority::get())
+				.and_provides((report.class_id, report.nft_id))
+				.longevity(REPORT_LONGEVITY)
+				.propagate(true)
+				.build()
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::call_index(0)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::create_class(
+			metadata_len(&[name, url, description, external_url])
+		))]
 		pub fn create_class(
 			origin: OriginFor<T>,
 			name: Vec<u8>,
@@ -144,7 +514,9 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(1)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::mint(
+			metadata_len(&[name, description, url, external_url])
+		))]
 		pub fn mint(
 			origin: OriginFor<T>,
 			class_id: Id,
@@ -152,10 +524,18 @@ pub mod pallet {
 			description: Vec<u8>,
 			url: Vec<u8>,
 			external_url: Vec<u8>,
+			metadata_hash: Option<ContentHash>,
+			media_hash: Option<ContentHash>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			///you need to make sure the owner of the class is the one minting it
 			ensure!(<Classes<T>>::contains_key(class_id), Error::<T>::ClassNotFound);
+			let supported =
+				|hash: &Option<ContentHash>| hash.as_ref().map_or(true, ContentHash::is_supported);
+			ensure!(
+				supported(&metadata_hash) && supported(&media_hash),
+				Error::<T>::UnsupportedContentHash
+			);
 			// let nft_id = Self::match_nft_id();
 			///each class nft should start from 1 should start from 
 			let nft_id = <NextNftId<T>>::get().unwrap_or(0);
@@ -166,8 +546,12 @@ pub mod pallet {
 				description: description.clone(),
 				url: url.clone(),
 				external_url: external_url.clone(),
+				metadata_hash,
+				media_hash,
 			};
 			<Nfts<T>>::insert(class_id, nft_id, nft.clone());
+			<TokensByOwner<T>>::insert((nft.owner.clone(), class_id), nft_id, ());
+			<PendingMetadata<T>>::insert(class_id, nft_id, ());
 			<NextNftId<T>>::put(nft_id + 1);
 			///name is availiable from the params
 			Self::deposit_event(Event::Minted {
@@ -176,13 +560,18 @@ pub mod pallet {
 				description: nft.description,
 				url: nft.url,
 				external_url: nft.external_url,
+				metadata_hash: nft.metadata_hash,
+				media_hash: nft.media_hash,
 				class_id,
+				nft_id,
 			});
 			Ok(())
 		}
 
 		#[pallet::call_index(2)]
-		#[pallet::weight(0)]
+		#[pallet::weight(
+			T::WeightInfo::transfer().saturating_add(T::OnTransfer::on_transfer_weight())
+		)]
 		pub fn transfer(
 			origin: OriginFor<T>,
 			to: T::AccountId,
@@ -191,25 +580,322 @@ pub mod pallet {
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			ensure!(<Classes<T>>::contains_key(class_id), Error::<T>::ClassNotFound);
-			ensure!(<Nfts<T>>::contains_key(class_id, nft_id), Error::<T>::NftNotFound);
-			let mut nft_info = <Nfts<T>>::get(class_id, nft_id).unwrap();
-			/// we need to check if the owner is the one who make the transfer
-			nft_info.owner = to.clone();
-			<Nfts<T>>::insert(class_id, nft_id, nft_info);
-			Self::deposit_event(Event::TransferNft { from: who, to, nft_id, class_id });
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner == who, Error::<T>::NotOwner);
+			let from = Self::do_transfer(class_id, nft_id, &to)?;
+			Self::deposit_event(Event::TransferNft { from, to, nft_id, class_id });
 			Ok(())
 		}
 
 		#[pallet::call_index(3)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::burn().saturating_add(T::OnBurn::on_burn_weight()))]
 		pub fn burn(origin: OriginFor<T>, nft_id: Id, class_id: Id) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			///why check if key is availiable then getting data just get the data and see if it it the owner
 			ensure!(<Nfts<T>>::contains_key(class_id, nft_id), Error::<T>::NftNotFound);
 			let nft_info = <Nfts<T>>::get(class_id, nft_id).unwrap();
 			ensure!(nft_info.owner == who, Error::<T>::NotOwner);
+			ensure!(!T::TokenLock::is_locked(class_id, nft_id), Error::<T>::TokenLocked);
+			Self::remove_expired_user(class_id, nft_id)?;
 			<Nfts<T>>::remove(class_id, nft_id);
-			Self::deposit_event(Event::Burned { nft_id, class_id });
+			<TokensByOwner<T>>::remove((who.clone(), class_id), nft_id);
+			<PendingMetadata<T>>::remove(class_id, nft_id);
+			<MetadataStatusOf<T>>::remove(class_id, nft_id);
+			T::OnBurn::on_burn(class_id, nft_id, &who);
+			Self::deposit_event(Event::Burned { nft_id, class_id, owner: who });
+			Ok(())
+		}
+
+		///sent by the off-chain worker of a verifier, records the metadata status of a token
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::report_metadata())]
+		pub fn report_metadata(
+			origin: OriginFor<T>,
+			report: MetadataReport<T::Public>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			let MetadataReport { class_id, nft_id, status, .. } = report;
+			ensure!(
+				<PendingMetadata<T>>::take(class_id, nft_id).is_some(),
+				Error::<T>::MetadataNotPending
+			);
+			<MetadataStatusOf<T>>::insert(class_id, nft_id, status);
+			match status {
+				MetadataStatus::Verified =>
+					Self::deposit_event(Event::MetadataVerified { class_id, nft_id }),
+				MetadataStatus::Broken(reason) =>
+					Self::deposit_event(Event::MetadataBroken { class_id, nft_id, reason }),
+			}
+			Ok(())
+		}
+
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::set_metadata_verifiers(verifiers.len() as u32))]
+		pub fn set_metadata_verifiers(
+			origin: OriginFor<T>,
+			verifiers: Vec<T::AccountId>,
+		) -> DispatchResult {
+			T::VerifierOrigin::ensure_origin(origin)?;
+			let bounded = BoundedVec::try_from(verifiers.clone())
+				.map_err(|_| Error::<T>::TooManyVerifiers)?;
+			<MetadataVerifiers<T>>::put(bounded);
+			Self::deposit_event(Event::MetadataVerifiersSet { verifiers });
+			Ok(())
+		}
+
+		///the owner lets `user` use the token until block `expires`, the token can't be
+		///transferred or burned until then and the user can't be replaced. refused while another
+		///pallet holds on to the token, see `TokenLock::is_user_locked`
+		#[pallet::call_index(6)]
+		#[pallet::weight(
+			T::WeightInfo::set_user().saturating_add(T::TokenLock::is_user_locked_weight())
+		)]
+		pub fn set_user(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			user: T::AccountId,
+			expires: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner == who, Error::<T>::NotOwner);
+			ensure!(!T::TokenLock::is_user_locked(class_id, nft_id), Error::<T>::TokenLocked);
+			Self::do_set_user(class_id, nft_id, user, expires)
+		}
+	}
+
+	///total length of the metadata fields of a class or token, the weight grows with it
+	fn metadata_len(fields: &[&Vec<u8>]) -> u32 {
+		fields.iter().map(|field| field.len() as u32).sum()
+	}
+
+	impl<T: Config> Pallet<T> {
+		///give the token to `to` and run the `OnTransfer` hook, fails while it is locked. returns
+		///the previous owner
+		pub fn do_transfer(
+			class_id: Id,
+			nft_id: Id,
+			to: &T::AccountId,
+		) -> Result<T::AccountId, DispatchError> {
+			ensure!(!T::TokenLock::is_locked(class_id, nft_id), Error::<T>::TokenLocked);
+			Self::remove_expired_user(class_id, nft_id)?;
+			let from =
+				<Nfts<T>>::try_mutate(class_id, nft_id, |nft| -> Result<_, DispatchError> {
+					let nft = nft.as_mut().ok_or(Error::<T>::NftNotFound)?;
+					Ok(sp_std::mem::replace(&mut nft.owner, to.clone()))
+				})?;
+			<TokensByOwner<T>>::remove((from.clone(), class_id), nft_id);
+			<TokensByOwner<T>>::insert((to.clone(), class_id), nft_id, ());
+			T::OnTransfer::on_transfer(class_id, nft_id, &from, to);
+			Ok(from)
+		}
+
+		///give the token a user until block `expires`, fails while the current user is active
+		pub fn do_set_user(
+			class_id: Id,
+			nft_id: Id,
+			user: T::AccountId,
+			expires: T::BlockNumber,
+		) -> DispatchResult {
+			ensure!(<Nfts<T>>::contains_key(class_id, nft_id), Error::<T>::NftNotFound);
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(expires > now, Error::<T>::ExpiryInPast);
+			if let Some(current) = <Users<T>>::get(class_id, nft_id) {
+				ensure!(!current.is_active(now), Error::<T>::TokenInUse);
+			}
+			<Users<T>>::insert(class_id, nft_id, TokenUser { user: user.clone(), expires });
+			Self::deposit_event(Event::UserSet { class_id, nft_id, user, expires });
+			Ok(())
+		}
+
+		///current user of the token, `None` when it has none or its right expired
+		pub fn user_of(class_id: Id, nft_id: Id) -> Option<T::AccountId> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			<Users<T>>::get(class_id, nft_id)
+				.filter(|user| user.is_active(now))
+				.map(|user| user.user)
+		}
+
+		///drop the user of the token once its right expired, fails while it is active
+		pub fn remove_expired_user(class_id: Id, nft_id: Id) -> DispatchResult {
+			if let Some(user) = <Users<T>>::get(class_id, nft_id) {
+				let now = <frame_system::Pallet<T>>::block_number();
+				ensure!(!user.is_active(now), Error::<T>::TokenInUse);
+				<Users<T>>::remove(class_id, nft_id);
+			}
+			Ok(())
+		}
+
+		///checks up to `MaxMetadataChecks` pending tokens and sends a signed unsigned report on
+		///each one that could be fetched or that failed to be fetched too many times
+		fn check_pending_metadata(block_number: T::BlockNumber) -> Result<(), &'static str> {
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			if !signer.can_sign() {
+				return Err("no `nftm` key in the keystore")
+			}
+			let gateway = Self::ipfs_gateway();
+			let tokens: Vec<(Id, Id)> = <PendingMetadata<T>>::iter_keys()
+				.filter(|(class_id, nft_id)| Self::claim_check(*class_id, *nft_id, block_number))
+				.take(T::MaxMetadataChecks::get() as usize)
+				.collect();
+			let mut reports = Vec::new();
+			let mut to_fetch = Vec::new();
+			for (class_id, nft_id) in tokens {
+				let nft = match <Nfts<T>>::get(class_id, nft_id) {
+					Some(nft) => nft,
+					None => continue,
+				};
+				if Self::fetch_failures(class_id, nft_id) >= metadata::MAX_FETCH_FAILURES {
+					reports.push((
+						class_id,
+						nft_id,
+						MetadataStatus::Broken(MetadataError::Unreachable),
+					));
+				} else {
+					to_fetch.push(((class_id, nft_id), nft));
+				}
+			}
+			let (ids, nfts): (Vec<(Id, Id)>, Vec<NftOf<T>>) = to_fetch.into_iter().unzip();
+			for ((class_id, nft_id), status) in
+				ids.into_iter().zip(metadata::check_all(&nfts, &gateway))
+			{
+				match status {
+					Ok(status) => {
+						Self::set_fetch_failures(class_id, nft_id, 0);
+						reports.push((class_id, nft_id, status));
+					},
+					Err(e) => {
+						log::debug!(
+							target: LOG_TARGET,
+							"metadata of nft {} in class {} unreachable: {:?}",
+							nft_id,
+							class_id,
+							e
+						);
+						let failures = Self::fetch_failures(class_id, nft_id);
+						Self::set_fetch_failures(class_id, nft_id, failures.saturating_add(1));
+					},
+				}
+			}
+			for (class_id, nft_id, status) in reports {
+				let sent = signer.send_unsigned_transaction(
+					|account| MetadataReport {
+						class_id,
+						nft_id,
+						status,
+						public: account.public.clone(),
+					},
+					|report, signature| Call::report_metadata { report, signature },
+				);
+				if let Some((_, Err(()))) = sent {
+					log::warn!(
+						target: LOG_TARGET,
+						"metadata report of nft {} in class {} not submitted",
+						nft_id,
+						class_id
+					);
+				}
+			}
+			Ok(())
+		}
+
+		///checks in a row the metadata of the token could not be fetched in
+		fn fetch_failures(class_id: Id, nft_id: Id) -> u32 {
+			let key = (METADATA_FAILURES_KEY, class_id, nft_id).encode();
+			StorageValueRef::persistent(&key).get().ok().flatten().unwrap_or(0)
+		}
+
+		fn set_fetch_failures(class_id: Id, nft_id: Id, failures: u32) {
+			let key = (METADATA_FAILURES_KEY, class_id, nft_id).encode();
+			let mut value = StorageValueRef::persistent(&key);
+			if failures == 0 {
+				value.clear();
+			} else {
+				value.set(&failures);
+			}
+		}
+
+		///gateway written to the `nft::ipfs-gateway` offchain storage, `IpfsGateway` otherwise
+		fn ipfs_gateway() -> Vec<u8> {
+			sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, IPFS_GATEWAY_KEY)
+				.filter(|gateway| !gateway.is_empty())
+				.unwrap_or_else(|| T::IpfsGateway::get().to_vec())
+		}
+
+		///whether the worker of `block_number` checks the token, a token is checked again only
+		///`RECHECK_AFTER` blocks later so that the report has time to get in a block
+		fn claim_check(class_id: Id, nft_id: Id, block_number: T::BlockNumber) -> bool {
+			let key = (METADATA_CHECK_KEY, class_id, nft_id).encode();
+			StorageValueRef::persistent(&key)
+				.mutate(|last: Result<Option<T::BlockNumber>, StorageRetrievalError>| match last {
+					Ok(Some(last)) if block_number < last.saturating_add(RECHECK_AFTER.into()) =>
+						Err(()),
+					_ => Ok(block_number),
+				})
+				.is_ok()
+		}
+
+		///`None` when the token doesn't exist or has no content hash of that kind, otherwise
+		///whether `data` is the content the token committed to
+		pub fn verify_content(
+			class_id: Id,
+			nft_id: Id,
+			kind: ContentKind,
+			data: &[u8],
+		) -> Option<bool> {
+			let nft = <Nfts<T>>::get(class_id, nft_id)?;
+			nft.content_hash(kind).map(|hash| hash.verify(data))
+		}
+
+		///up to `limit` tokens of `owner` in a class, starting after the `cursor` nft id
+		pub fn tokens_of_owner(
+			owner: T::AccountId,
+			class_id: Id,
+			cursor: Option<Id>,
+			limit: u32,
+		) -> Vec<(Id, NftOf<T>)> {
+			let key = (owner, class_id);
+			let ids = match cursor {
+				Some(nft_id) => {
+					let raw_key = <TokensByOwner<T>>::hashed_key_for(&key, nft_id);
+					<TokensByOwner<T>>::iter_key_prefix_from(&key, raw_key)
+				},
+				None => <TokensByOwner<T>>::iter_key_prefix(&key),
+			};
+			ids.take(limit as usize)
+				.filter_map(|nft_id| <Nfts<T>>::get(class_id, nft_id).map(|nft| (nft_id, nft)))
+				.collect()
+		}
+
+		///checks that every token is in an existing class and indexed under its owner, that the
+		///id counters are past every stored id and that users are on existing tokens
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			let next_class_id = <NextClassId<T>>::get().unwrap_or(0);
+			for class_id in <Classes<T>>::iter_keys() {
+				ensure!(class_id < next_class_id, "class id not below NextClassId");
+			}
+			let next_nft_id = <NextNftId<T>>::get().unwrap_or(0);
+			let mut tokens = 0usize;
+			for (class_id, nft_id, nft) in <Nfts<T>>::iter() {
+				tokens += 1;
+				ensure!(nft.class_id == class_id, "token stored under another class");
+				ensure!(<Classes<T>>::contains_key(class_id), "token of an unknown class");
+				ensure!(nft_id < next_nft_id, "nft id not below NextNftId");
+				ensure!(
+					<TokensByOwner<T>>::contains_key((nft.owner, class_id), nft_id),
+					"token missing from TokensByOwner"
+				);
+			}
+			ensure!(
+				<TokensByOwner<T>>::iter_keys().count() == tokens,
+				"TokensByOwner entry without a token"
+			);
+			for (class_id, nft_id) in <Users<T>>::iter_keys() {
+				ensure!(<Nfts<T>>::contains_key(class_id, nft_id), "user of a missing token");
+			}
 			Ok(())
 		}
 	}
@@ -0,0 +1,237 @@
+//! checks the off-chain worker runs on the metadata of newly minted tokens
+//!
+//! the `url` of a token must lead to an ERC-721 metadata document, `ipfs://` urls are fetched
+//! through an http gateway and must match the `metadata_hash` of the token when it has one. a non
+//! empty `external_url` must be a well formed url too. urls of hosts on the node's own network,
+//! loopback, private and link-local addresses, are refused so that minting a token can't make the
+//! validators probe their network. host names are not resolved here, a node that must be sure of
+//! it has to fetch through a proxy
+
+use crate::Nft;
+use codec::{Decode, Encode, MaxEncodedLen};
+use lite_json::JsonValue;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	offchain::{http, Duration, Timestamp},
+	RuntimeDebug,
+};
+use sp_std::{str, vec::Vec};
+
+///biggest metadata document the worker downloads
+pub const MAX_METADATA_SIZE: usize = 64 * 1024;
+
+///how long the worker waits for the metadata documents of a block, in milliseconds. they are
+///all fetched at the same time
+pub const FETCH_TIMEOUT: u64 = 5_000;
+
+///checks in a row the metadata of a token can fail to be fetched before it is reported as
+///unreachable
+pub const MAX_FETCH_FAILURES: u32 = 5;
+
+///what the off-chain worker found behind the `url` of a token
+#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum MetadataStatus {
+	///valid ERC-721 metadata
+	Verified,
+	Broken(MetadataError),
+}
+
+///why the metadata of a token is broken
+#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum MetadataError {
+	///`url` is not an http, https or ipfs url
+	InvalidUrl,
+	///`external_url` is set but is not an http, https or ipfs url
+	InvalidExternalUrl,
+	///the server answered with this status instead of 200, redirects are not followed
+	HttpStatus(u16),
+	///the document is bigger than `MAX_METADATA_SIZE`
+	TooLarge,
+	///the document is not utf-8 json
+	InvalidJson,
+	///the json does not follow the ERC-721 metadata schema
+	SchemaMismatch,
+	///the document is not the one the token's `metadata_hash` commits to
+	HashMismatch,
+	///the document could not be fetched `MAX_FETCH_FAILURES` times in a row
+	Unreachable,
+}
+
+///status of the metadata of each of `nfts`, their documents are fetched at the same time. `Err`
+///when the document could not be fetched and the check should be tried again later
+pub fn check_all<AccountId>(
+	nfts: &[Nft<AccountId, Vec<u8>>],
+	gateway: &[u8],
+) -> Vec<Result<MetadataStatus, http::Error>> {
+	let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT));
+	let mut statuses = Vec::with_capacity(nfts.len());
+	let mut requests = Vec::new();
+	for (index, nft) in nfts.iter().enumerate() {
+		statuses.push(match url_to_fetch(nft, gateway) {
+			Ok(url) => match send(&url, deadline) {
+				Ok(request) => {
+					requests.push((index, request));
+					//until its response is in
+					Err(http::Error::DeadlineReached)
+				},
+				Err(e) => Err(e),
+			},
+			Err(status) => Ok(status),
+		});
+	}
+	let (indices, pending): (Vec<usize>, Vec<_>) = requests.into_iter().unzip();
+	let responses = http::PendingRequest::try_wait_all(pending, deadline);
+	for (index, response) in indices.into_iter().zip(responses) {
+		let nft = &nfts[index];
+		statuses[index] = match response {
+			Ok(Ok(response)) => read(response, deadline).map(|body| match body {
+				Ok(body) if nft.metadata_hash.as_ref().map_or(false, |hash| !hash.verify(&body)) =>
+					MetadataStatus::Broken(MetadataError::HashMismatch),
+				Ok(body) => validate(&body),
+				Err(error) => MetadataStatus::Broken(error),
+			}),
+			Ok(Err(e)) => Err(e),
+			Err(_) => Err(http::Error::DeadlineReached),
+		};
+	}
+	statuses
+}
+
+///url the metadata of `nft` is fetched from, `Err` when its urls are already broken
+fn url_to_fetch<AccountId>(
+	nft: &Nft<AccountId, Vec<u8>>,
+	gateway: &[u8],
+) -> Result<Vec<u8>, MetadataStatus> {
+	let url =
+		resolve_url(&nft.url, gateway).ok_or(MetadataStatus::Broken(MetadataError::InvalidUrl))?;
+	if !nft.external_url.is_empty() && resolve_url(&nft.external_url, gateway).is_none() {
+		return Err(MetadataStatus::Broken(MetadataError::InvalidExternalUrl))
+	}
+	Ok(url)
+}
+
+///http url `url` is fetched from, `ipfs://<cid>/<path>` becomes `<gateway>/ipfs/<cid>/<path>`
+pub fn resolve_url(url: &[u8], gateway: &[u8]) -> Option<Vec<u8>> {
+	let url = str::from_utf8(url).ok()?;
+	if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+		return None
+	}
+	if let Some(path) = url.strip_prefix("ipfs://") {
+		//some tools still write `ipfs://ipfs/<cid>`
+		let path = path.strip_prefix("ipfs/").unwrap_or(path);
+		if path.is_empty() || path.starts_with('/') {
+			return None
+		}
+		let mut resolved = gateway.strip_suffix(b"/").unwrap_or(gateway).to_vec();
+		resolved.extend_from_slice(b"/ipfs/");
+		resolved.extend_from_slice(path.as_bytes());
+		return Some(resolved)
+	}
+	match url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) {
+		Some(rest) if is_public_host(rest) => Some(url.as_bytes().to_vec()),
+		_ => None,
+	}
+}
+
+///whether the host at the start of `rest`, the url after its scheme, is neither missing nor on
+///the node's own network. credentials and ipv6 literals are refused, metadata servers have no
+///use for them
+fn is_public_host(rest: &str) -> bool {
+	let authority = rest.split(|c| c == '/' || c == '?' || c == '#').next().unwrap_or_default();
+	if authority.contains('@') || authority.starts_with('[') {
+		return false
+	}
+	let host = authority.split(':').next().unwrap_or_default().to_ascii_lowercase();
+	let host = host.strip_suffix('.').unwrap_or(&host);
+	if host.is_empty() || host == "localhost" || host.ends_with(".localhost") {
+		return false
+	}
+	//resolvers also take `127.1`, `0x7f.0.0.1` and `2130706433` for an address, only a plain
+	//dotted quad is let through and only when it is public
+	let last_label = host.rsplit('.').next().unwrap_or_default();
+	if !last_label.starts_with(|c: char| c.is_ascii_digit()) {
+		return true
+	}
+	match ipv4(host) {
+		Some([a, b, _, _]) =>
+			!(a == 0 ||
+				a == 10 ||
+				a == 127 ||
+				(a == 100 && (64..128).contains(&b)) ||
+				(a == 169 && b == 254) ||
+				(a == 172 && (16..32).contains(&b)) ||
+				(a == 192 && b == 168) ||
+				a >= 224),
+		None => false,
+	}
+}
+
+///the four numbers of a dotted quad ipv4 address, without leading zeros
+fn ipv4(host: &str) -> Option<[u8; 4]> {
+	let mut octets = [0; 4];
+	let mut parts = host.split('.');
+	for octet in octets.iter_mut() {
+		let part = parts.next()?;
+		if part.is_empty() || (part.len() > 1 && part.starts_with('0')) {
+			return None
+		}
+		*octet = part.parse().ok()?;
+	}
+	parts.next().is_none().then(|| octets)
+}
+
+///starts the request for the document at `url`
+fn send(url: &[u8], deadline: Timestamp) -> Result<http::PendingRequest, http::Error> {
+	let url = str::from_utf8(url).map_err(|_| http::Error::Unknown)?;
+	http::Request::get(url)
+		.deadline(deadline)
+		.send()
+		.map_err(|_| http::Error::IoError)
+}
+
+///body of the document of `response`, the inner `Err` is the answer of a server that has no
+///valid document for us, the outer one a body that could not be read in time
+fn read(
+	response: http::Response,
+	deadline: Timestamp,
+) -> Result<Result<Vec<u8>, MetadataError>, http::Error> {
+	if response.code != 200 {
+		return Ok(Err(MetadataError::HttpStatus(response.code)))
+	}
+	let mut reader = response.body();
+	reader.deadline(deadline);
+	let body: Vec<u8> = reader.by_ref().take(MAX_METADATA_SIZE + 1).collect();
+	if let Some(e) = reader.error() {
+		return Err(*e)
+	}
+	if body.len() > MAX_METADATA_SIZE {
+		return Ok(Err(MetadataError::TooLarge))
+	}
+	Ok(Ok(body))
+}
+
+///checks `body` against the ERC-721 metadata JSON schema: an object whose `name`, `description`
+///and `image` are strings. `name` and `image` are required, wallets can't show a token without them
+pub fn validate(body: &[u8]) -> MetadataStatus {
+	let json = match str::from_utf8(body).ok().and_then(|text| lite_json::parse_json(text).ok()) {
+		Some(json) => json,
+		None => return MetadataStatus::Broken(MetadataError::InvalidJson),
+	};
+	let fields = match json {
+		JsonValue::Object(fields) => fields,
+		_ => return MetadataStatus::Broken(MetadataError::SchemaMismatch),
+	};
+	let is_string = |name: &str, required: bool| {
+		let field = fields.iter().find(|(key, _)| key.iter().copied().eq(name.chars()));
+		match field {
+			Some((_, JsonValue::String(_))) => true,
+			Some(_) => false,
+			None => !required,
+		}
+	};
+	if is_string("name", true) && is_string("description", false) && is_string("image", true) {
+		MetadataStatus::Verified
+	} else {
+		MetadataStatus::Broken(MetadataError::SchemaMismatch)
+	}
+}
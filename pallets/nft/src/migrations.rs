@@ -0,0 +1,97 @@
+//! storage migrations of the pallet, each `vN` module brings the storage from version `N - 1` to
+//! `N` and does nothing when the on-chain version is not `N - 1`
+
+use crate::{Config, Id, Nft, Nfts, Pallet, TokensByOwner};
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+	RuntimeDebug,
+};
+use scale_info::TypeInfo;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+const LOG_TARGET: &str = "runtime::nft";
+
+///storage layout before the content hashes and `TokensByOwner`
+pub mod v0 {
+	use super::*;
+
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct Nft<AccountId, Body> {
+		pub owner: AccountId,
+		pub description: Body,
+		pub name: Body,
+		pub url: Body,
+		pub external_url: Body,
+		pub class_id: Id,
+	}
+
+	pub type NftOf<T> = Nft<<T as frame_system::Config>::AccountId, Vec<u8>>;
+}
+
+///tokens get empty content hashes and `TokensByOwner` is rebuilt from `Nfts`
+pub mod v1 {
+	use super::*;
+
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				log::info!(target: LOG_TARGET, "v1 migration skipped, storage is not v0");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut count = 0u64;
+			<Nfts<T>>::translate::<v0::NftOf<T>, _>(|class_id, nft_id, old| {
+				count += 1;
+				<TokensByOwner<T>>::insert((old.owner.clone(), class_id), nft_id, ());
+				Some(Nft {
+					owner: old.owner,
+					description: old.description,
+					name: old.name,
+					url: old.url,
+					external_url: old.external_url,
+					class_id: old.class_id,
+					metadata_hash: None,
+					media_hash: None,
+				})
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "migrated {} tokens to v1", count);
+			//one read and two writes per token, the version read and write
+			T::DbWeight::get().reads_writes(count + 1, count * 2 + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let count = <Nfts<T>>::iter_keys().count() as u64;
+			Ok(count.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let before = u64::decode(&mut &state[..]).map_err(|_| "invalid pre_upgrade state")?;
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 1,
+				"storage version is not 1"
+			);
+			let mut after = 0u64;
+			for (class_id, nft_id, nft) in <Nfts<T>>::iter() {
+				after += 1;
+				frame_support::ensure!(
+					<TokensByOwner<T>>::contains_key((nft.owner, class_id), nft_id),
+					"token missing from TokensByOwner"
+				);
+			}
+			frame_support::ensure!(before == after, "tokens lost in the migration");
+			for class_id in <crate::Classes<T>>::iter_keys() {
+				frame_support::ensure!(
+					<crate::Classes<T>>::try_get(class_id).is_ok(),
+					"undecodable class"
+				);
+			}
+			Ok(())
+		}
+	}
+}
@@ -1,12 +1,20 @@
 use crate as pallet_nft;
-use frame_support::traits::{ConstU16, ConstU64,ConstU128};
+use frame_support::parameter_types;
+use frame_support::traits::{ConstU16, ConstU32, ConstU64,ConstU128, GenesisBuild};
 use frame_system as system;
+use frame_system::EnsureRoot;
+use parking_lot::RwLock;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
 	traits::{BlakeTwo256, IdentityLookup},
 };
 use frame_support::traits::Currency;
+use sp_core::offchain::{
+	testing::{OffchainState, PoolState, TestOffchainExt, TestTransactionPoolExt},
+	OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+};
 use sp_core::H256;
+use std::sync::Arc;
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -48,11 +56,75 @@ impl system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+
+impl system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<C> system::offchain::SendTransactionTypes<C> for Test
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+///signs with the keys set by `UintAuthorityId::set_all_keys`, no keystore needed
+pub struct TestAuthId;
+
+impl system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+parameter_types! {
+	pub const IpfsGateway: &'static [u8] = b"https://gateway.test/";
+}
+
 impl pallet_nft::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type Currency = ();
+	type OnTransfer = ();
+	type OnBurn = ();
+	type TokenLock = ();
+	type WeightInfo = ();
+	type AuthorityId = TestAuthId;
+	type IpfsGateway = IpfsGateway;
+	type MaxMetadataChecks = ConstU32<2>;
+	type MaxMetadataVerifiers = ConstU32<3>;
+	type VerifierOrigin = EnsureRoot<u64>;
+	type UnsignedPriority = ConstU64<100>;
 }
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
 }
+
+///runs `test` and checks the storage invariants of the pallet afterwards
+pub fn execute_with_try_state(mut ext: sp_io::TestExternalities, test: impl FnOnce()) {
+	ext.execute_with(|| {
+		test();
+		NftModule::do_try_state().unwrap();
+	})
+}
+
+///externalities of an off-chain worker whose node has the key of account 7, a verifier
+pub fn new_offchain_test_ext(
+) -> (sp_io::TestExternalities, Arc<RwLock<OffchainState>>, Arc<RwLock<PoolState>>) {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_nft::GenesisConfig::<Test> { metadata_verifiers: vec![7], ..Default::default() }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	UintAuthorityId::set_all_keys(vec![7]);
+	let (offchain, offchain_state) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut ext = sp_io::TestExternalities::from(t);
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.execute_with(|| System::set_block_number(1));
+	(ext, offchain_state, pool_state)
+}
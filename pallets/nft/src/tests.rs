@@ -1,15 +1,31 @@
-use crate::{mock::*, Class, Error, Nft};
-use frame_support::{assert_noop, assert_ok, print};
+use crate::{
+	content, metadata, migrations, mock::*, Class, ContentHash, ContentKind, Error, MetadataError,
+	MetadataReport, MetadataStatus, Nft, PendingMetadata, IPFS_GATEWAY_KEY, METADATA_FAILURES_KEY,
+};
+use codec::{Decode, Encode};
+use frame_support::{assert_noop, assert_ok, dispatch::DispatchResult, print};
+use frame_support::traits::{GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion};
 use frame_system::Origin;
+use parking_lot::RwLock;
+use sp_core::offchain::{
+	testing::{OffchainState, PendingRequest, PoolState},
+	StorageKind,
+};
+use sp_runtime::{
+	testing::{TestSignature, UintAuthorityId},
+	traits::{BadOrigin, ValidateUnsigned},
+	transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidity},
+	RuntimeAppPublic,
+};
 #[test]
 fn it_works_for_default_value() {
-	new_test_ext().execute_with(|| {
+	execute_with_try_state(new_test_ext(), || {
 
 	});
 }
 #[test]
 fn correct_error_for_none_value() {
-	new_test_ext().execute_with(|| {
+	execute_with_try_state(new_test_ext(), || {
 		assert_ok!(NftModule::create_class(
 			RuntimeOrigin::signed(1),
 			"first".into(),
@@ -34,7 +50,9 @@ fn correct_error_for_none_value() {
 			"first".into(),
 			"first".into(),
 			"first".into(),
-			"first".into()
+			"first".into(),
+			None,
+			None
 		));
 		assert_eq!(
 			NftModule::nfts(0,0),
@@ -44,7 +62,9 @@ fn correct_error_for_none_value() {
 				description: vec![102, 105, 114, 115, 116],
 				name: vec![102, 105, 114, 115, 116],
 				url: vec![102, 105, 114, 115, 116],
-				external_url: vec![102, 105, 114, 115, 116]
+				external_url: vec![102, 105, 114, 115, 116],
+				metadata_hash: None,
+				media_hash: None,
 			})
 		);
 
@@ -73,3 +93,609 @@ fn correct_error_for_none_value() {
 		);
 	});
 }
+
+#[test]
+fn only_the_owner_can_transfer_a_token() {
+	execute_with_try_state(new_test_ext(), || {
+		assert_ok!(NftModule::create_class(
+			RuntimeOrigin::signed(1),
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			"first".into()
+		));
+		assert_ok!(NftModule::mint(
+			RuntimeOrigin::signed(1),
+			0,
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			None,
+			None
+		));
+		assert_noop!(
+			NftModule::transfer(RuntimeOrigin::signed(2), 2, 0, 0),
+			Error::<Test>::NotOwner
+		);
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 3, 0, 0));
+		System::assert_last_event(
+			crate::Event::TransferNft { from: 1, to: 3, nft_id: 0, class_id: 0 }.into(),
+		);
+	});
+}
+
+#[test]
+fn tokens_of_owner_follows_mint_transfer_and_burn() {
+	execute_with_try_state(new_test_ext(), || {
+		assert_ok!(NftModule::create_class(
+			RuntimeOrigin::signed(1),
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			"first".into()
+		));
+		for _ in 0..3 {
+			assert_ok!(NftModule::mint(
+				RuntimeOrigin::signed(1),
+				0,
+				"first".into(),
+				"first".into(),
+				"first".into(),
+				"first".into(),
+				None,
+				None
+			));
+		}
+		let ids = |owner, cursor, limit| -> Vec<u64> {
+			let mut ids: Vec<u64> = NftModule::tokens_of_owner(owner, 0, cursor, limit)
+				.into_iter()
+				.map(|(nft_id, _)| nft_id)
+				.collect();
+			ids.sort();
+			ids
+		};
+		assert_eq!(ids(1, None, 10), vec![0, 1, 2]);
+
+		// paging with the last returned id as cursor gives back every token once
+		let first_page = NftModule::tokens_of_owner(1, 0, None, 2);
+		assert_eq!(first_page.len(), 2);
+		let cursor = first_page.last().map(|(nft_id, _)| *nft_id);
+		let second_page = NftModule::tokens_of_owner(1, 0, cursor, 2);
+		assert_eq!(second_page.len(), 1);
+
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 2, 1, 0));
+		assert_ok!(NftModule::burn(RuntimeOrigin::signed(1), 2, 0));
+		assert_eq!(ids(1, None, 10), vec![0]);
+		assert_eq!(ids(2, None, 10), vec![1]);
+		assert_eq!(NftModule::tokens_of_owner(2, 0, None, 10)[0].1.owner, 2);
+	});
+}
+
+const METADATA: &[u8] =
+	br#"{"name":"first","description":"the first one","image":"ipfs://QmImage/1.png"}"#;
+
+///mints a token of a new class with `url` as its metadata url
+fn mint_with_url(url: &str, external_url: &str) -> (u64, u64) {
+	let class_id = NftModule::next_class_id().unwrap_or(0);
+	let nft_id = NftModule::next_nft_id().unwrap_or(0);
+	assert_ok!(NftModule::create_class(
+		RuntimeOrigin::signed(1),
+		"first".into(),
+		"first".into(),
+		"first".into(),
+		"first".into()
+	));
+	assert_ok!(NftModule::mint(
+		RuntimeOrigin::signed(1),
+		class_id,
+		"first".into(),
+		"first".into(),
+		url.into(),
+		external_url.into(),
+		None,
+		None
+	));
+	(class_id, nft_id)
+}
+
+fn expect_get(offchain: &RwLock<OffchainState>, uri: &str, body: &[u8]) {
+	offchain.write().expect_request(PendingRequest {
+		method: "GET".into(),
+		uri: uri.into(),
+		response: Some(body.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+fn invalid(error: InvalidTransaction) -> TransactionValidity {
+	Err(error.into())
+}
+
+///reports the off-chain worker put in the pool
+fn submitted_reports(
+	pool: &RwLock<PoolState>,
+) -> Vec<(MetadataReport<UintAuthorityId>, TestSignature)> {
+	pool.write()
+		.transactions
+		.drain(..)
+		.map(|tx| {
+			let tx = Extrinsic::decode(&mut &*tx).unwrap();
+			assert_eq!(tx.signature, None);
+			match tx.call {
+				RuntimeCall::NftModule(crate::Call::report_metadata { report, signature }) =>
+					(report, signature),
+				call => panic!("unexpected call {:?}", call),
+			}
+		})
+		.collect()
+}
+
+#[test]
+fn minted_tokens_wait_for_a_metadata_report() {
+	execute_with_try_state(new_test_ext(), || {
+		let (class_id, nft_id) = mint_with_url("https://example.com/1.json", "");
+		assert!(PendingMetadata::<Test>::contains_key(class_id, nft_id));
+		assert_eq!(NftModule::metadata_status(class_id, nft_id), None);
+
+		assert_ok!(NftModule::burn(RuntimeOrigin::signed(1), nft_id, class_id));
+		assert!(!PendingMetadata::<Test>::contains_key(class_id, nft_id));
+	});
+}
+
+#[test]
+fn offchain_worker_verifies_metadata_through_the_ipfs_gateway() {
+	let (mut ext, offchain, pool) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let (class_id, nft_id) = mint_with_url("ipfs://QmMeta/1.json", "https://example.com/1");
+		expect_get(&offchain, "https://gateway.test/ipfs/QmMeta/1.json", METADATA);
+		NftModule::offchain_worker(1);
+
+		let mut reports = submitted_reports(&pool);
+		assert_eq!(reports.len(), 1);
+		let (report, signature) = reports.remove(0);
+		assert_eq!(
+			report,
+			MetadataReport {
+				class_id,
+				nft_id,
+				status: MetadataStatus::Verified,
+				public: UintAuthorityId(7)
+			}
+		);
+		let call =
+			crate::Call::report_metadata { report: report.clone(), signature: signature.clone() };
+		assert!(NftModule::validate_unsigned(TransactionSource::External, &call).is_ok());
+
+		assert_ok!(NftModule::report_metadata(
+			RuntimeOrigin::none(),
+			report.clone(),
+			signature.clone()
+		));
+		assert_eq!(NftModule::metadata_status(class_id, nft_id), Some(MetadataStatus::Verified));
+		System::assert_last_event(crate::Event::MetadataVerified { class_id, nft_id }.into());
+
+		// a token is reported on once
+		assert_noop!(
+			NftModule::report_metadata(RuntimeOrigin::none(), report, signature),
+			Error::<Test>::MetadataNotPending
+		);
+		assert_eq!(
+			NftModule::validate_unsigned(TransactionSource::External, &call),
+			invalid(InvalidTransaction::Stale)
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_reports_broken_metadata() {
+	let (mut ext, offchain, pool) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let bad_url = mint_with_url("ftp://example.com/1.json", "");
+		let bad_external_url = mint_with_url("https://example.com/2.json", "example.com");
+		let not_json = mint_with_url("https://example.com/3.json", "");
+		expect_get(&offchain, "https://example.com/3.json", b"<html>not found</html>");
+		// two checks per block
+		NftModule::offchain_worker(1);
+		NftModule::offchain_worker(2);
+
+		let no_image = mint_with_url("ipfs://ipfs/QmMeta/4.json", "");
+		expect_get(&offchain, "https://gateway.test/ipfs/QmMeta/4.json", br#"{"name":"4"}"#);
+		NftModule::offchain_worker(3);
+
+		let mut reports: Vec<_> = submitted_reports(&pool)
+			.into_iter()
+			.map(|(report, _)| ((report.class_id, report.nft_id), report.status))
+			.collect();
+		reports.sort_by_key(|(token, _)| *token);
+		assert_eq!(
+			reports,
+			vec![
+				(bad_url, MetadataStatus::Broken(MetadataError::InvalidUrl)),
+				(bad_external_url, MetadataStatus::Broken(MetadataError::InvalidExternalUrl)),
+				(not_json, MetadataStatus::Broken(MetadataError::InvalidJson)),
+				(no_image, MetadataStatus::Broken(MetadataError::SchemaMismatch)),
+			]
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_waits_before_checking_a_token_again() {
+	let (mut ext, offchain, pool) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let (class_id, nft_id) = mint_with_url("https://example.com/1.json", "");
+		expect_get(&offchain, "https://example.com/1.json", METADATA);
+		NftModule::offchain_worker(1);
+		assert_eq!(submitted_reports(&pool).len(), 1);
+
+		// the report didn't get in a block, the token is checked again 10 blocks later
+		NftModule::offchain_worker(10);
+		assert!(submitted_reports(&pool).is_empty());
+		expect_get(&offchain, "https://example.com/1.json", METADATA);
+		NftModule::offchain_worker(11);
+		let reports = submitted_reports(&pool);
+		assert_eq!((reports[0].0.class_id, reports[0].0.nft_id), (class_id, nft_id));
+	});
+}
+
+#[test]
+fn nodes_can_use_their_own_ipfs_gateway() {
+	let (mut ext, offchain, pool) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			IPFS_GATEWAY_KEY,
+			b"http://127.0.0.1:8080",
+		);
+		mint_with_url("ipfs://QmMeta/1.json", "");
+		expect_get(&offchain, "http://127.0.0.1:8080/ipfs/QmMeta/1.json", METADATA);
+		NftModule::offchain_worker(1);
+		assert_eq!(submitted_reports(&pool)[0].0.status, MetadataStatus::Verified);
+	});
+}
+
+#[test]
+fn offchain_worker_gives_up_on_unreachable_metadata() {
+	let (mut ext, offchain, pool) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let failures = |token: (u64, u64)| (METADATA_FAILURES_KEY, token.0, token.1).encode();
+		let set_failures = |token, count: u32| {
+			sp_io::offchain::local_storage_set(
+				StorageKind::PERSISTENT,
+				&failures(token),
+				&count.encode(),
+			)
+		};
+
+		// a document that is fetched in the end starts the count over
+		let reachable = mint_with_url("https://example.com/1.json", "");
+		set_failures(reachable, metadata::MAX_FETCH_FAILURES - 1);
+		expect_get(&offchain, "https://example.com/1.json", METADATA);
+		NftModule::offchain_worker(1);
+		assert_eq!(submitted_reports(&pool)[0].0.status, MetadataStatus::Verified);
+		assert_eq!(
+			sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &failures(reachable)),
+			None
+		);
+
+		// no request is made for a token that failed too often
+		let unreachable = mint_with_url("https://example.com/2.json", "");
+		set_failures(unreachable, metadata::MAX_FETCH_FAILURES);
+		NftModule::offchain_worker(2);
+		let reports = submitted_reports(&pool);
+		assert_eq!(reports.len(), 1);
+		assert_eq!((reports[0].0.class_id, reports[0].0.nft_id), unreachable);
+		assert_eq!(reports[0].0.status, MetadataStatus::Broken(MetadataError::Unreachable));
+	});
+}
+
+#[test]
+fn only_verifiers_can_report_metadata() {
+	let (mut ext, _, _) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let (class_id, nft_id) = mint_with_url("https://example.com/1.json", "");
+		let report = |public| MetadataReport {
+			class_id,
+			nft_id,
+			status: MetadataStatus::Verified,
+			public: UintAuthorityId(public),
+		};
+		let validate = |report: MetadataReport<UintAuthorityId>, signature| {
+			let call = crate::Call::report_metadata { report, signature };
+			NftModule::validate_unsigned(TransactionSource::External, &call)
+		};
+		let signed = |report: &MetadataReport<UintAuthorityId>| {
+			UintAuthorityId(report.public.0).sign(&report.encode()).unwrap()
+		};
+
+		let stranger = report(8);
+		assert_eq!(
+			validate(stranger.clone(), signed(&stranger)),
+			invalid(InvalidTransaction::BadSigner)
+		);
+		let forged = report(7);
+		assert_eq!(validate(forged, signed(&stranger)), invalid(InvalidTransaction::BadProof));
+
+		assert_noop!(
+			NftModule::set_metadata_verifiers(RuntimeOrigin::signed(7), vec![8]),
+			BadOrigin
+		);
+		assert_noop!(
+			NftModule::set_metadata_verifiers(RuntimeOrigin::root(), vec![1, 2, 3, 4]),
+			Error::<Test>::TooManyVerifiers
+		);
+		assert_ok!(NftModule::set_metadata_verifiers(RuntimeOrigin::root(), vec![8]));
+		assert!(validate(stranger.clone(), signed(&stranger)).is_ok());
+	});
+}
+
+#[test]
+fn metadata_urls_and_documents() {
+	let gateway = b"https://gateway.test/";
+	assert_eq!(
+		metadata::resolve_url(b"ipfs://QmMeta/1.json", gateway),
+		Some(b"https://gateway.test/ipfs/QmMeta/1.json".to_vec())
+	);
+	assert_eq!(
+		metadata::resolve_url(b"ipfs://ipfs/QmMeta", b"https://gateway.test"),
+		Some(b"https://gateway.test/ipfs/QmMeta".to_vec())
+	);
+	assert_eq!(
+		metadata::resolve_url(b"http://example.com/1.json", gateway),
+		Some(b"http://example.com/1.json".to_vec())
+	);
+	let broken: [&[u8]; 6] =
+		[b"", b"ipfs://", b"https://", b"https:///1.json", b"example.com", b"https://a b"];
+	for url in broken {
+		assert_eq!(metadata::resolve_url(url, gateway), None);
+	}
+	// hosts on the network of the node are not fetched from
+	let local: [&[u8]; 12] = [
+		b"http://localhost/1.json",
+		b"http://api.localhost/1.json",
+		b"http://127.0.0.1:8080/1.json",
+		b"http://127.1/1.json",
+		b"http://2130706433/1.json",
+		b"http://0x7f.0.0.1/1.json",
+		b"http://10.0.0.1/1.json",
+		b"http://172.16.5.4/1.json",
+		b"http://192.168.1.1/1.json",
+		b"http://169.254.169.254/latest/meta-data",
+		b"http://[::1]/1.json",
+		b"http://user@example.com/1.json",
+	];
+	for url in local {
+		assert_eq!(metadata::resolve_url(url, gateway), None);
+	}
+	assert_eq!(
+		metadata::resolve_url(b"https://93.184.216.34:443/1.json", gateway),
+		Some(b"https://93.184.216.34:443/1.json".to_vec())
+	);
+
+	assert_eq!(metadata::validate(METADATA), MetadataStatus::Verified);
+	assert_eq!(metadata::validate(br#"{"name":"1","image":"a.png"}"#), MetadataStatus::Verified);
+	for (body, error) in [
+		(&b"not json"[..], MetadataError::InvalidJson),
+		(br#"["name","image"]"#, MetadataError::SchemaMismatch),
+		(br#"{"name":"1","image":2}"#, MetadataError::SchemaMismatch),
+		(br#"{"name":"1","image":"a.png","description":null}"#, MetadataError::SchemaMismatch),
+	] {
+		assert_eq!(metadata::validate(body), MetadataStatus::Broken(error));
+	}
+}
+
+fn mint_with_hashes(
+	metadata_hash: Option<ContentHash>,
+	media_hash: Option<ContentHash>,
+) -> DispatchResult {
+	if NftModule::classes(0).is_none() {
+		assert_ok!(NftModule::create_class(
+			RuntimeOrigin::signed(1),
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			"first".into()
+		));
+	}
+	NftModule::mint(
+		RuntimeOrigin::signed(1),
+		0,
+		"first".into(),
+		"first".into(),
+		"https://example.com/1.json".into(),
+		"".into(),
+		metadata_hash,
+		media_hash,
+	)
+}
+
+#[test]
+fn tokens_commit_to_their_content_at_mint() {
+	execute_with_try_state(new_test_ext(), || {
+		let sha2 = |data: &[u8]| ContentHash::Multihash {
+			code: content::SHA2_256,
+			digest: sp_io::hashing::sha2_256(data).to_vec().try_into().unwrap(),
+		};
+		assert_ok!(mint_with_hashes(
+			Some(sha2(METADATA)),
+			Some(ContentHash::Blake2_256(sp_io::hashing::blake2_256(b"media")))
+		));
+		assert_eq!(NftModule::verify_content(0, 0, ContentKind::Metadata, METADATA), Some(true));
+		assert_eq!(NftModule::verify_content(0, 0, ContentKind::Media, b"media"), Some(true));
+		assert_eq!(NftModule::verify_content(0, 0, ContentKind::Media, b"swapped"), Some(false));
+
+		// no commitment or no token
+		assert_ok!(mint_with_hashes(None, None));
+		assert_eq!(NftModule::verify_content(0, 1, ContentKind::Media, b"media"), None);
+		assert_eq!(NftModule::verify_content(0, 2, ContentKind::Media, b"media"), None);
+
+		let unknown_function =
+			ContentHash::Multihash { code: 0x13, digest: vec![0; 64].try_into().unwrap() };
+		let short_digest = ContentHash::Multihash {
+			code: content::SHA2_256,
+			digest: vec![0; 20].try_into().unwrap(),
+		};
+		for hash in [unknown_function, short_digest] {
+			assert_noop!(mint_with_hashes(None, Some(hash)), Error::<Test>::UnsupportedContentHash);
+		}
+	});
+}
+
+#[test]
+fn content_hashes_are_multihashes() {
+	let digest = [0xab; 32];
+	let mut sha2 = vec![0x12, 0x20];
+	sha2.extend_from_slice(&digest);
+	let hash = ContentHash::from_multihash(&sha2).unwrap();
+	let expected_digest = digest.to_vec().try_into().unwrap();
+	assert_eq!(hash, ContentHash::Multihash { code: content::SHA2_256, digest: expected_digest });
+	assert_eq!(hash.to_multihash(), sha2);
+
+	// blake2-256 is the blake2b-256 multihash, its code takes two bytes
+	let blake2 = ContentHash::Blake2_256(sp_io::hashing::blake2_256(b"media")).to_multihash();
+	assert_eq!(blake2[..4], [0xa0, 0xe4, 0x02, 0x20]);
+	assert!(ContentHash::from_multihash(&blake2).unwrap().verify(b"media"));
+
+	assert_eq!(ContentHash::from_multihash(&sha2[..20]), None);
+	assert_eq!(ContentHash::from_multihash(&[0x80]), None);
+}
+
+#[test]
+fn offchain_worker_checks_the_metadata_hash() {
+	let (mut ext, offchain, pool) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let other = ContentHash::Blake2_256(sp_io::hashing::blake2_256(b"another document"));
+		assert_ok!(mint_with_hashes(Some(other), None));
+		expect_get(&offchain, "https://example.com/1.json", METADATA);
+		NftModule::offchain_worker(1);
+		assert_eq!(
+			submitted_reports(&pool)[0].0.status,
+			MetadataStatus::Broken(MetadataError::HashMismatch)
+		);
+	});
+}
+
+#[test]
+fn users_lock_the_token_until_their_right_expires() {
+	execute_with_try_state(new_test_ext(), || {
+		System::set_block_number(10);
+		assert_ok!(NftModule::create_class(
+			RuntimeOrigin::signed(1),
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			"first".into()
+		));
+		assert_ok!(NftModule::mint(
+			RuntimeOrigin::signed(1),
+			0,
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			"first".into(),
+			None,
+			None
+		));
+		assert_noop!(
+			NftModule::set_user(RuntimeOrigin::signed(2), 0, 0, 2, 20),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			NftModule::set_user(RuntimeOrigin::signed(1), 0, 0, 2, 10),
+			Error::<Test>::ExpiryInPast
+		);
+		assert_ok!(NftModule::set_user(RuntimeOrigin::signed(1), 0, 0, 2, 20));
+		System::assert_last_event(
+			crate::Event::UserSet { class_id: 0, nft_id: 0, user: 2, expires: 20 }.into(),
+		);
+		assert_eq!(NftModule::user_of(0, 0), Some(2));
+
+		// the owner can't take the token back or hand it over while it is used
+		assert_noop!(
+			NftModule::set_user(RuntimeOrigin::signed(1), 0, 0, 3, 30),
+			Error::<Test>::TokenInUse
+		);
+		assert_noop!(
+			NftModule::transfer(RuntimeOrigin::signed(1), 3, 0, 0),
+			Error::<Test>::TokenInUse
+		);
+		assert_noop!(NftModule::burn(RuntimeOrigin::signed(1), 0, 0), Error::<Test>::TokenInUse);
+
+		// the right lapses by itself at block 20
+		System::set_block_number(20);
+		assert_eq!(NftModule::user_of(0, 0), None);
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 3, 0, 0));
+		assert_eq!(NftModule::users(0, 0), None);
+	});
+}
+
+#[test]
+fn try_state_catches_orphan_tokens_and_stale_counters() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(mint_with_hashes(None, None));
+		assert_ok!(NftModule::do_try_state());
+
+		crate::NextNftId::<Test>::put(0);
+		assert_eq!(NftModule::do_try_state(), Err("nft id not below NextNftId"));
+		crate::NextNftId::<Test>::put(1);
+
+		let class = crate::Classes::<Test>::take(0).unwrap();
+		assert_eq!(NftModule::do_try_state(), Err("token of an unknown class"));
+		crate::Classes::<Test>::insert(0, class);
+		assert_ok!(NftModule::do_try_state());
+	});
+}
+
+#[test]
+fn v1_migration_adds_content_hashes_and_indexes_owners() {
+	new_test_ext().execute_with(|| {
+		let class = Class {
+			owner: 1,
+			description: b"class".to_vec(),
+			name: b"class".to_vec(),
+			url: b"class".to_vec(),
+			external_url: b"class".to_vec(),
+		};
+		crate::Classes::<Test>::insert(0, &class);
+		for (nft_id, owner) in [(0, 1), (1, 2), (2, 1)] {
+			let old = migrations::v0::Nft {
+				owner,
+				description: b"nft".to_vec(),
+				name: b"nft".to_vec(),
+				url: b"https://example.com/nft.json".to_vec(),
+				external_url: Vec::new(),
+				class_id: 0,
+			};
+			let key = crate::Nfts::<Test>::hashed_key_for(0, nft_id);
+			frame_support::storage::unhashed::put(&key, &old);
+		}
+		StorageVersion::new(0).put::<NftModule>();
+
+		#[cfg(feature = "try-runtime")]
+		let state = migrations::v1::MigrateToV1::<Test>::pre_upgrade().unwrap();
+		migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		#[cfg(feature = "try-runtime")]
+		migrations::v1::MigrateToV1::<Test>::post_upgrade(state).unwrap();
+
+		assert_eq!(NftModule::on_chain_storage_version(), 1);
+		assert_eq!(NftModule::classes(0), Some(class));
+		let nft = NftModule::nfts(0, 1).unwrap();
+		assert_eq!((nft.owner, nft.metadata_hash, nft.media_hash), (2, None, None));
+		let ids_of = |owner| {
+			let mut ids: Vec<_> = NftModule::tokens_of_owner(owner, 0, None, 10)
+				.into_iter()
+				.map(|(nft_id, _)| nft_id)
+				.collect();
+			ids.sort();
+			ids
+		};
+		assert_eq!(ids_of(1), vec![0, 2]);
+		assert_eq!(ids_of(2), vec![1]);
+
+		// a second run leaves the migrated storage alone
+		migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(NftModule::nfts(0, 1).unwrap().metadata_hash, None);
+	});
+}
@@ -0,0 +1,170 @@
+//! Placeholder weights for pallet_nft
+//!
+//! THESE ARE NOT BENCHMARK RESULTS. The storage reads and writes follow the code of each call,
+//! the execution times are round guesses meant to err on the high side. Replace this file with
+//! the output of the `benchmark pallet` command below, run on reference hardware, before relying
+//! on it in production. The command needs a template producing the `WeightInfo` trait, such as
+//! `.maintain/frame-weight-template.hbs` of Substrate, which is not part of this repository.
+
+// Command to generate:
+// ./target/release/node-template
+// benchmark
+// pallet
+// --chain=dev
+// --steps=50
+// --repeat=20
+// --pallet=pallet_nft
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --output=pallets/nft/src/weights.rs
+// --template=<path to frame-weight-template.hbs>
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_nft.
+pub trait WeightInfo {
+	fn create_class(n: u32, ) -> Weight;
+	fn mint(n: u32, ) -> Weight;
+	fn transfer() -> Weight;
+	fn burn() -> Weight;
+	fn report_metadata() -> Weight;
+	fn set_metadata_verifiers(n: u32, ) -> Weight;
+	fn set_user() -> Weight;
+}
+
+/// Weights for pallet_nft using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: NftModule NextClassId (r:1 w:1)
+	// Storage: NftModule Classes (r:0 w:1)
+	/// The range of component `n` is `[0, 4096]`.
+	fn create_class(n: u32, ) -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(n as u64))
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule NextNftId (r:1 w:1)
+	// Storage: NftModule Nfts (r:0 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:1)
+	// Storage: NftModule PendingMetadata (r:0 w:1)
+	/// The range of component `n` is `[0, 4096]`.
+	fn mint(n: u32, ) -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(3_000 as u64).saturating_mul(n as u64))
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	fn transfer() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:1)
+	// Storage: NftModule PendingMetadata (r:0 w:1)
+	// Storage: NftModule MetadataStatusOf (r:0 w:1)
+	fn burn() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(5 as u64))
+	}
+	// Storage: NftModule PendingMetadata (r:1 w:1)
+	// Storage: NftModule MetadataStatusOf (r:0 w:1)
+	fn report_metadata() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule MetadataVerifiers (r:0 w:1)
+	/// The range of component `n` is `[0, 16]`.
+	fn set_metadata_verifiers(n: u32, ) -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(100_000 as u64).saturating_mul(n as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:1)
+	fn set_user() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: NftModule NextClassId (r:1 w:1)
+	// Storage: NftModule Classes (r:0 w:1)
+	/// The range of component `n` is `[0, 4096]`.
+	fn create_class(n: u32, ) -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_000 as u64).saturating_mul(n as u64))
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule NextNftId (r:1 w:1)
+	// Storage: NftModule Nfts (r:0 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:1)
+	// Storage: NftModule PendingMetadata (r:0 w:1)
+	/// The range of component `n` is `[0, 4096]`.
+	fn mint(n: u32, ) -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(3_000 as u64).saturating_mul(n as u64))
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	fn transfer() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:1)
+	// Storage: NftModule PendingMetadata (r:0 w:1)
+	// Storage: NftModule MetadataStatusOf (r:0 w:1)
+	fn burn() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(5 as u64))
+	}
+	// Storage: NftModule PendingMetadata (r:1 w:1)
+	// Storage: NftModule MetadataStatusOf (r:0 w:1)
+	fn report_metadata() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule MetadataVerifiers (r:0 w:1)
+	/// The range of component `n` is `[0, 16]`.
+	fn set_metadata_verifiers(n: u32, ) -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(100_000 as u64).saturating_mul(n as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:1)
+	fn set_user() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+}
@@ -0,0 +1,40 @@
+//! Runtime API definition for the NFT market place pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_nft::Id;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Queries of the marketplace.
+	///
+	/// Version 2 adds `block_to_unix_ms` and `unix_ms_to_block`.
+	#[api_version(2)]
+	pub trait MarketplaceApi<Balance>
+	where
+		Balance: Codec,
+	{
+		/// Cheapest listed price of a class, `None` when nothing of the class is listed. Expired
+		/// listings are left out.
+		fn floor_price(class_id: Id) -> Option<Balance>;
+
+		/// Up to `limit` listings of a class as (price, nft id), cheapest first, without the
+		/// expired ones.
+		///
+		/// Pass the last entry of the previous page as `cursor` to get the next page.
+		fn listings(class_id: Id, limit: u32, cursor: Option<(Balance, Id)>) -> Vec<(Balance, Id)>;
+
+		/// Unix time in milliseconds at which a block is expected to be produced.
+		///
+		/// Estimated from the current block, its timestamp and the expected block time, so it
+		/// is only exact for the current block. Lets front ends show the end of auctions timed
+		/// in blocks.
+		fn block_to_unix_ms(block: u64) -> u64;
+
+		/// First block expected to be produced at or after a unix time in milliseconds.
+		///
+		/// The inverse of `block_to_unix_ms`, to pick the block bounds of an auction.
+		fn unix_ms_to_block(time: u64) -> u64;
+	}
+}
@@ -5,39 +5,232 @@ mod mock;
 
 #[cfg(test)]
 mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
+
 #[frame_support::pallet]
 pub mod pallet {
+	use crate::WeightInfo;
 	use codec::MaxEncodedLen;
 	use frame_support::pallet_prelude::*;
-	use frame_support::sp_runtime::SaturatedConversion;
+	use frame_support::sp_runtime::{
+		traits::{AccountIdConversion, AtLeast32BitUnsigned, Zero},
+		Permill, SaturatedConversion, Saturating,
+	};
+	use frame_support::storage::with_storage_layer;
 	use frame_support::traits::UnixTime;
-	use frame_support::traits::{Currency, ExistenceRequirement};
+	use frame_support::traits::{
+		BalanceStatus, Currency, ExistenceRequirement, ReservableCurrency,
+	};
+	use frame_support::PalletId;
 	use frame_system::pallet_prelude::*;
-	use pallet_nft::{Classes, Id, Nft, Nfts};
+	use pallet_nft::{Classes, Id, Nfts};
+	#[cfg(feature = "std")]
+	use serde::{Deserialize, Serialize};
+	use sp_std::vec::Vec;
+
+	const LOG_TARGET: &str = "runtime::nft_market_place";
+
+	///version of the storage layout, see `migrations.rs`
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
+	///the runtime should use this pallet as `OnTransfer` and `OnBurn` of `pallet_nft`, so that
+	///listings, auctions and swaps of a token go away when it changes hands
 	pub trait Config: frame_system::Config + pallet_nft::Config {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
-		type Currency: Currency<Self::AccountId>;
+		type Currency: ReservableCurrency<Self::AccountId>;
 		type UnixTime: UnixTime;
+		///expected ms between two blocks, turns the ms parameters into blocks for auctions timed
+		///in blocks and estimates when a block will be produced
+		#[pallet::constant]
+		type BlockTime: Get<Time>;
+		///how many listings, buyer offers, swaps and rentals the on_idle sweep may look at in one
+		///block
+		#[pallet::constant]
+		type MaxExpiredPerBlock: Get<u32>;
+		///how many listings can be bought with one `buy_many` call
+		#[pallet::constant]
+		type MaxBuyMany: Get<u32>;
+		///id of the account collecting the marketplace fee. it is given the existential deposit
+		///at genesis and by `migrations::EndowFeeAccount`, so that fees below it can be paid
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		///can pause and unpause the marketplace, a call type or a class
+		type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		///can change the marketplace parameters
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		///weight of the calls, see `weights.rs`
+		type WeightInfo: WeightInfo;
 	}
 	pub type OfferOf<T> = Offer<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
 	pub type AuctionOf<T> = Auction<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
-	type BalanceOf<T> =
+	pub type SwapOf<T> = Swap<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+	pub type BuyerOfferOf<T> = BuyerOffer<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+	pub type RentalListingOf<T> = RentalListing<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+	pub type RentalOf<T> = Rental<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+	pub type LoanOf<T> = Loan<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+	pub type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 	pub type Balance = u128;
+	///unix ms, or a block number for auctions timed in blocks
 	pub type Time = u64;
+
+	///parameters of the marketplace, changed with `set_parameters`. a field added here comes
+	///with a storage migration filling it in, see `migrations.rs`
+	#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct MarketParameters {
+		///share of every sale that goes to the marketplace account
+		pub fee_rate: Permill,
+		///highest royalty a class can ask for, a royalty set above a lowered cap is cut to it
+		pub royalty_cap: Permill,
+		///share of the highest bid a new bid must add to it
+		pub min_bid_increment: Permill,
+		///a bid less than this many ms before the end of an auction pushes the end to this
+		///many ms after the bid, auctions timed in blocks round it up to whole blocks
+		pub anti_sniping_window: Time,
+		///longest an auction can run, in ms, auctions timed in blocks round it up to whole blocks
+		pub max_auction_duration: Time,
+	}
+
+	impl Default for MarketParameters {
+		fn default() -> Self {
+			Self {
+				fee_rate: Permill::zero(),
+				royalty_cap: Permill::zero(),
+				min_bid_increment: Permill::zero(),
+				anti_sniping_window: 0,
+				// 30 days
+				max_auction_duration: 30 * 24 * 60 * 60 * 1000,
+			}
+		}
+	}
+
+	impl MarketParameters {
+		///highest `min_bid_increment`, a bid never has to add more than half of the highest one
+		pub const MAX_MIN_BID_INCREMENT: Permill = Permill::from_percent(50);
+		///highest `max_auction_duration`, a year
+		pub const MAX_AUCTION_DURATION: Time = 365 * 24 * 60 * 60 * 1000;
+
+		///the fee can't take the whole price and the fee and the royalty together can't take
+		///more than it, a bid at the end of the longest auction must still be able to push it
+		///back and neither the bid increment nor the auction duration go above their maximum
+		pub fn is_valid(&self) -> bool {
+			self.fee_rate < Permill::one() &&
+				self.fee_rate.deconstruct() + self.royalty_cap.deconstruct() <=
+					Permill::one().deconstruct() &&
+				self.min_bid_increment <= Self::MAX_MIN_BID_INCREMENT &&
+				self.anti_sniping_window < self.max_auction_duration &&
+				self.max_auction_duration <= Self::MAX_AUCTION_DURATION
+		}
+	}
+
+	///`MarketParameters` as `set_parameters` takes them. a new layout of the parameters is a new
+	///variant, so that a call encoded for an older one, e.g. in a proposal made before a runtime
+	///upgrade, still decodes to what it meant
+	#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub enum VersionedMarketParameters {
+		V1(MarketParameters),
+	}
+
+	impl VersionedMarketParameters {
+		///the parameters in the current layout
+		pub fn into_latest(self) -> MarketParameters {
+			match self {
+				Self::V1(parameters) => parameters,
+			}
+		}
+	}
+
+	impl From<MarketParameters> for VersionedMarketParameters {
+		fn from(parameters: MarketParameters) -> Self {
+			Self::V1(parameters)
+		}
+	}
 	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
 	pub struct Offer<AccountId, Balance> {
 		pub nft_id: Id,
 		pub class_id: Id,
 		pub amount: Balance,
 		pub owner: AccountId,
+		///unix ms after which the listing can not be bought anymore
+		pub expires_at: Option<Time>,
+	}
+
+	///offer made by a buyer on a token, the amount is reserved until it is accepted or withdrawn
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub struct BuyerOffer<AccountId, Balance> {
+		pub buyer: AccountId,
+		pub class_id: Id,
+		pub nft_id: Id,
+		pub amount: Balance,
+		pub expires_at: Option<Time>,
+	}
+
+	impl<AccountId, Balance> Offer<AccountId, Balance> {
+		pub fn is_expired(&self, now: Time) -> bool {
+			matches!(self.expires_at, Some(expires_at) if now >= expires_at)
+		}
+	}
+
+	impl<AccountId, Balance> BuyerOffer<AccountId, Balance> {
+		pub fn is_expired(&self, now: Time) -> bool {
+			matches!(self.expires_at, Some(expires_at) if now >= expires_at)
+		}
+	}
+
+	///key of a listing in the order book, the price and the nft id big endian so that the raw
+	///storage keys of a class sort by price and then by nft id
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		Copy,
+		Eq,
+		PartialEq,
+		Ord,
+		PartialOrd,
+		MaxEncodedLen,
+		RuntimeDebug,
+		TypeInfo,
+	)]
+	pub struct OrderKey([u8; 16], [u8; 8]);
+
+	impl OrderKey {
+		pub fn new<Balance: AtLeast32BitUnsigned>(price: Balance, nft_id: Id) -> Self {
+			Self(price.saturated_into::<u128>().to_be_bytes(), nft_id.to_be_bytes())
+		}
+
+		pub fn price<Balance: AtLeast32BitUnsigned>(&self) -> Balance {
+			u128::from_be_bytes(self.0).saturated_into()
+		}
+
+		pub fn nft_id(&self) -> Id {
+			Id::from_be_bytes(self.1)
+		}
 	}
 
 	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
@@ -50,6 +243,168 @@ pub mod pallet {
 		pub amount: Balance,
 		pub start_time: Time,
 		pub end_time: Time,
+		///whether `start_time` and `end_time` are unix ms or block numbers
+		pub time_base: TimeBase,
+	}
+
+	///clock an auction runs on. block numbers can't be skewed by the block author the way the
+	///timestamp can, so they give auctions a deterministic end
+	#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum TimeBase {
+		UnixMs,
+		Block,
+	}
+
+	impl Default for TimeBase {
+		fn default() -> Self {
+			TimeBase::UnixMs
+		}
+	}
+
+	///terms an owner rents a token out on, the rent is paid upfront for a number of periods
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub struct RentalListing<AccountId, Balance, BlockNumber> {
+		pub owner: AccountId,
+		pub price_per_period: Balance,
+		///length of a period in blocks
+		pub period: BlockNumber,
+		///most periods the token can be rented for at once
+		pub max_periods: u32,
+	}
+
+	///running rental, the whole rent is reserved on the renter when it starts and released to
+	///the owner as the periods go by
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub struct Rental<AccountId, Balance, BlockNumber> {
+		pub owner: AccountId,
+		pub renter: AccountId,
+		pub price_per_period: Balance,
+		pub period: BlockNumber,
+		pub periods: u32,
+		///block the rental started at
+		pub start: BlockNumber,
+		///rent already paid out to the owner
+		pub released: Balance,
+	}
+
+	impl<AccountId, Balance, BlockNumber> Rental<AccountId, Balance, BlockNumber>
+	where
+		Balance: AtLeast32BitUnsigned + Copy,
+		BlockNumber: AtLeast32BitUnsigned + Copy,
+	{
+		///block at which the user right of the renter lapses
+		pub fn end(&self) -> BlockNumber {
+			self.start.saturating_add(self.period.saturating_mul(self.periods.into()))
+		}
+
+		pub fn total(&self) -> Balance {
+			self.price_per_period.saturating_mul(self.periods.into())
+		}
+
+		///rent of the periods over at `now` that was not paid out yet
+		pub fn due(&self, now: BlockNumber) -> Balance {
+			let elapsed = (now.saturating_sub(self.start) / self.period.max(1u32.into()))
+				.saturated_into::<u32>()
+				.min(self.periods);
+			self.price_per_period
+				.saturating_mul(elapsed.into())
+				.saturating_sub(self.released)
+		}
+	}
+
+	///loan asked for by the owner of a token, the token stays in escrow with the marketplace
+	///until the loan is repaid or foreclosed or the request is cancelled
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub struct Loan<AccountId, Balance, BlockNumber> {
+		pub borrower: AccountId,
+		pub amount: Balance,
+		///paid to the lender on top of `amount`
+		pub interest: Balance,
+		///blocks the borrower has to repay once the loan is funded
+		pub duration: BlockNumber,
+		pub state: LoanState<AccountId, BlockNumber>,
+	}
+
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub enum LoanState<AccountId, BlockNumber> {
+		///waiting for a lender
+		Requested,
+		///the borrower can repay until block `deadline`, after it the lender can foreclose
+		Funded { lender: AccountId, deadline: BlockNumber },
+	}
+
+	///what the creator of a swap wants back, a given token or any token of a class
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub enum SwapTarget {
+		Token { class_id: Id, nft_id: Id },
+		AnyOfClass { class_id: Id },
+	}
+
+	///balance top-up attached to a swap, seen from the side of the swap creator
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub enum PriceDirection<Balance> {
+		///the creator pays this amount to the claimer, it is reserved when the swap is created
+		Send(Balance),
+		///the claimer pays this amount to the creator
+		Receive(Balance),
+	}
+
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub struct Swap<AccountId, Balance> {
+		pub owner: AccountId,
+		pub class_id: Id,
+		pub nft_id: Id,
+		pub target: SwapTarget,
+		pub price: Option<PriceDirection<Balance>>,
+		pub deadline: Time,
+	}
+
+	#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub enum BuyMode {
+		///if one listing can not be bought nothing is bought
+		AllOrNothing,
+		///buy what can be bought and skip the rest
+		BestEffort,
+	}
+
+	///calls that can be paused on their own, cancelling and withdrawing are never paused so that
+	///users can always get their tokens and reserved funds back
+	#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub enum MarketCall {
+		///`create_offer`
+		Listing,
+		///`create_auction` and `buy_auction`
+		Auction,
+		///`buy_offer`, `buy_many` and `accept_offer`
+		Buy,
+		///`create_swap` and `claim_swap`
+		Swap,
+		///`make_offer`
+		Offer,
+		///`list_for_rent` and `rent`
+		Rental,
+		///`request_loan` and `fund_loan`
+		Loan,
+	}
+
+	///what a pause applies to
+	#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+	pub enum PauseScope {
+		///every pausable call
+		All,
+		Call(MarketCall),
+		///pausable calls on the tokens of a class
+		Class(Id),
+	}
+
+	impl SwapTarget {
+		pub fn matches(&self, class_id: Id, nft_id: Id) -> bool {
+			match *self {
+				SwapTarget::Token { class_id: c, nft_id: n } => c == class_id && n == nft_id,
+				SwapTarget::AnyOfClass { class_id: c } => c == class_id,
+			}
+		}
 	}
 
 	#[pallet::storage]
@@ -74,6 +429,203 @@ pub mod pallet {
 		AuctionOf<T>, // auctions
 	>;
 
+	///listings of every class by price and nft id, the keys of a class are iterated from the
+	///cheapest listing
+	#[pallet::storage]
+	pub type OrderBook<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		Id, // class id
+		Identity,
+		OrderKey,
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn buyer_offers)]
+	pub type BuyerOffers<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		(Id, Id), // class id, nft id
+		Twox64Concat,
+		T::AccountId,    // buyer
+		BuyerOfferOf<T>, // buyer offers
+	>;
+
+	///raw key of the last listing looked at by the expiry sweep
+	#[pallet::storage]
+	pub type ListingSweepCursor<T> = StorageValue<_, Vec<u8>>;
+
+	///raw key of the last buyer offer looked at by the expiry sweep
+	#[pallet::storage]
+	pub type BuyerOfferSweepCursor<T> = StorageValue<_, Vec<u8>>;
+
+	///raw key of the last swap looked at by the expiry sweep
+	#[pallet::storage]
+	pub type SwapSweepCursor<T> = StorageValue<_, Vec<u8>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn rental_listings)]
+	pub type RentalListings<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		Id, // class id
+		Twox64Concat,
+		Id,                 // nft id
+		RentalListingOf<T>, // rental terms
+	>;
+
+	///rentals whose rent was not fully paid out, kept after the end until the owner claims the
+	///rest, the token changes hands or the sweep gets to it
+	#[pallet::storage]
+	#[pallet::getter(fn rentals)]
+	pub type Rentals<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		Id, // class id
+		Twox64Concat,
+		Id,          // nft id
+		RentalOf<T>, // rentals
+	>;
+
+	///raw key of the last rental looked at by the expiry sweep
+	#[pallet::storage]
+	pub type RentalSweepCursor<T> = StorageValue<_, Vec<u8>>;
+
+	///loans backed by a token in escrow, a token backs at most one loan
+	#[pallet::storage]
+	#[pallet::getter(fn loans)]
+	pub type Loans<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		Id, // class id
+		Twox64Concat,
+		Id,        // nft id
+		LoanOf<T>, // loans
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_swap_id)]
+	pub type NextSwapId<T> = StorageValue<_, Id>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn swaps)]
+	pub type Swaps<T: Config> = StorageMap<_, Twox64Concat, Id, SwapOf<T>>;
+
+	///swap in which a token is offered, a token can only be offered in one swap at a time
+	#[pallet::storage]
+	#[pallet::getter(fn nft_swaps)]
+	pub type NftSwaps<T> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		Id, // class id
+		Twox64Concat,
+		Id, // nft id
+		Id, // swap id
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn parameters)]
+	pub type Parameters<T> = StorageValue<_, MarketParameters, ValueQuery>;
+
+	///share of every sale of a token of the class that goes to the class owner
+	#[pallet::storage]
+	#[pallet::getter(fn royalties)]
+	pub type Royalties<T> = StorageMap<_, Twox64Concat, Id, Permill, ValueQuery>;
+
+	///scopes paused by `PauseOrigin`
+	#[pallet::storage]
+	pub type Paused<T> = StorageMap<_, Blake2_128Concat, PauseScope, ()>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub parameters: MarketParameters,
+		///royalty of a class created in the genesis of `pallet_nft`
+		pub royalties: Vec<(Id, Permill)>,
+		///(class id, nft id, price, unix ms it expires at) listed by the owner of a token minted
+		///at genesis
+		pub listings: Vec<(Id, Id, BalanceOf<T>, Option<Time>)>,
+		///(class id, nft id, reserve price, start time, end time, time base) auctioned by the
+		///owner of a token minted at genesis, without bids
+		pub auctions: Vec<(Id, Id, BalanceOf<T>, Time, Time, TimeBase)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				parameters: Default::default(),
+				royalties: Vec::new(),
+				listings: Vec::new(),
+				auctions: Vec::new(),
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			assert!(self.parameters.is_valid(), "invalid marketplace parameters");
+			<Parameters<T>>::put(self.parameters);
+			for (class_id, royalty) in &self.royalties {
+				assert!(
+					<Classes<T>>::contains_key(class_id),
+					"royalty of unknown class {}",
+					class_id
+				);
+				assert!(
+					*royalty <= self.parameters.royalty_cap,
+					"royalty of class {} is above the cap",
+					class_id
+				);
+				<Royalties<T>>::insert(class_id, royalty);
+			}
+			for (class_id, nft_id, amount, expires_at) in &self.listings {
+				let nft = <Nfts<T>>::get(class_id, nft_id)
+					.unwrap_or_else(|| panic!("listed nft {}/{} is not minted", class_id, nft_id));
+				let offer = Offer {
+					nft_id: *nft_id,
+					class_id: *class_id,
+					amount: *amount,
+					owner: nft.owner,
+					expires_at: *expires_at,
+				};
+				Pallet::<T>::insert_listing(offer);
+			}
+			for (class_id, nft_id, amount, start_time, end_time, time_base) in &self.auctions {
+				let nft = <Nfts<T>>::get(class_id, nft_id).unwrap_or_else(|| {
+					panic!("auctioned nft {}/{} is not minted", class_id, nft_id)
+				});
+				assert!(
+					!<Offers<T>>::contains_key(class_id, nft_id),
+					"nft {}/{} is both listed and auctioned",
+					class_id,
+					nft_id
+				);
+				assert!(
+					end_time > start_time,
+					"auction of nft {}/{} ends before it starts",
+					class_id,
+					nft_id
+				);
+				let auction = Auction {
+					spender: nft.owner.clone(),
+					class_id: *class_id,
+					nft_id: *nft_id,
+					owner: nft.owner,
+					amount: *amount,
+					min_amount: Zero::zero(),
+					start_time: *start_time,
+					end_time: *end_time,
+					time_base: *time_base,
+				};
+				<Auctions<T>>::insert(class_id, nft_id, auction);
+			}
+			Pallet::<T>::endow_fee_account();
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -88,6 +640,7 @@ pub mod pallet {
 			sender: T::AccountId,
 			amount: BalanceOf<T>,
 			nft_id: Id,
+			class_id: Id,
 		},
 		AuctionCreated {
 			class_id: Id,
@@ -97,6 +650,7 @@ pub mod pallet {
 			amount: BalanceOf<T>,
 			start_time: Time,
 			end_time: Time,
+			time_base: TimeBase,
 		},
 		NewUserAuctioner {
 			nft_id: Id,
@@ -124,6 +678,178 @@ pub mod pallet {
 			nft_id: Id,
 			class_id: Id,
 		},
+		SwapCreated {
+			swap_id: Id,
+			owner: T::AccountId,
+			class_id: Id,
+			nft_id: Id,
+			target: SwapTarget,
+			price: Option<PriceDirection<BalanceOf<T>>>,
+			deadline: Time,
+		},
+		SwapClaimed {
+			swap_id: Id,
+			owner: T::AccountId,
+			claimer: T::AccountId,
+			class_id: Id,
+			nft_id: Id,
+			received_class_id: Id,
+			received_nft_id: Id,
+			price: Option<PriceDirection<BalanceOf<T>>>,
+		},
+		SwapCanceled {
+			swap_id: Id,
+			owner: T::AccountId,
+		},
+		///the deadline of the swap passed, its top-up is given back to the owner
+		SwapExpired {
+			swap_id: Id,
+			owner: T::AccountId,
+		},
+		OfferExpired {
+			class_id: Id,
+			nft_id: Id,
+			owner: T::AccountId,
+		},
+		BuyerOfferCreated {
+			class_id: Id,
+			nft_id: Id,
+			buyer: T::AccountId,
+			amount: BalanceOf<T>,
+			expires_at: Option<Time>,
+		},
+		BuyerOfferWithdrawn {
+			class_id: Id,
+			nft_id: Id,
+			buyer: T::AccountId,
+		},
+		BuyerOfferAccepted {
+			class_id: Id,
+			nft_id: Id,
+			seller: T::AccountId,
+			buyer: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		BuyerOfferExpired {
+			class_id: Id,
+			nft_id: Id,
+			buyer: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		BuyerOfferCanceled {
+			class_id: Id,
+			nft_id: Id,
+			buyer: T::AccountId,
+		},
+		BatchItemBought {
+			class_id: Id,
+			nft_id: Id,
+			seller: T::AccountId,
+			buyer: T::AccountId,
+			price: BalanceOf<T>,
+		},
+		BatchItemFailed {
+			class_id: Id,
+			nft_id: Id,
+			error: DispatchError,
+		},
+		BatchBought {
+			buyer: T::AccountId,
+			bought: u32,
+			spent: BalanceOf<T>,
+		},
+		RoyaltySet {
+			class_id: Id,
+			royalty: Permill,
+		},
+		MarketPaused {
+			scope: PauseScope,
+		},
+		MarketUnpaused {
+			scope: PauseScope,
+		},
+		ParametersSet {
+			parameters: MarketParameters,
+		},
+		///a bid close to the end of the auction pushed the end back
+		AuctionExtended {
+			class_id: Id,
+			nft_id: Id,
+			end_time: Time,
+		},
+		RentalListed {
+			class_id: Id,
+			nft_id: Id,
+			owner: T::AccountId,
+			price_per_period: BalanceOf<T>,
+			period: T::BlockNumber,
+			max_periods: u32,
+		},
+		RentalListingCanceled {
+			class_id: Id,
+			nft_id: Id,
+		},
+		///`renter` paid `rent` upfront and uses the token until block `expires`
+		Rented {
+			class_id: Id,
+			nft_id: Id,
+			owner: T::AccountId,
+			renter: T::AccountId,
+			periods: u32,
+			rent: BalanceOf<T>,
+			expires: T::BlockNumber,
+		},
+		RentClaimed {
+			class_id: Id,
+			nft_id: Id,
+			owner: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		///the rental is over and the rest of its rent was paid out to the owner
+		RentalEnded {
+			class_id: Id,
+			nft_id: Id,
+			owner: T::AccountId,
+			renter: T::AccountId,
+		},
+		///the token went into escrow for a loan of `amount`
+		LoanRequested {
+			class_id: Id,
+			nft_id: Id,
+			borrower: T::AccountId,
+			amount: BalanceOf<T>,
+			interest: BalanceOf<T>,
+			duration: T::BlockNumber,
+		},
+		///the token went back to the borrower
+		LoanRequestCanceled {
+			class_id: Id,
+			nft_id: Id,
+			borrower: T::AccountId,
+		},
+		LoanFunded {
+			class_id: Id,
+			nft_id: Id,
+			borrower: T::AccountId,
+			lender: T::AccountId,
+			amount: BalanceOf<T>,
+			deadline: T::BlockNumber,
+		},
+		///the borrower paid `amount` and interest back and got the token back
+		LoanRepaid {
+			class_id: Id,
+			nft_id: Id,
+			borrower: T::AccountId,
+			lender: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		///the loan was not repaid in time, the lender got the token
+		LoanForeclosed {
+			class_id: Id,
+			nft_id: Id,
+			borrower: T::AccountId,
+			lender: T::AccountId,
+		},
 	}
 
 	#[pallet::error]
@@ -145,32 +871,92 @@ pub mod pallet {
 		OfferNotFound,
 		NftNotFound,
 		AuctionNotFound,
+		SwapNotFound,
+		SwapExpired,
+		SwapTargetMismatch,
+		///less than the price is reserved on the payer, e.g. after part of it was slashed
+		InsufficientReserve,
+		CantSwapWithYourself,
+		AuctionIsRun,
+		OfferExpired,
+		CantOfferOnYourNft,
+		SwapIsRun,
+		MaxTotalExceeded,
+		RoyaltyAboveCap,
+		///the call, the class or the whole marketplace is paused
+		Paused,
+		///the fee and royalty cap add up to more than 100% or the anti-sniping window is not
+		///shorter than the longest auction
+		InvalidParameters,
+		///the auction would run longer than `max_auction_duration`
+		AuctionTooLong,
+		///the token has a user, it can't be sold, auctioned, swapped or rented out until the
+		///right lapses
+		TokenRented,
+		///the period or the number of periods is 0, or more periods than listed are asked for
+		InvalidRentalTerms,
+		RentalListingNotFound,
+		RentalNotFound,
+		CantRentYourNft,
+		///no period of the rental is over since the last claim
+		NothingToClaim,
+		LoanNotFound,
+		///the amount or the duration of the loan is 0
+		InvalidLoanTerms,
+		LoanAlreadyFunded,
+		LoanNotFunded,
+		CantLendToYourself,
+		NotLender,
+		///the deadline of the loan has passed, it can only be foreclosed
+		LoanOverdue,
+		///the deadline of the loan has not passed yet
+		LoanNotOverdue,
+		///the loan is not on the terms the lender agreed to, it was requested again on others
+		LoanTermsMismatch,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::sweep_expired(remaining_weight)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
 	}
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		///the name listing instead of offer there is another features called offer for next update
 		#[pallet::call_index(0)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::create_offer())]
 		pub fn create_offer(
 			origin: OriginFor<T>,
 			class_id: Id,
 			nft_id: Id,
 			amount: BalanceOf<T>,
+			expires_at: Option<Time>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Listing, class_id)?;
 			/// make it function instead if using ut like this
 			let nft = <Nfts<T>>::get(class_id, nft_id).unwrap();
 			ensure!(nft.owner == who, Error::<T>::NotOwner);
+			Self::ensure_not_rented(class_id, nft_id)?;
 			///create function to transfer number from balance into u128 instead of doing it like this
 			ensure!(amount > 0_u128.saturated_into::<BalanceOf<T>>(), Error::<T>::AmountToLow);
-			let offer = Offer { class_id, nft_id, owner: who.clone(), amount };
-			<Offers<T>>::insert(class_id, nft_id, offer);
+			if let Some(expires_at) = expires_at {
+				ensure!(expires_at > Self::now(), Error::<T>::UnderTime);
+			}
+			let offer = Offer { class_id, nft_id, owner: who.clone(), amount, expires_at };
+			Self::insert_listing(offer);
 			Self::deposit_event(Event::OfferCreated { class_id, nft_id, owner: who, amount });
 			Ok(())
 		}
 
 		#[pallet::call_index(1)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::buy_offer())]
 		pub fn buy_offer(
 			origin: OriginFor<T>,
 			nft_id: Id,
@@ -178,43 +964,21 @@ pub mod pallet {
 			amount: BalanceOf<T>,
 		) -> DispatchResult {
 			let from = ensure_signed(origin.clone())?;
-			let offer = <Offers<T>>::get(class_id, nft_id).unwrap();
-			/// why contains key for class then nft and then get nft getting nft if not found  will throw error
-			ensure!(<Classes<T>>::contains_key(class_id), Error::<T>::ClassNotFound);
-			ensure!(<Nfts<T>>::contains_key(class_id, nft_id), Error::<T>::NftNotFound);
-			let nft = <Nfts<T>>::get(offer.class_id, offer.nft_id).unwrap();
-			///you get the offer above what is the meaning of checking it here
-			ensure!(<Offers<T>>::contains_key(class_id, nft_id), Error::<T>::OfferNotFound);
-			ensure!(offer.owner == nft.owner, Error::<T>::NftBuyed);
-			ensure!(amount >= offer.amount, Error::<T>::AmountToLow);
-			///create function for the transfer instead of using it like this
-			<T as Config>::Currency::transfer(
-				&from,
-				&nft.owner,
-				offer.amount,
-				ExistenceRequirement::KeepAlive,
-			)?;
-			///you created new nft you need to transfer the nft to the new owner
-			<Nfts<T>>::insert(
-				class_id,
+			Self::ensure_not_paused(MarketCall::Buy, class_id)?;
+			let (seller, amount) = Self::do_buy_offer(&from, class_id, nft_id, amount)?;
+			Self::deposit_event(Event::OfferBuyed {
+				buyer: from,
+				sender: seller,
+				amount,
 				nft_id,
-				Nft {
-					owner: from.clone(),
-					class_id,
-					description: nft.description.clone(),
-					name: nft.name.clone(),
-					url: nft.url.clone(),
-					external_url: nft.external_url.clone(),
-				},
-			);
-			<Offers<T>>::remove(class_id, nft_id);
-			///we need to know the price we sell nft on in the events
-			Self::deposit_event(Event::TransferNft { from: nft.owner, to: from, nft_id, class_id });
+				class_id,
+			});
 			Ok(())
 		}
 
+		///`start_time` and `end_time` are unix ms or block numbers, depending on `time_base`
 		#[pallet::call_index(2)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::create_auction())]
 		pub fn create_auction(
 			origin: OriginFor<T>,
 			class_id: Id,
@@ -222,19 +986,25 @@ pub mod pallet {
 			amount: BalanceOf<T>,
 			start_time: Time,
 			end_time: Time,
+			time_base: TimeBase,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Auction, class_id)?;
 			///dont use contains for the nft and class if you will get the key after
 			ensure!(<Classes<T>>::contains_key(class_id), Error::<T>::ClassNotFound);
 			ensure!(<Nfts<T>>::contains_key(class_id, nft_id), Error::<T>::NftNotFound);
 			///create function for getting owner
 			let nft = <Nfts<T>>::get(class_id, nft_id).unwrap();
 			ensure!(nft.owner == who, Error::<T>::NotOwner);
-			let now = Self::now();
+			Self::ensure_not_rented(class_id, nft_id)?;
+			let now = Self::now_in(time_base);
 			/// create function for transfer 
 			ensure!(amount > 0_u128.saturated_into::<BalanceOf<T>>(), Error::<T>::AmountToLow);
 			ensure!(start_time >= now, Error::<T>::UnderTime);
 			ensure!(end_time > start_time, Error::<T>::EndTimeUnderStartTime);
+			let max_duration =
+				Self::span_in(time_base, <Parameters<T>>::get().max_auction_duration);
+			ensure!(end_time - start_time <= max_duration, Error::<T>::AuctionTooLong);
 			///who is the owner the spender and we dont have current highest bidder and change amount name into higgest bid
 			///the amount params it is meant to be the min amount and amount in auction is 0
 			let auction = Auction {
@@ -246,6 +1016,7 @@ pub mod pallet {
 				min_amount: 0_u128.saturated_into::<BalanceOf<T>>(),
 				start_time,
 				end_time,
+				time_base,
 			};
 			///why clone the whole auction if it is the last time you will use it for the auction min amount you can use 0
 			/// why send the amount in the event if it is meaned to be always 0 
@@ -258,122 +1029,85 @@ pub mod pallet {
 				start_time,
 				end_time,
 				amount,
+				time_base,
 			});
 			Ok(())
 		}
 
-		// #[pallet::call_index(3)]
-		// #[pallet::weight(0)]
-		// pub fn buy_auction(
-		// 	origin: OriginFor<T>,
-		// 	nft_id: Id,
-		// 	class_id: Id,
-		// 	amount: BalanceOf<T>,
-		// ) -> DispatchResult {
-		// 	let who = ensure_signed(origin)?;
-		// 	let now = Self::now();
-		// 	ensure!(<Classes<T>>::contains_key(class_id), Error::<T>::ClassNotFound);
-		// 	ensure!(<Nfts<T>>::contains_key(class_id, nft_id), Error::<T>::NftNotFound);
-		// 	ensure!(<Auctions<T>>::contains_key(class_id, nft_id), Error::<T>::AuctionNotFound);
-		// 	let nft = <Nfts<T>>::get(class_id, nft_id).unwrap();
-		// 	let auction = <Auctions<T>>::get(class_id, nft_id).unwrap();
-		// 	ensure!(now >= auction.start_time, Error::<T>::TimeNotStarted);
-		// 	ensure!(now < auction.end_time, Error::<T>::TimeOut);
-		// 	ensure!(nft.owner != who, Error::<T>::NotOwner);
-		// 	ensure!(!<Offers<T>>::contains_key(class_id, nft_id), Error::<T>::OfferIsRun);
-		// 	ensure!(amount > auction.amount, Error::<T>::AmountToLow);
-		// 	ensure!(nft.owner == auction.owner, Error::<T>::NftBuyed);
-
-		// 	if auction.spender == nft.owner {
-		// 		<T as Config>::Currency::transfer(
-		// 			&who,
-		// 			&auction.owner,
-		// 			amount,
-		// 			ExistenceRequirement::KeepAlive,
-		// 		)?;
-		// 		<Auctions<T>>::insert(
-		// 			class_id,
-		// 			nft_id,
-		// 			Auction {
-		// 				owner: auction.owner,
-		// 				nft_id,
-		// 				spender: who.clone(),
-		// 				class_id,
-		// 				min_amount: amount,
-		// 				start_time: auction.start_time,
-		// 				end_time: auction.end_time,
-		// 				amount: auction.amount,
-		// 			},
-		// 		);
-		// 		return Ok(());
-		// 	}
-		
-		// 	<T as Config>::Currency::transfer(
-		// 		&who,
-		// 		&auction.owner,
-		// 		amount,
-		// 		ExistenceRequirement::KeepAlive,
-		// 	)?;
-		// 	<T as Config>::Currency::transfer(
-		// 		&auction.owner,
-		// 		&auction.spender,
-		// 		auction.min_amount,
-		// 		ExistenceRequirement::KeepAlive,
-		// 	)?;
-		// 	<Auctions<T>>::insert(
-		// 		class_id,
-		// 		nft_id,
-		// 		Auction {
-		// 			owner: auction.owner,
-		// 			nft_id,
-		// 			spender: who.clone(),
-		// 			class_id,
-		// 			min_amount: amount,
-		// 			start_time: auction.start_time,
-		// 			end_time: auction.end_time,
-		// 			amount: auction.amount,
-		// 		},
-		// 	);
-		// 	Self::deposit_event(Event::NewUserAuctioner {
-		// 		nft_id,
-		// 		class_id,
-		// 		auctioner: who,
-		// 		amount,
-		// 	});
-		// 	Ok(())
-		// }
+		///bid on a running auction, the bid stays reserved until someone bids higher
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::buy_auction())]
+		pub fn buy_auction(
+			origin: OriginFor<T>,
+			nft_id: Id,
+			class_id: Id,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Auction, class_id)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			let mut auction =
+				<Auctions<T>>::get(class_id, nft_id).ok_or(Error::<T>::AuctionNotFound)?;
+			let now = Self::now_in(auction.time_base);
+			ensure!(now >= auction.start_time, Error::<T>::TimeNotStarted);
+			ensure!(now < auction.end_time, Error::<T>::TimeOut);
+			ensure!(nft.owner != who, Error::<T>::NotOwner);
+			ensure!(!<Offers<T>>::contains_key(class_id, nft_id), Error::<T>::OfferIsRun);
+			ensure!(nft.owner == auction.owner, Error::<T>::NftBuyed);
+			///amount is the lowest accepted bid and min_amount the highest bid so far
+			ensure!(amount >= auction.amount, Error::<T>::AmountToLow);
+			ensure!(amount > auction.min_amount, Error::<T>::AmountToLow);
+			let parameters = <Parameters<T>>::get();
+			let increment = parameters.min_bid_increment * auction.min_amount;
+			ensure!(
+				amount >= auction.min_amount.saturating_add(increment),
+				Error::<T>::AmountToLow
+			);
+
+			<T as Config>::Currency::reserve(&who, amount)?;
+			if auction.spender != auction.owner {
+				<T as Config>::Currency::unreserve(&auction.spender, auction.min_amount);
+			}
+			auction.spender = who.clone();
+			auction.min_amount = amount;
+			let window = Self::span_in(auction.time_base, parameters.anti_sniping_window);
+			let extended = auction.end_time - now < window;
+			if extended {
+				auction.end_time = now + window;
+			}
+			let end_time = auction.end_time;
+			<Auctions<T>>::insert(class_id, nft_id, auction);
+			Self::deposit_event(Event::NewUserAuctioner {
+				nft_id,
+				class_id,
+				auctioner: who,
+				amount,
+			});
+			if extended {
+				Self::deposit_event(Event::AuctionExtended { class_id, nft_id, end_time });
+			}
+			Ok(())
+		}
 
 		#[pallet::call_index(4)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::auction_finish())]
 		pub fn auction_finish(origin: OriginFor<T>, nft_id: Id, class_id: Id) -> DispatchResult {
 			let who = ensure_signed(origin.clone())?;
-			let now = Self::now();
 			///stop using contains key
 			ensure!(<Classes<T>>::contains_key(class_id), Error::<T>::ClassNotFound);
 			ensure!(<Nfts<T>>::contains_key(class_id, nft_id), Error::<T>::NftNotFound);
 			ensure!(<Auctions<T>>::contains_key(class_id, nft_id), Error::<T>::AuctionNotFound);
 			let nft = <Nfts<T>>::get(class_id, nft_id).unwrap();
 			let auction = <Auctions<T>>::get(class_id, nft_id).unwrap();
-			///you need to let the winner and the owner finish the auction
-			ensure!(who == auction.owner, Error::<T>::OnlyOwner);
+			ensure!(who == auction.owner || who == auction.spender, Error::<T>::OnlyOwner);
+			let now = Self::now_in(auction.time_base);
 			ensure!(now >= auction.end_time, Error::<T>::WaitWhenAuctionFinish);
 			ensure!(auction.owner != auction.spender, Error::<T>::NoBodyParticipateWithThisAuction);
-			///again transfer the nft using created  function called transfer nft 
-			<Nfts<T>>::insert(
-				class_id,
-				nft_id,
-				Nft {
-					owner: auction.spender.clone(),
-					class_id,
-					description: nft.description.clone(),
-					name: nft.name.clone(),
-					url: nft.url.clone(),
-					external_url: nft.external_url.clone(),
-				},
-			);
 			<Auctions<T>>::remove(class_id, nft_id);
+			Self::settle(&auction.spender, &auction.owner, class_id, auction.min_amount, true)?;
+			Self::transfer_nft(class_id, nft_id, &auction.owner, &auction.spender)?;
 			Self::deposit_event(Event::AuctionFinished {
-				spender: auction.owner,
+				spender: auction.spender,
 				class_id: nft.class_id,
 				nft_id,
 				amount: auction.min_amount,
@@ -382,7 +1116,7 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(5)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::cancel_auction())]
 		pub fn cancel_auction(origin: OriginFor<T>, nft_id: Id, class_id: Id) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			let auction = <Auctions<T>>::get(class_id, nft_id).unwrap();
@@ -395,22 +1129,1270 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(6)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::cancel_offer())]
 		pub fn cancel_offer(origin: OriginFor<T>, nft_id: Id, class_id: Id) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			let offer = <Offers<T>>::get(class_id, nft_id).unwrap();
 			ensure!(offer.owner == who, Error::<T>::NotOwner);
 			///you already have the class id 
 			let class_id = offer.class_id;
-			<Offers<T>>::remove(class_id, nft_id);
+			Self::remove_listing(class_id, nft_id);
 			Self::deposit_event(Event::OfferCanceled { nft_id, class_id });
 			Ok(())
 		}
+
+		///offer one of your nfts in exchange for a given token or any token of a class,
+		///optionally with a balance top-up in either direction
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::create_swap())]
+		pub fn create_swap(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			target: SwapTarget,
+			price: Option<PriceDirection<BalanceOf<T>>>,
+			deadline: Time,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Swap, class_id)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner == who, Error::<T>::NotOwner);
+			ensure!(!<Auctions<T>>::contains_key(class_id, nft_id), Error::<T>::AuctionIsRun);
+			ensure!(!<NftSwaps<T>>::contains_key(class_id, nft_id), Error::<T>::SwapIsRun);
+			Self::ensure_not_rented(class_id, nft_id)?;
+			ensure!(deadline > Self::now(), Error::<T>::UnderTime);
+			if let Some(PriceDirection::Send(amount) | PriceDirection::Receive(amount)) = price {
+				ensure!(amount > 0_u128.saturated_into::<BalanceOf<T>>(), Error::<T>::AmountToLow);
+			}
+			if let Some(PriceDirection::Send(amount)) = price {
+				<T as Config>::Currency::reserve(&who, amount)?;
+			}
+			let swap_id = <NextSwapId<T>>::get().unwrap_or(0);
+			let swap = Swap {
+				owner: who.clone(),
+				class_id,
+				nft_id,
+				target: target.clone(),
+				price: price.clone(),
+				deadline,
+			};
+			<Swaps<T>>::insert(swap_id, swap);
+			<NftSwaps<T>>::insert(class_id, nft_id, swap_id);
+			<NextSwapId<T>>::put(swap_id + 1);
+			Self::deposit_event(Event::SwapCreated {
+				swap_id,
+				owner: who,
+				class_id,
+				nft_id,
+				target,
+				price,
+				deadline,
+			});
+			Ok(())
+		}
+
+		///give the token asked for by the swap and receive the offered token and top-up in return
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::claim_swap())]
+		pub fn claim_swap(
+			origin: OriginFor<T>,
+			swap_id: Id,
+			class_id: Id,
+			nft_id: Id,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let swap = <Swaps<T>>::get(swap_id).ok_or(Error::<T>::SwapNotFound)?;
+			Self::ensure_not_paused(MarketCall::Swap, swap.class_id)?;
+			Self::ensure_not_paused(MarketCall::Swap, class_id)?;
+			ensure!(swap.owner != who, Error::<T>::CantSwapWithYourself);
+			ensure!(Self::now() <= swap.deadline, Error::<T>::SwapExpired);
+			ensure!(swap.target.matches(class_id, nft_id), Error::<T>::SwapTargetMismatch);
+			let offered =
+				<Nfts<T>>::get(swap.class_id, swap.nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(offered.owner == swap.owner, Error::<T>::NftBuyed);
+			let wanted = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(wanted.owner == who, Error::<T>::NotOwner);
+			ensure!(
+				!<Auctions<T>>::contains_key(swap.class_id, swap.nft_id),
+				Error::<T>::AuctionIsRun
+			);
+			ensure!(!<Auctions<T>>::contains_key(class_id, nft_id), Error::<T>::AuctionIsRun);
+
+			<Swaps<T>>::remove(swap_id);
+			<NftSwaps<T>>::remove(swap.class_id, swap.nft_id);
+			match swap.price {
+				Some(PriceDirection::Send(amount)) => {
+					let missing = <T as Config>::Currency::repatriate_reserved(
+						&swap.owner,
+						&who,
+						amount,
+						BalanceStatus::Free,
+					)?;
+					ensure!(missing.is_zero(), Error::<T>::InsufficientReserve);
+				},
+				Some(PriceDirection::Receive(amount)) => {
+					<T as Config>::Currency::transfer(
+						&who,
+						&swap.owner,
+						amount,
+						ExistenceRequirement::KeepAlive,
+					)?;
+				},
+				None => {},
+			}
+			Self::transfer_nft(swap.class_id, swap.nft_id, &swap.owner, &who)?;
+			Self::transfer_nft(class_id, nft_id, &who, &swap.owner)?;
+			Self::deposit_event(Event::SwapClaimed {
+				swap_id,
+				owner: swap.owner,
+				claimer: who,
+				class_id: swap.class_id,
+				nft_id: swap.nft_id,
+				received_class_id: class_id,
+				received_nft_id: nft_id,
+				price: swap.price,
+			});
+			Ok(())
+		}
+
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::cancel_swap())]
+		pub fn cancel_swap(origin: OriginFor<T>, swap_id: Id) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let swap = <Swaps<T>>::get(swap_id).ok_or(Error::<T>::SwapNotFound)?;
+			ensure!(swap.owner == who, Error::<T>::NotOwner);
+			if let Some(PriceDirection::Send(amount)) = swap.price {
+				<T as Config>::Currency::unreserve(&who, amount);
+			}
+			<Swaps<T>>::remove(swap_id);
+			<NftSwaps<T>>::remove(swap.class_id, swap.nft_id);
+			Self::deposit_event(Event::SwapCanceled { swap_id, owner: who });
+			Ok(())
+		}
+
+		///offer to buy a token whether it is listed or not, a new offer replaces the old one
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::make_offer())]
+		pub fn make_offer(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			amount: BalanceOf<T>,
+			expires_at: Option<Time>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Offer, class_id)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner != who, Error::<T>::CantOfferOnYourNft);
+			ensure!(amount > 0_u128.saturated_into::<BalanceOf<T>>(), Error::<T>::AmountToLow);
+			if let Some(expires_at) = expires_at {
+				ensure!(expires_at > Self::now(), Error::<T>::UnderTime);
+			}
+			if let Some(old) = <BuyerOffers<T>>::get((class_id, nft_id), &who) {
+				<T as Config>::Currency::unreserve(&who, old.amount);
+			}
+			<T as Config>::Currency::reserve(&who, amount)?;
+			let offer = BuyerOffer { buyer: who.clone(), class_id, nft_id, amount, expires_at };
+			<BuyerOffers<T>>::insert((class_id, nft_id), &who, offer);
+			Self::deposit_event(Event::BuyerOfferCreated {
+				class_id,
+				nft_id,
+				buyer: who,
+				amount,
+				expires_at,
+			});
+			Ok(())
+		}
+
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::withdraw_offer())]
+		pub fn withdraw_offer(origin: OriginFor<T>, class_id: Id, nft_id: Id) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let offer = <BuyerOffers<T>>::take((class_id, nft_id), &who)
+				.ok_or(Error::<T>::OfferNotFound)?;
+			<T as Config>::Currency::unreserve(&who, offer.amount);
+			Self::deposit_event(Event::BuyerOfferWithdrawn { class_id, nft_id, buyer: who });
+			Ok(())
+		}
+
+		///the owner of the token sells it to the buyer for the reserved amount
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::accept_offer())]
+		pub fn accept_offer(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			buyer: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Buy, class_id)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner == who, Error::<T>::NotOwner);
+			ensure!(!<Auctions<T>>::contains_key(class_id, nft_id), Error::<T>::AuctionIsRun);
+			let offer = <BuyerOffers<T>>::get((class_id, nft_id), &buyer)
+				.ok_or(Error::<T>::OfferNotFound)?;
+			ensure!(!offer.is_expired(Self::now()), Error::<T>::OfferExpired);
+			Self::settle(&buyer, &who, class_id, offer.amount, true)?;
+			<BuyerOffers<T>>::remove((class_id, nft_id), &buyer);
+			Self::transfer_nft(class_id, nft_id, &who, &buyer)?;
+			Self::deposit_event(Event::BuyerOfferAccepted {
+				class_id,
+				nft_id,
+				seller: who,
+				buyer,
+				amount: offer.amount,
+			});
+			Ok(())
+		}
+
+		///buy several listings at once, each item is (class id, nft id, max price) and all the
+		///bought items together can not cost more than `max_total`
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::buy_many(items.len() as u32))]
+		pub fn buy_many(
+			origin: OriginFor<T>,
+			items: BoundedVec<(Id, Id, BalanceOf<T>), T::MaxBuyMany>,
+			max_total: BalanceOf<T>,
+			mode: BuyMode,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut spent = 0_u128.saturated_into::<BalanceOf<T>>();
+			let mut bought = 0_u32;
+			for (class_id, nft_id, max_price) in items {
+				let result = with_storage_layer(|| -> Result<_, DispatchError> {
+					Self::ensure_not_paused(MarketCall::Buy, class_id)?;
+					let (seller, price) = Self::do_buy_offer(&who, class_id, nft_id, max_price)?;
+					ensure!(spent.saturating_add(price) <= max_total, Error::<T>::MaxTotalExceeded);
+					Ok((seller, price))
+				});
+				match (result, mode) {
+					(Ok((seller, price)), _) => {
+						spent = spent.saturating_add(price);
+						bought += 1;
+						Self::deposit_event(Event::BatchItemBought {
+							class_id,
+							nft_id,
+							seller,
+							buyer: who.clone(),
+							price,
+						});
+					},
+					(Err(error), BuyMode::AllOrNothing) => return Err(error),
+					(Err(error), BuyMode::BestEffort) => {
+						Self::deposit_event(Event::BatchItemFailed { class_id, nft_id, error });
+					},
+				}
+			}
+			Self::deposit_event(Event::BatchBought { buyer: who, bought, spent });
+			Ok(())
+		}
+
+		///the class owner sets the share of every sale of the class paid to them, up to the cap
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::set_royalty())]
+		pub fn set_royalty(origin: OriginFor<T>, class_id: Id, royalty: Permill) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let class = <Classes<T>>::get(class_id).ok_or(Error::<T>::ClassNotFound)?;
+			ensure!(class.owner == who, Error::<T>::NotOwner);
+			ensure!(royalty <= <Parameters<T>>::get().royalty_cap, Error::<T>::RoyaltyAboveCap);
+			if royalty.is_zero() {
+				<Royalties<T>>::remove(class_id);
+			} else {
+				<Royalties<T>>::insert(class_id, royalty);
+			}
+			Self::deposit_event(Event::RoyaltySet { class_id, royalty });
+			Ok(())
+		}
+
+		///stop the calls of `scope` until it is unpaused, cancelling and withdrawing keep working
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::pause())]
+		pub fn pause(origin: OriginFor<T>, scope: PauseScope) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			<Paused<T>>::insert(scope, ());
+			Self::deposit_event(Event::MarketPaused { scope });
+			Ok(())
+		}
+
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::WeightInfo::unpause())]
+		pub fn unpause(origin: OriginFor<T>, scope: PauseScope) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			<Paused<T>>::remove(scope);
+			Self::deposit_event(Event::MarketUnpaused { scope });
+			Ok(())
+		}
+
+		///replace the marketplace parameters, running auctions keep their end time
+		#[pallet::call_index(17)]
+		#[pallet::weight(T::WeightInfo::set_parameters())]
+		pub fn set_parameters(
+			origin: OriginFor<T>,
+			parameters: VersionedMarketParameters,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let parameters = parameters.into_latest();
+			ensure!(parameters.is_valid(), Error::<T>::InvalidParameters);
+			<Parameters<T>>::put(parameters);
+			Self::deposit_event(Event::ParametersSet { parameters });
+			Ok(())
+		}
+
+		///offer the token for rent at `price_per_period` for every `period` blocks, for up to
+		///`max_periods` periods at once. listing it again replaces the terms
+		#[pallet::call_index(18)]
+		#[pallet::weight(T::WeightInfo::list_for_rent())]
+		pub fn list_for_rent(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			price_per_period: BalanceOf<T>,
+			period: T::BlockNumber,
+			max_periods: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Rental, class_id)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner == who, Error::<T>::NotOwner);
+			Self::ensure_not_rented(class_id, nft_id)?;
+			ensure!(
+				price_per_period > 0_u128.saturated_into::<BalanceOf<T>>(),
+				Error::<T>::AmountToLow
+			);
+			ensure!(!period.is_zero() && max_periods > 0, Error::<T>::InvalidRentalTerms);
+			let listing =
+				RentalListing { owner: who.clone(), price_per_period, period, max_periods };
+			<RentalListings<T>>::insert(class_id, nft_id, listing);
+			Self::deposit_event(Event::RentalListed {
+				class_id,
+				nft_id,
+				owner: who,
+				price_per_period,
+				period,
+				max_periods,
+			});
+			Ok(())
+		}
+
+		#[pallet::call_index(19)]
+		#[pallet::weight(T::WeightInfo::cancel_rental_listing())]
+		pub fn cancel_rental_listing(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let listing = <RentalListings<T>>::get(class_id, nft_id)
+				.ok_or(Error::<T>::RentalListingNotFound)?;
+			ensure!(listing.owner == who, Error::<T>::NotOwner);
+			<RentalListings<T>>::remove(class_id, nft_id);
+			Self::deposit_event(Event::RentalListingCanceled { class_id, nft_id });
+			Ok(())
+		}
+
+		///rent a token for `periods` periods, the whole rent is reserved now and paid out to the
+		///owner as the periods go by. the renter is the user of the token until the rental ends
+		///and the token can't change hands until then
+		#[pallet::call_index(20)]
+		#[pallet::weight(T::WeightInfo::rent())]
+		pub fn rent(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			periods: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Rental, class_id)?;
+			let listing = <RentalListings<T>>::get(class_id, nft_id)
+				.ok_or(Error::<T>::RentalListingNotFound)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner == listing.owner, Error::<T>::NftBuyed);
+			ensure!(nft.owner != who, Error::<T>::CantRentYourNft);
+			ensure!(periods > 0 && periods <= listing.max_periods, Error::<T>::InvalidRentalTerms);
+			ensure!(!<Offers<T>>::contains_key(class_id, nft_id), Error::<T>::OfferIsRun);
+			ensure!(!<Auctions<T>>::contains_key(class_id, nft_id), Error::<T>::AuctionIsRun);
+			ensure!(!<NftSwaps<T>>::contains_key(class_id, nft_id), Error::<T>::SwapIsRun);
+			let now = <frame_system::Pallet<T>>::block_number();
+			// pay out a rental that is over before the new one takes its place
+			Self::end_rental(class_id, nft_id, now);
+			let rental = Rental {
+				owner: nft.owner.clone(),
+				renter: who.clone(),
+				price_per_period: listing.price_per_period,
+				period: listing.period,
+				periods,
+				start: now,
+				released: Zero::zero(),
+			};
+			let (rent, expires) = (rental.total(), rental.end());
+			<T as Config>::Currency::reserve(&who, rent)?;
+			pallet_nft::Pallet::<T>::do_set_user(class_id, nft_id, who.clone(), expires)?;
+			<RentalListings<T>>::remove(class_id, nft_id);
+			<Rentals<T>>::insert(class_id, nft_id, rental);
+			Self::deposit_event(Event::Rented {
+				class_id,
+				nft_id,
+				owner: nft.owner,
+				renter: who,
+				periods,
+				rent,
+				expires,
+			});
+			Ok(())
+		}
+
+		///pay out the rent of the periods over since the last claim to the owner, the rental
+		///ends once it is over and all of its rent is paid out
+		#[pallet::call_index(21)]
+		#[pallet::weight(T::WeightInfo::claim_rent())]
+		pub fn claim_rent(origin: OriginFor<T>, class_id: Id, nft_id: Id) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut rental =
+				<Rentals<T>>::get(class_id, nft_id).ok_or(Error::<T>::RentalNotFound)?;
+			ensure!(rental.owner == who, Error::<T>::NotOwner);
+			let now = <frame_system::Pallet<T>>::block_number();
+			if Self::end_rental(class_id, nft_id, now) {
+				return Ok(())
+			}
+			let amount = rental.due(now);
+			ensure!(!amount.is_zero(), Error::<T>::NothingToClaim);
+			Self::pay(&rental.renter, &who, amount, true)?;
+			rental.released = rental.released.saturating_add(amount);
+			<Rentals<T>>::insert(class_id, nft_id, rental);
+			Self::deposit_event(Event::RentClaimed { class_id, nft_id, owner: who, amount });
+			Ok(())
+		}
+
+		///ask for a loan of `amount` to be repaid with `interest` within `duration` blocks of
+		///being funded, the token goes into escrow until then
+		#[pallet::call_index(22)]
+		#[pallet::weight(T::WeightInfo::request_loan())]
+		pub fn request_loan(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			amount: BalanceOf<T>,
+			interest: BalanceOf<T>,
+			duration: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Loan, class_id)?;
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(nft.owner == who, Error::<T>::NotOwner);
+			Self::ensure_not_rented(class_id, nft_id)?;
+			ensure!(!<Auctions<T>>::contains_key(class_id, nft_id), Error::<T>::AuctionIsRun);
+			ensure!(!<NftSwaps<T>>::contains_key(class_id, nft_id), Error::<T>::SwapIsRun);
+			ensure!(!amount.is_zero() && !duration.is_zero(), Error::<T>::InvalidLoanTerms);
+			// the listing of the token goes away with the transfer
+			Self::transfer_nft(class_id, nft_id, &who, &Self::escrow_account())?;
+			let loan = Loan {
+				borrower: who.clone(),
+				amount,
+				interest,
+				duration,
+				state: LoanState::Requested,
+			};
+			<Loans<T>>::insert(class_id, nft_id, loan);
+			Self::deposit_event(Event::LoanRequested {
+				class_id,
+				nft_id,
+				borrower: who,
+				amount,
+				interest,
+				duration,
+			});
+			Ok(())
+		}
+
+		///take the token back out of escrow while no lender funded the loan
+		#[pallet::call_index(23)]
+		#[pallet::weight(T::WeightInfo::cancel_loan_request())]
+		pub fn cancel_loan_request(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let loan = <Loans<T>>::get(class_id, nft_id).ok_or(Error::<T>::LoanNotFound)?;
+			ensure!(loan.borrower == who, Error::<T>::NotOwner);
+			ensure!(loan.state == LoanState::Requested, Error::<T>::LoanAlreadyFunded);
+			<Loans<T>>::remove(class_id, nft_id);
+			Self::transfer_nft(class_id, nft_id, &Self::escrow_account(), &who)?;
+			Self::deposit_event(Event::LoanRequestCanceled { class_id, nft_id, borrower: who });
+			Ok(())
+		}
+
+		///pay the amount of the loan to the borrower, the deadline to repay starts now. the
+		///lender passes the terms it agrees to, the borrower may have requested the loan again
+		///on other terms in the meantime
+		#[pallet::call_index(24)]
+		#[pallet::weight(T::WeightInfo::fund_loan())]
+		pub fn fund_loan(
+			origin: OriginFor<T>,
+			class_id: Id,
+			nft_id: Id,
+			amount: BalanceOf<T>,
+			interest: BalanceOf<T>,
+			duration: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused(MarketCall::Loan, class_id)?;
+			let mut loan = <Loans<T>>::get(class_id, nft_id).ok_or(Error::<T>::LoanNotFound)?;
+			ensure!(loan.borrower != who, Error::<T>::CantLendToYourself);
+			ensure!(loan.state == LoanState::Requested, Error::<T>::LoanAlreadyFunded);
+			ensure!(
+				(loan.amount, loan.interest, loan.duration) == (amount, interest, duration),
+				Error::<T>::LoanTermsMismatch
+			);
+			<T as Config>::Currency::transfer(
+				&who,
+				&loan.borrower,
+				loan.amount,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			let deadline = <frame_system::Pallet<T>>::block_number().saturating_add(loan.duration);
+			loan.state = LoanState::Funded { lender: who.clone(), deadline };
+			Self::deposit_event(Event::LoanFunded {
+				class_id,
+				nft_id,
+				borrower: loan.borrower.clone(),
+				lender: who,
+				amount: loan.amount,
+				deadline,
+			});
+			<Loans<T>>::insert(class_id, nft_id, loan);
+			Ok(())
+		}
+
+		///pay the amount and the interest to the lender and get the token back, up to the
+		///deadline. repaying and foreclosing are never paused
+		#[pallet::call_index(25)]
+		#[pallet::weight(T::WeightInfo::repay_loan())]
+		pub fn repay_loan(origin: OriginFor<T>, class_id: Id, nft_id: Id) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let loan = <Loans<T>>::get(class_id, nft_id).ok_or(Error::<T>::LoanNotFound)?;
+			ensure!(loan.borrower == who, Error::<T>::NotOwner);
+			let (lender, deadline) = match loan.state {
+				LoanState::Funded { lender, deadline } => (lender, deadline),
+				LoanState::Requested => return Err(Error::<T>::LoanNotFunded.into()),
+			};
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now <= deadline, Error::<T>::LoanOverdue);
+			let amount = loan.amount.saturating_add(loan.interest);
+			<T as Config>::Currency::transfer(
+				&who,
+				&lender,
+				amount,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			<Loans<T>>::remove(class_id, nft_id);
+			Self::transfer_nft(class_id, nft_id, &Self::escrow_account(), &who)?;
+			Self::deposit_event(Event::LoanRepaid {
+				class_id,
+				nft_id,
+				borrower: who,
+				lender,
+				amount,
+			});
+			Ok(())
+		}
+
+		///the lender takes the token of a loan that was not repaid by its deadline
+		#[pallet::call_index(26)]
+		#[pallet::weight(T::WeightInfo::foreclose())]
+		pub fn foreclose(origin: OriginFor<T>, class_id: Id, nft_id: Id) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let loan = <Loans<T>>::get(class_id, nft_id).ok_or(Error::<T>::LoanNotFound)?;
+			let deadline = match loan.state {
+				LoanState::Funded { lender, deadline } if lender == who => deadline,
+				LoanState::Funded { .. } => return Err(Error::<T>::NotLender.into()),
+				LoanState::Requested => return Err(Error::<T>::LoanNotFunded.into()),
+			};
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now > deadline, Error::<T>::LoanNotOverdue);
+			<Loans<T>>::remove(class_id, nft_id);
+			Self::transfer_nft(class_id, nft_id, &Self::escrow_account(), &who)?;
+			Self::deposit_event(Event::LoanForeclosed {
+				class_id,
+				nft_id,
+				borrower: loan.borrower,
+				lender: who,
+			});
+			Ok(())
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
 		pub fn now() -> u64 {
 			T::UnixTime::now().as_millis().saturated_into::<u64>()
 		}
+
+		///current unix ms or block number
+		pub fn now_in(time_base: TimeBase) -> Time {
+			match time_base {
+				TimeBase::UnixMs => Self::now(),
+				TimeBase::Block =>
+					<frame_system::Pallet<T>>::block_number().saturated_into::<Time>(),
+			}
+		}
+
+		///`ms` in the given time base, rounded up to whole blocks
+		pub fn span_in(time_base: TimeBase, ms: Time) -> Time {
+			match time_base {
+				TimeBase::UnixMs => ms,
+				TimeBase::Block => {
+					let block_time = T::BlockTime::get().max(1);
+					ms / block_time + (ms % block_time != 0) as Time
+				},
+			}
+		}
+
+		///whether `call` on the tokens of `class_id` is paused, by itself, for the class or for
+		///the whole marketplace
+		pub fn is_paused(call: MarketCall, class_id: Id) -> bool {
+			[PauseScope::All, PauseScope::Call(call), PauseScope::Class(class_id)]
+				.iter()
+				.any(<Paused<T>>::contains_key)
+		}
+
+		fn ensure_not_paused(call: MarketCall, class_id: Id) -> DispatchResult {
+			ensure!(!Self::is_paused(call, class_id), Error::<T>::Paused);
+			Ok(())
+		}
+
+		fn ensure_not_rented(class_id: Id, nft_id: Id) -> DispatchResult {
+			ensure!(
+				pallet_nft::Pallet::<T>::user_of(class_id, nft_id).is_none(),
+				Error::<T>::TokenRented
+			);
+			Ok(())
+		}
+
+		///account the marketplace fee is paid to
+		pub fn fee_account() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		///give the fee account the existential deposit when it does not exist yet, a fee below
+		///the existential deposit could not be paid to it. returns whether it was endowed
+		pub fn endow_fee_account() -> bool {
+			let account = Self::fee_account();
+			if !<T as Config>::Currency::total_balance(&account).is_zero() {
+				return false
+			}
+			let minimum = <T as Config>::Currency::minimum_balance();
+			<T as Config>::Currency::make_free_balance_be(&account, minimum);
+			true
+		}
+
+		///account holding the tokens that back a loan
+		pub fn escrow_account() -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(b"escrow")
+		}
+
+		///pay the seller for a sale, less the marketplace fee and the royalty of the class which
+		///go to the fee account and the class owner. `reserved` tells whether the price is
+		///reserved on the buyer (bids, buyer offers) or still free (listings)
+		fn settle(
+			buyer: &T::AccountId,
+			seller: &T::AccountId,
+			class_id: Id,
+			price: BalanceOf<T>,
+			reserved: bool,
+		) -> DispatchResult {
+			let parameters = <Parameters<T>>::get();
+			let fee = parameters.fee_rate * price;
+			Self::pay(buyer, &Self::fee_account(), fee, reserved)?;
+			let mut rest = price.saturating_sub(fee);
+			if let Some(class) = <Classes<T>>::get(class_id) {
+				if &class.owner != seller {
+					// the cap may have been lowered since the royalty was set
+					let rate = <Royalties<T>>::get(class_id).min(parameters.royalty_cap);
+					let royalty = rate * price;
+					Self::pay(buyer, &class.owner, royalty, reserved)?;
+					rest = rest.saturating_sub(royalty);
+				}
+			}
+			Self::pay(buyer, seller, rest, reserved)
+		}
+
+		fn pay(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			amount: BalanceOf<T>,
+			reserved: bool,
+		) -> DispatchResult {
+			if amount.is_zero() {
+				return Ok(())
+			}
+			if reserved {
+				let missing = <T as Config>::Currency::repatriate_reserved(
+					from,
+					to,
+					amount,
+					BalanceStatus::Free,
+				)?;
+				ensure!(missing.is_zero(), Error::<T>::InsufficientReserve);
+			} else {
+				<T as Config>::Currency::transfer(
+					from,
+					to,
+					amount,
+					ExistenceRequirement::KeepAlive,
+				)?;
+			}
+			Ok(())
+		}
+
+		///move the nft to the new owner, the `OnTransfer` hook drops whatever is still listed for
+		///it so callers take their own listing, auction or swap out before calling this
+		fn transfer_nft(
+			class_id: Id,
+			nft_id: Id,
+			from: &T::AccountId,
+			to: &T::AccountId,
+		) -> DispatchResult {
+			pallet_nft::Pallet::<T>::do_transfer(class_id, nft_id, to)?;
+			Self::deposit_event(Event::TransferNft {
+				from: from.clone(),
+				to: to.clone(),
+				nft_id,
+				class_id,
+			});
+			Ok(())
+		}
+
+		///drop the listing, rental listing, auction and swap of a token, bids and swap top-ups
+		///are given back and the rest of the rent of a rental that is over is paid out
+		fn cancel_listings(class_id: Id, nft_id: Id) {
+			if Self::remove_listing(class_id, nft_id).is_some() {
+				Self::deposit_event(Event::OfferCanceled { class_id, nft_id });
+			}
+			if <RentalListings<T>>::take(class_id, nft_id).is_some() {
+				Self::deposit_event(Event::RentalListingCanceled { class_id, nft_id });
+			}
+			// the token can only change hands or be burned once its rental is over
+			Self::end_rental(class_id, nft_id, <frame_system::Pallet<T>>::block_number());
+			if let Some(auction) = <Auctions<T>>::take(class_id, nft_id) {
+				if auction.spender != auction.owner {
+					<T as Config>::Currency::unreserve(&auction.spender, auction.min_amount);
+				}
+				Self::deposit_event(Event::AuctionCanceled { class_id, nft_id });
+			}
+			if let Some(swap_id) = <NftSwaps<T>>::take(class_id, nft_id) {
+				if let Some(swap) = <Swaps<T>>::take(swap_id) {
+					if let Some(PriceDirection::Send(amount)) = swap.price {
+						<T as Config>::Currency::unreserve(&swap.owner, amount);
+					}
+					Self::deposit_event(Event::SwapCanceled { swap_id, owner: swap.owner });
+				}
+			}
+		}
+
+		///pay out the rest of the rent of a rental that is over and drop it along with the user
+		///right of the renter, returns whether there was one
+		fn end_rental(class_id: Id, nft_id: Id, now: T::BlockNumber) -> bool {
+			let rental = match <Rentals<T>>::get(class_id, nft_id) {
+				Some(rental) if now >= rental.end() => rental,
+				_ => return false,
+			};
+			<Rentals<T>>::remove(class_id, nft_id);
+			let rest = rental.total().saturating_sub(rental.released);
+			// the rent has been reserved on the renter since the rental started, only what is
+			// still reserved can be paid out
+			let paid = <T as Config>::Currency::repatriate_reserved(
+				&rental.renter,
+				&rental.owner,
+				rest,
+				BalanceStatus::Free,
+			)
+			.map_or_else(|_| Zero::zero(), |unpaid| rest.saturating_sub(unpaid));
+			if !paid.is_zero() {
+				Self::deposit_event(Event::RentClaimed {
+					class_id,
+					nft_id,
+					owner: rental.owner.clone(),
+					amount: paid,
+				});
+			}
+			// the owner may have given the token a new user in the meantime, it stays
+			let user = pallet_nft::Users::<T>::get(class_id, nft_id);
+			if user.map_or(false, |user| user.user == rental.renter) {
+				if let Err(e) = pallet_nft::Pallet::<T>::remove_expired_user(class_id, nft_id) {
+					log::warn!(
+						target: LOG_TARGET,
+						"renter of nft {} in class {} kept its user right: {:?}",
+						nft_id,
+						class_id,
+						e
+					);
+				}
+			}
+			Self::deposit_event(Event::RentalEnded {
+				class_id,
+				nft_id,
+				owner: rental.owner,
+				renter: rental.renter,
+			});
+			true
+		}
+
+		fn cancel_buyer_offer(class_id: Id, nft_id: Id, buyer: &T::AccountId) {
+			if let Some(offer) = <BuyerOffers<T>>::take((class_id, nft_id), buyer) {
+				<T as Config>::Currency::unreserve(buyer, offer.amount);
+				Self::deposit_event(Event::BuyerOfferCanceled {
+					class_id,
+					nft_id,
+					buyer: buyer.clone(),
+				});
+			}
+		}
+
+		///store the listing and put it in the order book of its class, a token that is listed
+		///again gets the new price
+		fn insert_listing(offer: OfferOf<T>) {
+			let (class_id, nft_id) = (offer.class_id, offer.nft_id);
+			if let Some(old) = <Offers<T>>::get(class_id, nft_id) {
+				<OrderBook<T>>::remove(class_id, OrderKey::new(old.amount, nft_id));
+			}
+			<OrderBook<T>>::insert(class_id, OrderKey::new(offer.amount, nft_id), ());
+			<Offers<T>>::insert(class_id, nft_id, offer);
+		}
+
+		///remove the listing from `Offers` and from the order book of its class
+		fn remove_listing(class_id: Id, nft_id: Id) -> Option<OfferOf<T>> {
+			let offer = <Offers<T>>::take(class_id, nft_id)?;
+			<OrderBook<T>>::remove(class_id, OrderKey::new(offer.amount, nft_id));
+			Some(offer)
+		}
+
+		///listings of a class from the cheapest one after `cursor` on, leaving out the expired
+		///ones the on_idle sweep has not removed yet
+		fn live_listings(
+			class_id: Id,
+			cursor: Option<(BalanceOf<T>, Id)>,
+		) -> impl Iterator<Item = (BalanceOf<T>, Id)> {
+			let keys = match cursor {
+				Some((price, nft_id)) => {
+					let start =
+						<OrderBook<T>>::hashed_key_for(class_id, OrderKey::new(price, nft_id));
+					<OrderBook<T>>::iter_key_prefix_from(class_id, start)
+				},
+				None => <OrderBook<T>>::iter_key_prefix(class_id),
+			};
+			let now = Self::now();
+			keys.filter(move |key| {
+				<Offers<T>>::get(class_id, key.nft_id())
+					.map_or(false, |offer| !offer.is_expired(now))
+			})
+			.map(|key| (key.price(), key.nft_id()))
+		}
+
+		///cheapest price of a class among the listings that can still be bought
+		pub fn floor_price(class_id: Id) -> Option<BalanceOf<T>> {
+			Self::live_listings(class_id, None).next().map(|(price, _)| price)
+		}
+
+		///up to `limit` listings of a class that can still be bought from the cheapest, starting
+		///after the (price, nft id) given as cursor
+		pub fn listings(
+			class_id: Id,
+			limit: u32,
+			cursor: Option<(BalanceOf<T>, Id)>,
+		) -> Vec<(BalanceOf<T>, Id)> {
+			Self::live_listings(class_id, cursor).take(limit as usize).collect()
+		}
+
+		///unix ms at which `block` is expected, counted from the current block and time with
+		///`BlockTime`. it is an estimate for past blocks as well
+		pub fn block_to_unix_ms(block: Time) -> Time {
+			let (current, now) = (Self::now_in(TimeBase::Block), Self::now());
+			let block_time = T::BlockTime::get();
+			if block >= current {
+				now.saturating_add((block - current).saturating_mul(block_time))
+			} else {
+				now.saturating_sub((current - block).saturating_mul(block_time))
+			}
+		}
+
+		///first block expected at or after `time` unix ms, the inverse of `block_to_unix_ms`
+		pub fn unix_ms_to_block(time: Time) -> Time {
+			let (current, now) = (Self::now_in(TimeBase::Block), Self::now());
+			if time >= now {
+				current.saturating_add(Self::span_in(TimeBase::Block, time - now))
+			} else {
+				let block_time = T::BlockTime::get().max(1);
+				current.saturating_sub((now - time) / block_time)
+			}
+		}
+
+		///checks that listings, auctions, swaps and rentals are on tokens their seller owns, that
+		///the order books follow the listings, that renters use their token until the rental
+		///ends, that the tokens of loans are in escrow and that the reserved balance of every
+		///account is what its bids, buyer offers, swap top-ups and unpaid rent hold. the
+		///marketplace must be the only pallet reserving `Currency`
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			use sp_std::collections::btree_map::BTreeMap;
+
+			ensure!(<Parameters<T>>::get().is_valid(), "invalid marketplace parameters");
+			let owner_of =
+				|class_id: Id, nft_id: Id| <Nfts<T>>::get(class_id, nft_id).map(|nft| nft.owner);
+			let mut listed = 0usize;
+			for (class_id, nft_id, offer) in <Offers<T>>::iter() {
+				listed += 1;
+				ensure!(
+					(offer.class_id, offer.nft_id) == (class_id, nft_id),
+					"listing stored under another token"
+				);
+				ensure!(owner_of(class_id, nft_id) == Some(offer.owner), "listing of a non owner");
+				ensure!(
+					<OrderBook<T>>::contains_key(class_id, OrderKey::new(offer.amount, nft_id)),
+					"listing missing from the order book"
+				);
+			}
+			ensure!(
+				<OrderBook<T>>::iter_keys().count() == listed,
+				"order book entry without a listing"
+			);
+
+			let mut reserved = BTreeMap::<T::AccountId, BalanceOf<T>>::new();
+			let mut hold = |who: &T::AccountId, amount: BalanceOf<T>| {
+				let total = reserved.entry(who.clone()).or_insert_with(Zero::zero);
+				*total = total.saturating_add(amount);
+			};
+			for (class_id, nft_id, auction) in <Auctions<T>>::iter() {
+				ensure!(
+					owner_of(class_id, nft_id).as_ref() == Some(&auction.owner),
+					"auction of a non owner"
+				);
+				if auction.spender != auction.owner {
+					hold(&auction.spender, auction.min_amount);
+				}
+			}
+			let next_swap_id = <NextSwapId<T>>::get().unwrap_or(0);
+			for (swap_id, swap) in <Swaps<T>>::iter() {
+				ensure!(swap_id < next_swap_id, "swap id not below NextSwapId");
+				ensure!(
+					owner_of(swap.class_id, swap.nft_id).as_ref() == Some(&swap.owner),
+					"swap of a non owner"
+				);
+				ensure!(
+					<NftSwaps<T>>::get(swap.class_id, swap.nft_id) == Some(swap_id),
+					"swap missing from NftSwaps"
+				);
+				if let Some(PriceDirection::Send(amount)) = swap.price {
+					hold(&swap.owner, amount);
+				}
+			}
+			ensure!(
+				<NftSwaps<T>>::iter_values().count() == <Swaps<T>>::iter_keys().count(),
+				"NftSwaps entry without a swap"
+			);
+			// offers on burned tokens stay until the sweep gives them back
+			for (_, buyer, offer) in <BuyerOffers<T>>::iter() {
+				hold(&buyer, offer.amount);
+			}
+			for (class_id, nft_id, listing) in <RentalListings<T>>::iter() {
+				ensure!(
+					owner_of(class_id, nft_id).as_ref() == Some(&listing.owner),
+					"rental listing of a non owner"
+				);
+			}
+			let block = <frame_system::Pallet<T>>::block_number();
+			for (class_id, nft_id, rental) in <Rentals<T>>::iter() {
+				ensure!(
+					owner_of(class_id, nft_id).as_ref() == Some(&rental.owner),
+					"rental of a non owner"
+				);
+				ensure!(rental.released <= rental.total(), "more rent paid out than reserved");
+				if block < rental.end() {
+					ensure!(
+						pallet_nft::Pallet::<T>::user_of(class_id, nft_id).as_ref() ==
+							Some(&rental.renter),
+						"renter is not the user of the token"
+					);
+				}
+				hold(&rental.renter, rental.total().saturating_sub(rental.released));
+			}
+			let escrow = Self::escrow_account();
+			for (class_id, nft_id, loan) in <Loans<T>>::iter() {
+				ensure!(
+					owner_of(class_id, nft_id) == Some(escrow.clone()),
+					"loan token not in escrow"
+				);
+				if let LoanState::Funded { lender, .. } = loan.state {
+					ensure!(lender != loan.borrower, "loan funded by its borrower");
+				}
+			}
+			for (who, amount) in reserved {
+				ensure!(
+					<T as Config>::Currency::reserved_balance(&who) == amount,
+					"reserved balance does not match the outstanding bids"
+				);
+			}
+			Ok(())
+		}
+
+		///pay the listing price to the seller and take the token, `max_price` is the most the
+		///buyer accepts to pay, returns the seller and the paid price
+		fn do_buy_offer(
+			buyer: &T::AccountId,
+			class_id: Id,
+			nft_id: Id,
+			max_price: BalanceOf<T>,
+		) -> Result<(T::AccountId, BalanceOf<T>), DispatchError> {
+			let offer = <Offers<T>>::get(class_id, nft_id).ok_or(Error::<T>::OfferNotFound)?;
+			ensure!(<Classes<T>>::contains_key(class_id), Error::<T>::ClassNotFound);
+			let nft = <Nfts<T>>::get(class_id, nft_id).ok_or(Error::<T>::NftNotFound)?;
+			ensure!(offer.owner == nft.owner, Error::<T>::NftBuyed);
+			ensure!(!offer.is_expired(Self::now()), Error::<T>::OfferExpired);
+			ensure!(max_price >= offer.amount, Error::<T>::AmountToLow);
+			Self::settle(buyer, &nft.owner, class_id, offer.amount, false)?;
+			Self::remove_listing(class_id, nft_id);
+			Self::transfer_nft(class_id, nft_id, &nft.owner, buyer)?;
+			Ok((nft.owner, offer.amount))
+		}
+
+		///remove expired listings, buyer offers and swaps and end the rentals that are over, buyer
+		///offers on burned tokens are given back as well. at most `MaxExpiredPerBlock` entries
+		///are looked at per block and the sweep carries on from where it stopped in the next block
+		pub(crate) fn sweep_expired(remaining_weight: Weight) -> Weight {
+			// the four cursors are read and written once per sweep, whatever it looks at
+			let cursors = T::DbWeight::get().reads_writes(4, 4);
+			let per_item = T::DbWeight::get().reads_writes(2, 2);
+			let budget = remaining_weight
+				.saturating_sub(cursors)
+				.ref_time()
+				.checked_div(per_item.ref_time())
+				.unwrap_or(u64::MAX)
+				.min(T::MaxExpiredPerBlock::get() as u64) as u32;
+			if budget == 0 {
+				return Weight::zero()
+			}
+			let now = Self::now();
+			let listing_budget = budget - budget / 2;
+			let inspected = Self::sweep_listings(now, listing_budget);
+			let inspected = inspected + Self::sweep_buyer_offers(now, budget - inspected);
+			let inspected = inspected + Self::sweep_swaps(now, budget - inspected);
+			// rentals get what is left, their user right lapses without the sweep and owners can
+			// claim the rest of the rent themselves
+			let block = <frame_system::Pallet<T>>::block_number();
+			let (rentals, ended) = Self::sweep_rentals(block, budget - inspected);
+			// ending a rental also pays out the rent and drops the user
+			cursors
+				.saturating_add(per_item.saturating_mul((inspected + rentals) as u64))
+				.saturating_add(T::DbWeight::get().reads_writes(3, 3).saturating_mul(ended as u64))
+		}
+
+		fn sweep_listings(now: Time, budget: u32) -> u32 {
+			// with nothing to look at the cursor would be reset to the first entry
+			if budget == 0 {
+				return 0
+			}
+			let mut iter = match <ListingSweepCursor<T>>::get() {
+				Some(raw_key) => <Offers<T>>::iter_from(raw_key),
+				None => <Offers<T>>::iter(),
+			};
+			let mut inspected = 0;
+			let mut cursor = None;
+			let mut expired = Vec::new();
+			while inspected < budget {
+				let (class_id, nft_id, offer) = match iter.next() {
+					Some(item) => item,
+					None => {
+						// reached the end, start again from the first listing next time
+						cursor = None;
+						break
+					},
+				};
+				inspected += 1;
+				cursor = Some(<Offers<T>>::hashed_key_for(class_id, nft_id));
+				if offer.is_expired(now) {
+					expired.push((class_id, nft_id, offer.owner));
+				}
+			}
+			match cursor {
+				Some(raw_key) => <ListingSweepCursor<T>>::put(raw_key),
+				None => <ListingSweepCursor<T>>::kill(),
+			}
+			for (class_id, nft_id, owner) in expired {
+				Self::remove_listing(class_id, nft_id);
+				Self::deposit_event(Event::OfferExpired { class_id, nft_id, owner });
+			}
+			inspected
+		}
+
+		fn sweep_buyer_offers(now: Time, budget: u32) -> u32 {
+			if budget == 0 {
+				return 0
+			}
+			let mut iter = match <BuyerOfferSweepCursor<T>>::get() {
+				Some(raw_key) => <BuyerOffers<T>>::iter_from(raw_key),
+				None => <BuyerOffers<T>>::iter(),
+			};
+			let mut inspected = 0;
+			let mut cursor = None;
+			let mut expired = Vec::new();
+			let mut burned = Vec::new();
+			while inspected < budget {
+				let (key, buyer, offer) = match iter.next() {
+					Some(item) => item,
+					None => {
+						cursor = None;
+						break
+					},
+				};
+				inspected += 1;
+				cursor = Some(<BuyerOffers<T>>::hashed_key_for(key, &buyer));
+				if !<Nfts<T>>::contains_key(offer.class_id, offer.nft_id) {
+					burned.push(offer);
+				} else if offer.is_expired(now) {
+					expired.push(offer);
+				}
+			}
+			match cursor {
+				Some(raw_key) => <BuyerOfferSweepCursor<T>>::put(raw_key),
+				None => <BuyerOfferSweepCursor<T>>::kill(),
+			}
+			for offer in expired {
+				<BuyerOffers<T>>::remove((offer.class_id, offer.nft_id), &offer.buyer);
+				<T as Config>::Currency::unreserve(&offer.buyer, offer.amount);
+				Self::deposit_event(Event::BuyerOfferExpired {
+					class_id: offer.class_id,
+					nft_id: offer.nft_id,
+					buyer: offer.buyer,
+					amount: offer.amount,
+				});
+			}
+			for offer in burned {
+				Self::cancel_buyer_offer(offer.class_id, offer.nft_id, &offer.buyer);
+			}
+			inspected
+		}
+
+		fn sweep_swaps(now: Time, budget: u32) -> u32 {
+			if budget == 0 {
+				return 0
+			}
+			let mut iter = match <SwapSweepCursor<T>>::get() {
+				Some(raw_key) => <Swaps<T>>::iter_from(raw_key),
+				None => <Swaps<T>>::iter(),
+			};
+			let mut inspected = 0;
+			let mut cursor = None;
+			let mut expired = Vec::new();
+			while inspected < budget {
+				let (swap_id, swap) = match iter.next() {
+					Some(item) => item,
+					None => {
+						cursor = None;
+						break
+					},
+				};
+				inspected += 1;
+				cursor = Some(<Swaps<T>>::hashed_key_for(swap_id));
+				// a swap can still be claimed at its deadline
+				if now > swap.deadline {
+					expired.push((swap_id, swap));
+				}
+			}
+			match cursor {
+				Some(raw_key) => <SwapSweepCursor<T>>::put(raw_key),
+				None => <SwapSweepCursor<T>>::kill(),
+			}
+			for (swap_id, swap) in expired {
+				<Swaps<T>>::remove(swap_id);
+				<NftSwaps<T>>::remove(swap.class_id, swap.nft_id);
+				if let Some(PriceDirection::Send(amount)) = swap.price {
+					<T as Config>::Currency::unreserve(&swap.owner, amount);
+				}
+				Self::deposit_event(Event::SwapExpired { swap_id, owner: swap.owner });
+			}
+			inspected
+		}
+
+		///returns how many rentals were looked at and how many of them ended
+		fn sweep_rentals(now: T::BlockNumber, budget: u32) -> (u32, u32) {
+			if budget == 0 {
+				return (0, 0)
+			}
+			let mut iter = match <RentalSweepCursor<T>>::get() {
+				Some(raw_key) => <Rentals<T>>::iter_from(raw_key),
+				None => <Rentals<T>>::iter(),
+			};
+			let mut inspected = 0;
+			let mut cursor = None;
+			let mut over = Vec::new();
+			while inspected < budget {
+				let (class_id, nft_id, rental) = match iter.next() {
+					Some(item) => item,
+					None => {
+						cursor = None;
+						break
+					},
+				};
+				inspected += 1;
+				cursor = Some(<Rentals<T>>::hashed_key_for(class_id, nft_id));
+				if now >= rental.end() {
+					over.push((class_id, nft_id));
+				}
+			}
+			match cursor {
+				Some(raw_key) => <RentalSweepCursor<T>>::put(raw_key),
+				None => <RentalSweepCursor<T>>::kill(),
+			}
+			let ended = over.len() as u32;
+			for (class_id, nft_id) in over {
+				Self::end_rental(class_id, nft_id, now);
+			}
+			(inspected, ended)
+		}
+	}
+
+	impl<T: Config> pallet_nft::OnTransfer<T::AccountId> for Pallet<T> {
+		fn on_transfer(class_id: Id, nft_id: Id, _from: &T::AccountId, to: &T::AccountId) {
+			Self::cancel_listings(class_id, nft_id);
+			// the new owner can not buy the token from itself
+			Self::cancel_buyer_offer(class_id, nft_id, to);
+		}
+
+		///also covers the `Loans` read of the `TokenLock` check
+		fn on_transfer_weight() -> Weight {
+			T::WeightInfo::on_transfer()
+		}
+	}
+
+	impl<T: Config> pallet_nft::TokenLock for Pallet<T> {
+		///a token in escrow for a loan stays there until the loan is settled
+		fn is_locked(class_id: Id, nft_id: Id) -> bool {
+			<Loans<T>>::contains_key(class_id, nft_id)
+		}
+
+		///a buyer, bidder or renter could otherwise get a token someone else is using
+		fn is_user_locked(class_id: Id, nft_id: Id) -> bool {
+			<Loans<T>>::contains_key(class_id, nft_id) ||
+				<Offers<T>>::contains_key(class_id, nft_id) ||
+				<Auctions<T>>::contains_key(class_id, nft_id) ||
+				<NftSwaps<T>>::contains_key(class_id, nft_id) ||
+				<RentalListings<T>>::contains_key(class_id, nft_id)
+		}
+
+		fn is_user_locked_weight() -> Weight {
+			T::DbWeight::get().reads(5)
+		}
+	}
+
+	impl<T: Config> pallet_nft::OnBurn<T::AccountId> for Pallet<T> {
+		fn on_burn(class_id: Id, nft_id: Id, _owner: &T::AccountId) {
+			// a token can have any number of buyer offers, the sweep gives them back later and
+			// their buyers can withdraw them in the meantime
+			Self::cancel_listings(class_id, nft_id);
+		}
+
+		///also covers the `Loans` read of the `TokenLock` check
+		fn on_burn_weight() -> Weight {
+			T::WeightInfo::on_burn()
+		}
 	}
 }
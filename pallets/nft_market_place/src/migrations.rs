@@ -0,0 +1,263 @@
+//! storage migrations of the pallet, each `vN` module brings the storage from version `N - 1` to
+//! `N` and does nothing when the on-chain version is not `N - 1`
+
+use crate::{
+	Auction, Auctions, BalanceOf, Config, MarketParameters, Offer, Offers, OrderBook, OrderKey,
+	Pallet, Parameters, TimeBase,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+	RuntimeDebug,
+};
+use pallet_nft::Id;
+use scale_info::TypeInfo;
+use sp_runtime::Permill;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+const LOG_TARGET: &str = "runtime::nft_market_place";
+
+///gives the fee account the existential deposit on chains that started without it, it does
+///nothing once the account exists so it can stay in the migrations of the runtime
+pub struct EndowFeeAccount<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for EndowFeeAccount<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if Pallet::<T>::endow_fee_account() {
+			log::info!(target: LOG_TARGET, "endowed the marketplace fee account");
+			T::DbWeight::get().reads_writes(1, 1)
+		} else {
+			T::DbWeight::get().reads(1)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+		use frame_support::traits::Currency;
+		use sp_runtime::traits::Zero;
+		let account = Pallet::<T>::fee_account();
+		frame_support::ensure!(
+			!<T as Config>::Currency::total_balance(&account).is_zero(),
+			"the fee account does not exist"
+		);
+		Ok(())
+	}
+}
+
+///storage layout before listings could expire and had an order book
+pub mod v0 {
+	use super::*;
+
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct Offer<AccountId, Balance> {
+		pub nft_id: Id,
+		pub class_id: Id,
+		pub amount: Balance,
+		pub owner: AccountId,
+	}
+
+	pub type OfferOf<T> = Offer<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+}
+
+///listings never expire and `OrderBook` is built from `Offers`. `Auctions` keep their layout
+pub mod v1 {
+	use super::*;
+
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				log::info!(target: LOG_TARGET, "v1 migration skipped, storage is not v0");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut count = 0u64;
+			<Offers<T>>::translate::<v0::OfferOf<T>, _>(|class_id, nft_id, old| {
+				count += 1;
+				<OrderBook<T>>::insert(class_id, OrderKey::new(old.amount, nft_id), ());
+				Some(Offer {
+					nft_id: old.nft_id,
+					class_id: old.class_id,
+					amount: old.amount,
+					owner: old.owner,
+					expires_at: None,
+				})
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "migrated {} listings to v1", count);
+			T::DbWeight::get().reads_writes(count + 1, 2 * count + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let offers = <Offers<T>>::iter_keys().count() as u64;
+			let auctions = <crate::Auctions<T>>::iter_keys().count() as u64;
+			Ok((offers, auctions).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (offers, auctions) =
+				<(u64, u64)>::decode(&mut &state[..]).map_err(|_| "invalid pre_upgrade state")?;
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 1,
+				"storage version is not 1"
+			);
+			let mut after = 0u64;
+			for (class_id, nft_id, offer) in <Offers<T>>::iter() {
+				after += 1;
+				frame_support::ensure!(
+					<OrderBook<T>>::contains_key(class_id, OrderKey::new(offer.amount, nft_id)),
+					"listing missing from the order book"
+				);
+			}
+			let listed = <OrderBook<T>>::iter_keys().count() as u64;
+			frame_support::ensure!(after == listed, "order book entry without a listing");
+			frame_support::ensure!(after == offers, "listings lost in the migration");
+			// up to v2 the auctions are stored without `time_base`
+			#[frame_support::storage_alias]
+			type Auctions<T: Config> = StorageDoubleMap<
+				Pallet<T>,
+				frame_support::Twox64Concat,
+				Id,
+				frame_support::Twox64Concat,
+				Id,
+				super::v3::AuctionOf<T>,
+			>;
+			frame_support::ensure!(
+				<Auctions<T>>::iter().count() as u64 == auctions,
+				"undecodable auction"
+			);
+			Ok(())
+		}
+	}
+}
+
+///`FeeRate` and `RoyaltyCap` become fields of `Parameters`, the new parameters get their default
+pub mod v2 {
+	use super::*;
+	use frame_support::pallet_prelude::ValueQuery;
+
+	#[frame_support::storage_alias]
+	type FeeRate<T: Config> = StorageValue<Pallet<T>, Permill, ValueQuery>;
+
+	#[frame_support::storage_alias]
+	type RoyaltyCap<T: Config> = StorageValue<Pallet<T>, Permill, ValueQuery>;
+
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				log::info!(target: LOG_TARGET, "v2 migration skipped, storage is not v1");
+				return T::DbWeight::get().reads(1)
+			}
+			let parameters = MarketParameters {
+				fee_rate: <FeeRate<T>>::take(),
+				royalty_cap: <RoyaltyCap<T>>::take(),
+				..Default::default()
+			};
+			<Parameters<T>>::put(parameters);
+			StorageVersion::new(2).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "migrated the marketplace parameters to v2");
+			T::DbWeight::get().reads_writes(3, 4)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((<FeeRate<T>>::get(), <RoyaltyCap<T>>::get()).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (fee_rate, royalty_cap) = <(Permill, Permill)>::decode(&mut &state[..])
+				.map_err(|_| "invalid pre_upgrade state")?;
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 2,
+				"storage version is not 2"
+			);
+			let parameters = <Parameters<T>>::get();
+			frame_support::ensure!(
+				(parameters.fee_rate, parameters.royalty_cap) == (fee_rate, royalty_cap),
+				"fee rate or royalty cap lost in the migration"
+			);
+			frame_support::ensure!(!<FeeRate<T>>::exists(), "FeeRate is still stored");
+			Ok(())
+		}
+	}
+}
+
+///auctions get a time base, the existing ones run on unix ms
+pub mod v3 {
+	use super::*;
+
+	///auction layout before `time_base`
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct Auction<AccountId, Balance> {
+		pub owner: AccountId,
+		pub nft_id: Id,
+		pub spender: AccountId,
+		pub class_id: Id,
+		pub min_amount: Balance,
+		pub amount: Balance,
+		pub start_time: u64,
+		pub end_time: u64,
+	}
+
+	pub type AuctionOf<T> = Auction<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				log::info!(target: LOG_TARGET, "v3 migration skipped, storage is not v2");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut count = 0u64;
+			<Auctions<T>>::translate::<AuctionOf<T>, _>(|_, _, old| {
+				count += 1;
+				Some(super::Auction {
+					owner: old.owner,
+					nft_id: old.nft_id,
+					spender: old.spender,
+					class_id: old.class_id,
+					min_amount: old.min_amount,
+					amount: old.amount,
+					start_time: old.start_time,
+					end_time: old.end_time,
+					time_base: TimeBase::UnixMs,
+				})
+			});
+			StorageVersion::new(3).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "migrated {} auctions to v3", count);
+			T::DbWeight::get().reads_writes(count + 1, count + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let count = <Auctions<T>>::iter_keys().count() as u64;
+			Ok(count.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let before = u64::decode(&mut &state[..]).map_err(|_| "invalid pre_upgrade state")?;
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 3,
+				"storage version is not 3"
+			);
+			let mut after = 0u64;
+			for auction in <Auctions<T>>::iter_values() {
+				after += 1;
+				frame_support::ensure!(
+					auction.time_base == TimeBase::UnixMs,
+					"migrated auction not timed in unix ms"
+				);
+			}
+			frame_support::ensure!(before == after, "auctions lost in the migration");
+			Ok(())
+		}
+	}
+}
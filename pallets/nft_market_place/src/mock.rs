@@ -1,8 +1,10 @@
 use crate::{self as pallet_nft_market_place, Balance};
-use frame_support::traits::{ConstU16, ConstU64};
+use frame_support::traits::{ConstU128, ConstU16, ConstU32, ConstU64};
+use frame_support::{parameter_types, PalletId};
 use frame_system as system;
+use frame_system::EnsureRoot;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
 	traits::{BlakeTwo256, IdentityLookup},
 };
 
@@ -19,7 +21,8 @@ frame_support::construct_runtime!(
 		System: frame_system,
 		NftMarketPlaceModule: pallet_nft_market_place,
 		NftModule: pallet_nft,
-		Timestamp:pallet_timestamp
+		Timestamp:pallet_timestamp,
+		Balances: pallet_balances,
 	}
 );
 
@@ -41,7 +44,7 @@ impl system::Config for Test {
 	type BlockHashCount = ConstU64<250>;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<Balance>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -50,15 +53,73 @@ impl system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = Balance;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU128<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<C> system::offchain::SendTransactionTypes<C> for Test
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = TestXt<RuntimeCall, ()>;
+}
+
+pub struct TestAuthId;
+
+impl system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+parameter_types! {
+	pub const IpfsGateway: &'static [u8] = b"https://gateway.test";
+}
+
 impl pallet_nft::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
-	type Currency = ();
+	type Currency = Balances;
+	type OnTransfer = NftMarketPlaceModule;
+	type OnBurn = NftMarketPlaceModule;
+	type TokenLock = NftMarketPlaceModule;
+	type WeightInfo = ();
+	type AuthorityId = TestAuthId;
+	type IpfsGateway = IpfsGateway;
+	type MaxMetadataChecks = ConstU32<2>;
+	type MaxMetadataVerifiers = ConstU32<3>;
+	type VerifierOrigin = EnsureRoot<u64>;
+	type UnsignedPriority = ConstU64<100>;
+}
+
+parameter_types! {
+	pub const MarketPlacePalletId: PalletId = PalletId(*b"nft/mrkt");
 }
 
 impl pallet_nft_market_place::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
-	type Currency = ();
+	type Currency = Balances;
 	type UnixTime = Timestamp;
+	type BlockTime = ConstU64<10>;
+	type MaxExpiredPerBlock = ConstU32<4>;
+	type MaxBuyMany = ConstU32<5>;
+	type PalletId = MarketPlacePalletId;
+	type PauseOrigin = EnsureRoot<u64>;
+	type AdminOrigin = EnsureRoot<u64>;
+	type WeightInfo = ();
 }
 
 impl pallet_timestamp::Config for Test {
@@ -69,6 +130,33 @@ impl pallet_timestamp::Config for Test {
 
 }
 
+pub const INITIAL_BALANCE: Balance = 1_000_000;
+
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	new_test_ext_with_genesis(Default::default(), Default::default())
+}
+
+///runs `test` and checks the storage invariants of the pallet afterwards
+pub fn execute_with_try_state(mut ext: sp_io::TestExternalities, test: impl FnOnce()) {
+	ext.execute_with(|| {
+		test();
+		NftMarketPlaceModule::do_try_state().unwrap();
+	})
+}
+
+pub fn new_test_ext_with_genesis(
+	nft: pallet_nft::GenesisConfig<Test>,
+	market: pallet_nft_market_place::GenesisConfig<Test>,
+) -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: (1..=4).map(|who| (who, INITIAL_BALANCE)).collect(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	nft.assimilate_storage(&mut t).unwrap();
+	market.assimilate_storage(&mut t).unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
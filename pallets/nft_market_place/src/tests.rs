@@ -1,15 +1,28 @@
-use crate::{mock::*, Auction, Offer};
-use frame_support::{assert_noop, assert_ok, print};
+use crate::{
+	migrations, mock::*, Auction, BuyMode, BuyerOffer, MarketCall, MarketParameters, Offer,
+	PauseScope, PriceDirection, Swap, SwapTarget, TimeBase, VersionedMarketParameters,
+};
+use frame_support::{
+	assert_noop, assert_ok, bounded_vec, print,
+	traits::{
+		Currency, GetStorageVersion, Hooks, OnRuntimeUpgrade, ReservableCurrency, StorageVersion,
+	},
+	weights::Weight,
+};
 use frame_system::Origin;
+use pallet_balances::Error as BalancesError;
 use pallet_nft::{Class, Error, Nft};
-use sp_runtime::SaturatedConversion;
+use sp_runtime::{
+	traits::{BadOrigin, Dispatchable},
+	Permill, SaturatedConversion,
+};
 #[test]
 fn it_works_for_default_value() {
-	new_test_ext().execute_with(|| {});
+	execute_with_try_state(new_test_ext(), || {});
 }
 #[test]
 fn correct_error_for_none_value() {
-	new_test_ext().execute_with(|| {
+	execute_with_try_state(new_test_ext(), || {
 		assert_ok!(NftModule::create_class(
 			RuntimeOrigin::signed(1),
 			"first".into(),
@@ -33,7 +46,9 @@ fn correct_error_for_none_value() {
 			"first".into(),
 			"first".into(),
 			"first".into(),
-			"first".into()
+			"first".into(),
+			None,
+			None
 		));
 		assert_eq!(
 			NftModule::nfts(0, 0),
@@ -43,7 +58,9 @@ fn correct_error_for_none_value() {
 				description: vec![102, 105, 114, 115, 116],
 				name: vec![102, 105, 114, 115, 116],
 				url: vec![102, 105, 114, 115, 116],
-				external_url: vec![102, 105, 114, 115, 116]
+				external_url: vec![102, 105, 114, 115, 116],
+				metadata_hash: None,
+				media_hash: None,
 			})
 		);
 
@@ -51,13 +68,17 @@ fn correct_error_for_none_value() {
 		assert_eq!(NftModule::next_class_id(), Some(1));
 
 		//###############Pallet Market Place###############//
-		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 100));
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 100, None));
 		assert_eq!(
 			NftMarketPlaceModule::offers(0, 0),
-			Some(Offer { owner: 1, class_id: 0, nft_id: 0, amount: 100 })
+			Some(Offer { owner: 1, class_id: 0, nft_id: 0, amount: 100, expires_at: None })
 		);
 
 		assert_ok!(NftMarketPlaceModule::buy_offer(RuntimeOrigin::signed(2), 0, 0, 100));
+		System::assert_last_event(
+			crate::Event::OfferBuyed { buyer: 2, sender: 1, amount: 100, nft_id: 0, class_id: 0 }
+				.into(),
+		);
 
 		assert_eq!(NftMarketPlaceModule::offers(0, 0), None);
 
@@ -69,7 +90,9 @@ fn correct_error_for_none_value() {
 				description: vec![102, 105, 114, 115, 116],
 				name: vec![102, 105, 114, 115, 116],
 				url: vec![102, 105, 114, 115, 116],
-				external_url: vec![102, 105, 114, 115, 116]
+				external_url: vec![102, 105, 114, 115, 116],
+				metadata_hash: None,
+				media_hash: None,
 			})
 		);
 
@@ -93,7 +116,8 @@ fn correct_error_for_none_value() {
 			0,
 			100,
 			now,
-			now + 100
+			now + 100,
+			TimeBase::UnixMs
 		));
 
 		assert_eq!(
@@ -106,7 +130,8 @@ fn correct_error_for_none_value() {
 				min_amount: 0,
 				amount: 100,
 				start_time: now,
-				end_time: now + 100
+				end_time: now + 100,
+				time_base: TimeBase::UnixMs
 			})
 		);
 
@@ -125,7 +150,9 @@ fn correct_error_for_none_value() {
 			"first".into(),
 			"first".into(),
 			"first".into(),
-			"first".into()
+			"first".into(),
+			None,
+			None
 		));
 		assert_eq!(
 			NftModule::nfts(0, 1),
@@ -135,14 +162,16 @@ fn correct_error_for_none_value() {
 				description: vec![102, 105, 114, 115, 116],
 				name: vec![102, 105, 114, 115, 116],
 				url: vec![102, 105, 114, 115, 116],
-				external_url: vec![102, 105, 114, 115, 116]
+				external_url: vec![102, 105, 114, 115, 116],
+				metadata_hash: None,
+				media_hash: None,
 			})
 		);
 
 		assert_eq!(NftModule::next_nft_id(), Some(2));
 
 
-		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 1, 100));
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 1, 100, None));
 
 
 
@@ -156,3 +185,1430 @@ fn correct_error_for_none_value() {
 		);
 	});
 }
+
+fn create_class_and_mint(owner: u64, amount: u64) {
+	assert_ok!(NftModule::create_class(
+		RuntimeOrigin::signed(owner),
+		"class".into(),
+		"class".into(),
+		"class".into(),
+		"class".into()
+	));
+	let class_id = NftModule::next_class_id().unwrap() - 1;
+	for _ in 0..amount {
+		assert_ok!(NftModule::mint(
+			RuntimeOrigin::signed(owner),
+			class_id,
+			"nft".into(),
+			"nft".into(),
+			"nft".into(),
+			"nft".into(),
+			None,
+			None
+		));
+	}
+}
+
+fn owner_of(class_id: u64, nft_id: u64) -> u64 {
+	NftModule::nfts(class_id, nft_id).unwrap().owner
+}
+
+#[test]
+fn swap_specific_token_with_top_up() {
+	execute_with_try_state(new_test_ext(), || {
+		// class 0 nft 0 belongs to 1, class 1 nft 1 belongs to 2
+		create_class_and_mint(1, 1);
+		create_class_and_mint(2, 1);
+		assert_ok!(NftMarketPlaceModule::create_swap(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			SwapTarget::Token { class_id: 1, nft_id: 1 },
+			Some(PriceDirection::Send(50)),
+			1000
+		));
+		assert_eq!(
+			NftMarketPlaceModule::swaps(0),
+			Some(Swap {
+				owner: 1,
+				class_id: 0,
+				nft_id: 0,
+				target: SwapTarget::Token { class_id: 1, nft_id: 1 },
+				price: Some(PriceDirection::Send(50)),
+				deadline: 1000
+			})
+		);
+		assert_eq!(Balances::reserved_balance(1), 50);
+
+		assert_noop!(
+			NftMarketPlaceModule::claim_swap(RuntimeOrigin::signed(2), 0, 0, 0),
+			crate::Error::<Test>::SwapTargetMismatch
+		);
+		assert_noop!(
+			NftMarketPlaceModule::claim_swap(RuntimeOrigin::signed(3), 0, 1, 1),
+			crate::Error::<Test>::NotOwner
+		);
+		assert_ok!(NftMarketPlaceModule::claim_swap(RuntimeOrigin::signed(2), 0, 1, 1));
+
+		assert_eq!(owner_of(0, 0), 2);
+		assert_eq!(owner_of(1, 1), 1);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 50);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 50);
+		assert_eq!(NftMarketPlaceModule::swaps(0), None);
+	});
+}
+
+#[test]
+fn swap_any_token_of_class_with_payment_from_claimer() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		create_class_and_mint(2, 2);
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(2), 1, 2, 300, None));
+		assert_ok!(NftMarketPlaceModule::create_swap(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			SwapTarget::AnyOfClass { class_id: 1 },
+			Some(PriceDirection::Receive(20)),
+			1000
+		));
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		assert_ok!(NftMarketPlaceModule::claim_swap(RuntimeOrigin::signed(2), 0, 1, 2));
+
+		assert_eq!(owner_of(0, 0), 2);
+		assert_eq!(owner_of(1, 2), 1);
+		assert_eq!(owner_of(1, 1), 2);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 20);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 20);
+		// the listing made by the previous owner is gone with the token
+		assert_eq!(NftMarketPlaceModule::offers(1, 2), None);
+	});
+}
+
+#[test]
+fn swap_can_not_be_claimed_after_deadline_or_when_cancelled() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		create_class_and_mint(2, 1);
+		assert_noop!(
+			NftMarketPlaceModule::create_swap(
+				RuntimeOrigin::signed(2),
+				0,
+				0,
+				SwapTarget::AnyOfClass { class_id: 1 },
+				None,
+				1000
+			),
+			crate::Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			NftMarketPlaceModule::create_swap(
+				RuntimeOrigin::signed(1),
+				0,
+				0,
+				SwapTarget::AnyOfClass { class_id: 1 },
+				Some(PriceDirection::Send(INITIAL_BALANCE + 1)),
+				1000
+			),
+			BalancesError::<Test>::InsufficientBalance
+		);
+		assert_ok!(NftMarketPlaceModule::create_swap(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			SwapTarget::AnyOfClass { class_id: 1 },
+			Some(PriceDirection::Send(10)),
+			1000
+		));
+		Timestamp::set_timestamp(1001);
+		assert_noop!(
+			NftMarketPlaceModule::claim_swap(RuntimeOrigin::signed(2), 0, 1, 1),
+			crate::Error::<Test>::SwapExpired
+		);
+		assert_noop!(
+			NftMarketPlaceModule::cancel_swap(RuntimeOrigin::signed(2), 0),
+			crate::Error::<Test>::NotOwner
+		);
+		assert_ok!(NftMarketPlaceModule::cancel_swap(RuntimeOrigin::signed(1), 0));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE);
+		assert_noop!(
+			NftMarketPlaceModule::claim_swap(RuntimeOrigin::signed(2), 0, 1, 1),
+			crate::Error::<Test>::SwapNotFound
+		);
+	});
+}
+
+#[test]
+fn expired_swap_is_swept_and_its_top_up_given_back() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		assert_ok!(NftMarketPlaceModule::create_swap(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			SwapTarget::AnyOfClass { class_id: 1 },
+			Some(PriceDirection::Send(10)),
+			1000
+		));
+		// still claimable at the deadline
+		Timestamp::set_timestamp(1000);
+		NftMarketPlaceModule::on_idle(1, Weight::MAX);
+		assert!(NftMarketPlaceModule::swaps(0).is_some());
+
+		Timestamp::set_timestamp(1001);
+		NftMarketPlaceModule::on_idle(2, Weight::MAX);
+		assert_eq!(NftMarketPlaceModule::swaps(0), None);
+		assert_eq!(NftMarketPlaceModule::nft_swaps(0, 0), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		System::assert_has_event(crate::Event::SwapExpired { swap_id: 0, owner: 1 }.into());
+	});
+}
+
+#[test]
+fn swap_top_up_that_is_no_longer_reserved_can_not_be_claimed() {
+	new_test_ext().execute_with(|| {
+		create_class_and_mint(1, 1);
+		create_class_and_mint(2, 1);
+		assert_ok!(NftMarketPlaceModule::create_swap(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			SwapTarget::AnyOfClass { class_id: 1 },
+			Some(PriceDirection::Send(30)),
+			1000
+		));
+		Balances::slash_reserved(&1, 10);
+		assert_noop!(
+			NftMarketPlaceModule::claim_swap(RuntimeOrigin::signed(2), 0, 1, 1),
+			crate::Error::<Test>::InsufficientReserve
+		);
+	});
+}
+
+#[test]
+fn expired_listing_can_not_be_bought() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		assert_ok!(NftMarketPlaceModule::create_offer(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			100,
+			Some(500)
+		));
+		Timestamp::set_timestamp(500);
+		assert_noop!(
+			NftMarketPlaceModule::buy_offer(RuntimeOrigin::signed(2), 0, 0, 100),
+			crate::Error::<Test>::OfferExpired
+		);
+	});
+}
+
+#[test]
+fn buyer_offer_is_accepted_from_reserved_funds() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		assert_noop!(
+			NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(1), 0, 0, 100, None),
+			crate::Error::<Test>::CantOfferOnYourNft
+		);
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(2), 0, 0, 100, None));
+		// a second offer from the same buyer replaces the first one
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(2), 0, 0, 150, None));
+		assert_eq!(Balances::reserved_balance(2), 150);
+		assert_eq!(
+			NftMarketPlaceModule::buyer_offers((0, 0), 2),
+			Some(BuyerOffer { buyer: 2, class_id: 0, nft_id: 0, amount: 150, expires_at: None })
+		);
+
+		assert_ok!(NftMarketPlaceModule::accept_offer(RuntimeOrigin::signed(1), 0, 0, 2));
+		assert_eq!(owner_of(0, 0), 2);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 150);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 150);
+		assert_eq!(NftMarketPlaceModule::buyer_offers((0, 0), 2), None);
+	});
+}
+
+#[test]
+fn on_idle_sweeps_expired_entries_and_unreserves_funds() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 4);
+		for nft_id in 0..4 {
+			assert_ok!(NftMarketPlaceModule::create_offer(
+				RuntimeOrigin::signed(1),
+				0,
+				nft_id,
+				100,
+				Some(100 + nft_id)
+			));
+		}
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(2), 0, 0, 70, Some(100)));
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(3), 0, 0, 80, None));
+		Timestamp::set_timestamp(102);
+
+		// MaxExpiredPerBlock is 4, half of it goes to the listings
+		NftMarketPlaceModule::on_idle(1, Weight::MAX);
+		let remaining = (0..4).filter(|nft_id| NftMarketPlaceModule::offers(0, nft_id).is_some());
+		assert!(remaining.count() >= 2);
+		assert_eq!(NftMarketPlaceModule::buyer_offers((0, 0), 2), None);
+		assert!(NftMarketPlaceModule::buyer_offers((0, 0), 3).is_some());
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::reserved_balance(3), 80);
+		System::assert_has_event(
+			crate::Event::BuyerOfferExpired { class_id: 0, nft_id: 0, buyer: 2, amount: 70 }.into(),
+		);
+
+		// the sweep goes on from where it stopped, nft 3 expires at 103 and stays listed
+		NftMarketPlaceModule::on_idle(2, Weight::MAX);
+		NftMarketPlaceModule::on_idle(3, Weight::MAX);
+		assert_eq!(NftMarketPlaceModule::offers(0, 0), None);
+		assert_eq!(NftMarketPlaceModule::offers(0, 1), None);
+		assert_eq!(NftMarketPlaceModule::offers(0, 2), None);
+		assert!(NftMarketPlaceModule::offers(0, 3).is_some());
+		System::assert_has_event(
+			crate::Event::OfferExpired { class_id: 0, nft_id: 2, owner: 1 }.into(),
+		);
+	});
+}
+
+#[test]
+fn sweep_keeps_the_cursors_it_has_no_budget_for() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 3);
+		for nft_id in 0..3 {
+			assert_ok!(NftMarketPlaceModule::create_offer(
+				RuntimeOrigin::signed(1),
+				0,
+				nft_id,
+				100,
+				None
+			));
+		}
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(2), 0, 0, 70, None));
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(3), 0, 0, 80, None));
+		crate::SwapSweepCursor::<Test>::put(vec![1, 2, 3]);
+		crate::RentalSweepCursor::<Test>::put(vec![4, 5, 6]);
+
+		// listings and buyer offers use up the whole budget of 4
+		NftMarketPlaceModule::on_idle(1, Weight::MAX);
+		assert!(crate::ListingSweepCursor::<Test>::get().is_some());
+		assert!(crate::BuyerOfferSweepCursor::<Test>::get().is_some());
+		assert_eq!(crate::SwapSweepCursor::<Test>::get(), Some(vec![1, 2, 3]));
+		assert_eq!(crate::RentalSweepCursor::<Test>::get(), Some(vec![4, 5, 6]));
+	});
+}
+
+#[test]
+fn highest_bid_is_reserved_and_paid_when_auction_finishes() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		assert_ok!(NftMarketPlaceModule::create_auction(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			100,
+			0,
+			100,
+			TimeBase::UnixMs
+		));
+		assert_noop!(
+			NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(2), 0, 0, 99),
+			crate::Error::<Test>::AmountToLow
+		);
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(2), 0, 0, 100));
+		assert_noop!(
+			NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(3), 0, 0, 100),
+			crate::Error::<Test>::AmountToLow
+		);
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(3), 0, 0, 120));
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::reserved_balance(3), 120);
+
+		assert_noop!(
+			NftMarketPlaceModule::auction_finish(RuntimeOrigin::signed(3), 0, 0),
+			crate::Error::<Test>::WaitWhenAuctionFinish
+		);
+		Timestamp::set_timestamp(100);
+		assert_ok!(NftMarketPlaceModule::auction_finish(RuntimeOrigin::signed(3), 0, 0));
+		assert_eq!(owner_of(0, 0), 3);
+		assert_eq!(NftMarketPlaceModule::auctions(0, 0), None);
+		assert_eq!(Balances::free_balance(3), INITIAL_BALANCE - 120);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 120);
+	});
+}
+
+#[test]
+fn transfer_through_pallet_nft_cancels_listings_and_refunds() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 2);
+		create_class_and_mint(3, 1);
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 100, None));
+		assert_ok!(NftMarketPlaceModule::create_auction(
+			RuntimeOrigin::signed(1),
+			0,
+			1,
+			100,
+			0,
+			100,
+			TimeBase::UnixMs
+		));
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(2), 1, 0, 150));
+		assert_ok!(NftMarketPlaceModule::create_swap(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			SwapTarget::AnyOfClass { class_id: 1 },
+			Some(PriceDirection::Send(30)),
+			1000
+		));
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(2), 0, 0, 40, None));
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(3), 0, 0, 50, None));
+
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 2, 0, 0));
+		assert_eq!(NftMarketPlaceModule::offers(0, 0), None);
+		assert_eq!(NftMarketPlaceModule::swaps(0), None);
+		assert_eq!(NftMarketPlaceModule::nft_swaps(0, 0), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		// the new owner's own offer is gone, the other buyer's offer stays for the new owner
+		assert_eq!(NftMarketPlaceModule::buyer_offers((0, 0), 2), None);
+		assert!(NftMarketPlaceModule::buyer_offers((0, 0), 3).is_some());
+		System::assert_has_event(crate::Event::OfferCanceled { class_id: 0, nft_id: 0 }.into());
+		System::assert_has_event(crate::Event::SwapCanceled { swap_id: 0, owner: 1 }.into());
+
+		assert_ok!(NftModule::burn(RuntimeOrigin::signed(1), 1, 0));
+		assert_eq!(NftMarketPlaceModule::auctions(0, 1), None);
+		System::assert_has_event(crate::Event::AuctionCanceled { class_id: 0, nft_id: 1 }.into());
+
+		// offers on a burned token are given back by the sweep, not by the burn
+		assert_ok!(NftModule::burn(RuntimeOrigin::signed(2), 0, 0));
+		assert_eq!(Balances::reserved_balance(3), 50);
+		NftMarketPlaceModule::on_idle(1, Weight::MAX);
+		assert_eq!(NftMarketPlaceModule::buyer_offers((0, 0), 3), None);
+		System::assert_has_event(
+			crate::Event::BuyerOfferCanceled { class_id: 0, nft_id: 0, buyer: 3 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE);
+	});
+}
+
+fn list_floor() {
+	// class 0 with nfts 0, 1, 2 listed by 1 for 100, 200 and 300
+	create_class_and_mint(1, 3);
+	for nft_id in 0..3 {
+		assert_ok!(NftMarketPlaceModule::create_offer(
+			RuntimeOrigin::signed(1),
+			0,
+			nft_id,
+			(nft_id as u128 + 1) * 100,
+			None
+		));
+	}
+}
+
+#[test]
+fn buy_many_all_or_nothing_buys_everything_or_nothing() {
+	execute_with_try_state(new_test_ext(), || {
+		list_floor();
+		// dispatched as a call so that the failed batch is rolled back like on chain
+		let buy_many = |items, max_total| {
+			RuntimeCall::NftMarketPlaceModule(crate::Call::buy_many {
+				items,
+				max_total,
+				mode: BuyMode::AllOrNothing,
+			})
+			.dispatch(RuntimeOrigin::signed(2))
+		};
+		assert_noop!(
+			buy_many(bounded_vec![(0, 0, 100), (0, 1, 200), (0, 2, 300)], 500),
+			crate::Error::<Test>::MaxTotalExceeded
+		);
+		assert_noop!(
+			buy_many(bounded_vec![(0, 0, 100), (0, 1, 150)], 1000),
+			crate::Error::<Test>::AmountToLow
+		);
+		assert_ok!(NftMarketPlaceModule::buy_many(
+			RuntimeOrigin::signed(2),
+			bounded_vec![(0, 0, 100), (0, 1, 200)],
+			300,
+			BuyMode::AllOrNothing
+		));
+		assert_eq!(owner_of(0, 0), 2);
+		assert_eq!(owner_of(0, 1), 2);
+		assert_eq!(owner_of(0, 2), 1);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE - 300);
+		System::assert_last_event(
+			crate::Event::BatchBought { buyer: 2, bought: 2, spent: 300 }.into(),
+		);
+	});
+}
+
+#[test]
+fn buy_many_best_effort_skips_what_can_not_be_bought() {
+	execute_with_try_state(new_test_ext(), || {
+		list_floor();
+		assert_ok!(NftMarketPlaceModule::buy_many(
+			RuntimeOrigin::signed(2),
+			bounded_vec![(0, 0, 100), (0, 5, 100), (0, 1, 200), (0, 2, 300)],
+			350,
+			BuyMode::BestEffort
+		));
+		assert_eq!(owner_of(0, 0), 2);
+		assert_eq!(owner_of(0, 1), 2);
+		// the third listing would go over the maximum total and stays listed
+		assert_eq!(owner_of(0, 2), 1);
+		assert!(NftMarketPlaceModule::offers(0, 2).is_some());
+		System::assert_has_event(
+			crate::Event::BatchItemFailed {
+				class_id: 0,
+				nft_id: 5,
+				error: crate::Error::<Test>::OfferNotFound.into(),
+			}
+			.into(),
+		);
+		System::assert_has_event(
+			crate::Event::BatchItemFailed {
+				class_id: 0,
+				nft_id: 2,
+				error: crate::Error::<Test>::MaxTotalExceeded.into(),
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			crate::Event::BatchBought { buyer: 2, bought: 2, spent: 300 }.into(),
+		);
+	});
+}
+
+#[test]
+fn order_book_is_sorted_and_follows_listings() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 5);
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 3, 400, None));
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 300, None));
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 1, 100, None));
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 2, 200, None));
+		assert_eq!(NftMarketPlaceModule::floor_price(0), Some(100));
+		assert_eq!(
+			NftMarketPlaceModule::listings(0, 10, None),
+			vec![(100, 1), (200, 2), (300, 0), (400, 3)]
+		);
+		// an expired listing is left out before the sweep removes it
+		assert_ok!(NftMarketPlaceModule::create_offer(
+			RuntimeOrigin::signed(1),
+			0,
+			4,
+			50,
+			Some(500)
+		));
+		assert_eq!(NftMarketPlaceModule::floor_price(0), Some(50));
+		Timestamp::set_timestamp(500);
+		assert_eq!(NftMarketPlaceModule::floor_price(0), Some(100));
+		assert_eq!(NftMarketPlaceModule::listings(0, 1, None), vec![(100, 1)]);
+		assert_ok!(NftMarketPlaceModule::cancel_offer(RuntimeOrigin::signed(1), 4, 0));
+
+		// listing again moves the token to its new price
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 150, None));
+		assert_eq!(NftMarketPlaceModule::listings(0, 2, None), vec![(100, 1), (150, 0)]);
+		assert_eq!(NftMarketPlaceModule::listings(0, 2, Some((150, 0))), vec![(200, 2), (400, 3)]);
+
+		assert_ok!(NftMarketPlaceModule::buy_offer(RuntimeOrigin::signed(2), 1, 0, 100));
+		assert_eq!(NftMarketPlaceModule::floor_price(0), Some(150));
+		assert_ok!(NftMarketPlaceModule::cancel_offer(RuntimeOrigin::signed(1), 0, 0));
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 3, 2, 0));
+		assert_ok!(NftModule::burn(RuntimeOrigin::signed(1), 3, 0));
+		assert_eq!(NftMarketPlaceModule::floor_price(0), None);
+		assert_eq!(crate::OrderBook::<Test>::iter_prefix(0).count(), 0);
+	});
+}
+
+fn demo_genesis() -> (pallet_nft::GenesisConfig<Test>, crate::GenesisConfig<Test>) {
+	let body = b"demo".to_vec();
+	let nft = |owner| Nft {
+		owner,
+		description: body.clone(),
+		name: body.clone(),
+		url: body.clone(),
+		external_url: body.clone(),
+		class_id: 0,
+		metadata_hash: None,
+		media_hash: None,
+	};
+	let class = Class {
+		owner: 1,
+		description: body.clone(),
+		name: body.clone(),
+		url: body.clone(),
+		external_url: body.clone(),
+	};
+	(
+		pallet_nft::GenesisConfig {
+			classes: vec![(0, class)],
+			nfts: vec![(0, nft(1)), (1, nft(2))],
+			metadata_verifiers: vec![],
+		},
+		crate::GenesisConfig {
+			parameters: MarketParameters {
+				fee_rate: Permill::from_percent(10),
+				royalty_cap: Permill::from_percent(20),
+				..Default::default()
+			},
+			royalties: vec![(0, Permill::from_percent(5))],
+			listings: vec![(0, 1, 100, None)],
+			auctions: vec![],
+		},
+	)
+}
+
+#[test]
+fn genesis_seeds_collection_listings_auctions_and_parameters() {
+	let (nft, mut market) = demo_genesis();
+	market.auctions.push((0, 0, 50, 10, 20, TimeBase::Block));
+	execute_with_try_state(new_test_ext_with_genesis(nft, market), || {
+		assert_eq!(NftModule::classes(0).map(|class| class.owner), Some(1));
+		assert_eq!(owner_of(0, 0), 1);
+		assert_eq!(owner_of(0, 1), 2);
+		assert_eq!(NftModule::tokens_of_owner(2, 0, None, 10).len(), 1);
+		assert_eq!(NftModule::next_class_id(), Some(1));
+		assert_eq!(NftModule::next_nft_id(), Some(2));
+		assert_eq!(
+			NftMarketPlaceModule::offers(0, 1),
+			Some(Offer { owner: 2, class_id: 0, nft_id: 1, amount: 100, expires_at: None })
+		);
+		assert_eq!(NftMarketPlaceModule::floor_price(0), Some(100));
+		assert_eq!(
+			NftMarketPlaceModule::auctions(0, 0),
+			Some(Auction {
+				owner: 1,
+				nft_id: 0,
+				spender: 1,
+				class_id: 0,
+				min_amount: 0,
+				amount: 50,
+				start_time: 10,
+				end_time: 20,
+				time_base: TimeBase::Block,
+			})
+		);
+		assert_eq!(NftMarketPlaceModule::parameters().fee_rate, Permill::from_percent(10));
+		assert_eq!(NftMarketPlaceModule::royalties(0), Permill::from_percent(5));
+	});
+}
+
+#[test]
+fn sale_pays_fee_and_royalty() {
+	let (nft, market) = demo_genesis();
+	execute_with_try_state(new_test_ext_with_genesis(nft, market), || {
+		let fee_account = NftMarketPlaceModule::fee_account();
+		// endowed at genesis
+		assert_eq!(Balances::free_balance(&fee_account), 1);
+		assert_ok!(NftMarketPlaceModule::buy_offer(RuntimeOrigin::signed(3), 1, 0, 100));
+		assert_eq!(owner_of(0, 1), 3);
+		assert_eq!(Balances::free_balance(3), INITIAL_BALANCE - 100);
+		assert_eq!(Balances::free_balance(&fee_account), 1 + 10);
+		// royalty to the class owner
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 5);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 85);
+
+		// the class owner selling their own token pays no royalty
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(4), 0, 0, 200, None));
+		assert_ok!(NftMarketPlaceModule::accept_offer(RuntimeOrigin::signed(1), 0, 0, 4));
+		assert_eq!(Balances::reserved_balance(4), 0);
+		assert_eq!(Balances::free_balance(4), INITIAL_BALANCE - 200);
+		assert_eq!(Balances::free_balance(&fee_account), 1 + 30);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 5 + 180);
+	});
+}
+
+#[test]
+fn royalty_is_set_by_class_owner_up_to_cap() {
+	let (nft, market) = demo_genesis();
+	execute_with_try_state(new_test_ext_with_genesis(nft, market), || {
+		assert_noop!(
+			NftMarketPlaceModule::set_royalty(
+				RuntimeOrigin::signed(2),
+				0,
+				Permill::from_percent(1)
+			),
+			crate::Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			NftMarketPlaceModule::set_royalty(
+				RuntimeOrigin::signed(1),
+				0,
+				Permill::from_percent(21)
+			),
+			crate::Error::<Test>::RoyaltyAboveCap
+		);
+		assert_ok!(NftMarketPlaceModule::set_royalty(
+			RuntimeOrigin::signed(1),
+			0,
+			Permill::from_percent(20)
+		));
+		System::assert_last_event(
+			crate::Event::RoyaltySet { class_id: 0, royalty: Permill::from_percent(20) }.into(),
+		);
+		assert_ok!(NftMarketPlaceModule::set_royalty(RuntimeOrigin::signed(1), 0, Permill::zero()));
+		assert!(!crate::Royalties::<Test>::contains_key(0));
+	});
+}
+
+#[test]
+fn admin_sets_parameters_within_bounds() {
+	execute_with_try_state(new_test_ext(), || {
+		let parameters = MarketParameters {
+			fee_rate: Permill::from_percent(5),
+			royalty_cap: Permill::from_percent(10),
+			min_bid_increment: Permill::from_percent(10),
+			anti_sniping_window: 20,
+			max_auction_duration: 1_000,
+		};
+		assert_noop!(
+			NftMarketPlaceModule::set_parameters(RuntimeOrigin::signed(1), parameters.into()),
+			BadOrigin
+		);
+		let too_expensive = MarketParameters { fee_rate: Permill::from_percent(95), ..parameters };
+		let all_fee = MarketParameters {
+			fee_rate: Permill::one(),
+			royalty_cap: Permill::zero(),
+			..parameters
+		};
+		let endless = MarketParameters { anti_sniping_window: 1_000, ..parameters };
+		let steep = MarketParameters { min_bid_increment: Permill::from_percent(51), ..parameters };
+		let too_long = MarketParameters {
+			max_auction_duration: MarketParameters::MAX_AUCTION_DURATION + 1,
+			..parameters
+		};
+		for invalid in [too_expensive, all_fee, endless, steep, too_long] {
+			assert_noop!(
+				NftMarketPlaceModule::set_parameters(RuntimeOrigin::root(), invalid.into()),
+				crate::Error::<Test>::InvalidParameters
+			);
+		}
+		assert_ok!(NftMarketPlaceModule::set_parameters(
+			RuntimeOrigin::root(),
+			VersionedMarketParameters::V1(parameters)
+		));
+		System::assert_last_event(crate::Event::ParametersSet { parameters }.into());
+		assert_eq!(NftMarketPlaceModule::parameters(), parameters);
+	});
+}
+
+#[test]
+fn auctions_follow_bid_increment_anti_sniping_and_duration() {
+	execute_with_try_state(new_test_ext(), || {
+		assert_ok!(NftMarketPlaceModule::set_parameters(
+			RuntimeOrigin::root(),
+			MarketParameters {
+				min_bid_increment: Permill::from_percent(10),
+				anti_sniping_window: 20,
+				max_auction_duration: 1_000,
+				..Default::default()
+			}
+			.into()
+		));
+		create_class_and_mint(1, 1);
+		assert_noop!(
+			NftMarketPlaceModule::create_auction(
+				RuntimeOrigin::signed(1),
+				0,
+				0,
+				100,
+				0,
+				1_001,
+				TimeBase::UnixMs
+			),
+			crate::Error::<Test>::AuctionTooLong
+		);
+		assert_ok!(NftMarketPlaceModule::create_auction(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			100,
+			0,
+			100,
+			TimeBase::UnixMs
+		));
+		// the first bid only has to reach the reserve price
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(2), 0, 0, 100));
+		assert_noop!(
+			NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(3), 0, 0, 109),
+			crate::Error::<Test>::AmountToLow
+		);
+
+		// a bid in the last 20 ms pushes the end back
+		Timestamp::set_timestamp(90);
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(3), 0, 0, 110));
+		System::assert_last_event(
+			crate::Event::AuctionExtended { class_id: 0, nft_id: 0, end_time: 110 }.into(),
+		);
+		Timestamp::set_timestamp(100);
+		assert_noop!(
+			NftMarketPlaceModule::auction_finish(RuntimeOrigin::signed(3), 0, 0),
+			crate::Error::<Test>::WaitWhenAuctionFinish
+		);
+		Timestamp::set_timestamp(110);
+		assert_ok!(NftMarketPlaceModule::auction_finish(RuntimeOrigin::signed(3), 0, 0));
+		assert_eq!(owner_of(0, 0), 3);
+	});
+}
+
+#[test]
+fn auctions_timed_in_blocks_ignore_the_timestamp() {
+	execute_with_try_state(new_test_ext(), || {
+		// 10 ms blocks, the window is 3 blocks and the longest auction 100 blocks
+		assert_ok!(NftMarketPlaceModule::set_parameters(
+			RuntimeOrigin::root(),
+			MarketParameters {
+				anti_sniping_window: 25,
+				max_auction_duration: 1_000,
+				..Default::default()
+			}
+			.into()
+		));
+		create_class_and_mint(1, 1);
+		assert_noop!(
+			NftMarketPlaceModule::create_auction(
+				RuntimeOrigin::signed(1),
+				0,
+				0,
+				100,
+				1,
+				102,
+				TimeBase::Block
+			),
+			crate::Error::<Test>::AuctionTooLong
+		);
+		assert_ok!(NftMarketPlaceModule::create_auction(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			100,
+			1,
+			21,
+			TimeBase::Block
+		));
+		System::assert_last_event(
+			crate::Event::AuctionCreated {
+				class_id: 0,
+				nft_id: 0,
+				owner: 1,
+				min_amount: 0,
+				amount: 100,
+				start_time: 1,
+				end_time: 21,
+				time_base: TimeBase::Block,
+			}
+			.into(),
+		);
+
+		// a timestamp far past the end does not end the auction
+		Timestamp::set_timestamp(1_000_000);
+		System::set_block_number(19);
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(2), 0, 0, 100));
+		System::assert_last_event(
+			crate::Event::AuctionExtended { class_id: 0, nft_id: 0, end_time: 22 }.into(),
+		);
+		System::set_block_number(21);
+		assert_noop!(
+			NftMarketPlaceModule::auction_finish(RuntimeOrigin::signed(2), 0, 0),
+			crate::Error::<Test>::WaitWhenAuctionFinish
+		);
+		System::set_block_number(22);
+		assert_ok!(NftMarketPlaceModule::auction_finish(RuntimeOrigin::signed(2), 0, 0));
+		assert_eq!(owner_of(0, 0), 2);
+	});
+}
+
+#[test]
+fn block_numbers_and_unix_ms_convert_with_the_block_time() {
+	execute_with_try_state(new_test_ext(), || {
+		System::set_block_number(22);
+		Timestamp::set_timestamp(1_000);
+		assert_eq!(NftMarketPlaceModule::block_to_unix_ms(22), 1_000);
+		assert_eq!(NftMarketPlaceModule::block_to_unix_ms(25), 1_030);
+		assert_eq!(NftMarketPlaceModule::block_to_unix_ms(20), 980);
+		assert_eq!(NftMarketPlaceModule::block_to_unix_ms(0), 780);
+		// the first block produced at or after the time
+		assert_eq!(NftMarketPlaceModule::unix_ms_to_block(1_031), 26);
+		assert_eq!(NftMarketPlaceModule::unix_ms_to_block(1_030), 25);
+		assert_eq!(NftMarketPlaceModule::unix_ms_to_block(985), 21);
+		assert_eq!(NftMarketPlaceModule::unix_ms_to_block(0), 0);
+	});
+}
+
+#[test]
+fn paused_calls_fail_but_funds_and_tokens_can_be_recovered() {
+	execute_with_try_state(new_test_ext(), || {
+		list_floor();
+		assert_ok!(NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(2), 0, 0, 50, None));
+		assert_noop!(
+			NftMarketPlaceModule::pause(RuntimeOrigin::signed(1), PauseScope::All),
+			BadOrigin
+		);
+
+		let listing = PauseScope::Call(MarketCall::Listing);
+		assert_ok!(NftMarketPlaceModule::pause(RuntimeOrigin::root(), listing));
+		System::assert_last_event(crate::Event::MarketPaused { scope: listing }.into());
+		create_class_and_mint(1, 1);
+		assert_noop!(
+			NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 1, 3, 100, None),
+			crate::Error::<Test>::Paused
+		);
+		// only listing is paused
+		assert_ok!(NftMarketPlaceModule::buy_offer(RuntimeOrigin::signed(3), 2, 0, 300));
+
+		assert_ok!(NftMarketPlaceModule::pause(RuntimeOrigin::root(), PauseScope::Class(0)));
+		assert_noop!(
+			NftMarketPlaceModule::buy_offer(RuntimeOrigin::signed(3), 1, 0, 200),
+			crate::Error::<Test>::Paused
+		);
+		assert_ok!(NftMarketPlaceModule::buy_many(
+			RuntimeOrigin::signed(3),
+			bounded_vec![(0, 1, 200)],
+			200,
+			BuyMode::BestEffort
+		));
+		System::assert_has_event(
+			crate::Event::BatchItemFailed {
+				class_id: 0,
+				nft_id: 1,
+				error: crate::Error::<Test>::Paused.into(),
+			}
+			.into(),
+		);
+
+		assert_ok!(NftMarketPlaceModule::pause(RuntimeOrigin::root(), PauseScope::All));
+		assert!(NftMarketPlaceModule::is_paused(MarketCall::Offer, 1));
+		assert_noop!(
+			NftMarketPlaceModule::make_offer(RuntimeOrigin::signed(2), 1, 3, 50, None),
+			crate::Error::<Test>::Paused
+		);
+		// cancelling and withdrawing still work
+		assert_ok!(NftMarketPlaceModule::cancel_offer(RuntimeOrigin::signed(1), 1, 0));
+		assert_ok!(NftMarketPlaceModule::withdraw_offer(RuntimeOrigin::signed(2), 0, 0));
+		assert_eq!(Balances::reserved_balance(2), 0);
+
+		for scope in [PauseScope::All, PauseScope::Class(0), listing] {
+			assert_ok!(NftMarketPlaceModule::unpause(RuntimeOrigin::root(), scope));
+		}
+		System::assert_last_event(crate::Event::MarketUnpaused { scope: listing }.into());
+		assert!(!NftMarketPlaceModule::is_paused(MarketCall::Listing, 1));
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 1, 3, 100, None));
+	});
+}
+
+#[test]
+fn rent_is_reserved_upfront_and_paid_out_period_by_period() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		let rental = PauseScope::Call(MarketCall::Rental);
+		assert_ok!(NftMarketPlaceModule::pause(RuntimeOrigin::root(), rental));
+		assert_noop!(
+			NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(1), 0, 0, 10, 5, 3),
+			crate::Error::<Test>::Paused
+		);
+		assert_ok!(NftMarketPlaceModule::unpause(RuntimeOrigin::root(), rental));
+		assert_noop!(
+			NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(2), 0, 0, 10, 5, 3),
+			crate::Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(1), 0, 0, 10, 0, 3),
+			crate::Error::<Test>::InvalidRentalTerms
+		);
+		assert_ok!(NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(1), 0, 0, 10, 5, 3));
+		assert_noop!(
+			NftMarketPlaceModule::rent(RuntimeOrigin::signed(1), 0, 0, 2),
+			crate::Error::<Test>::CantRentYourNft
+		);
+		assert_noop!(
+			NftMarketPlaceModule::rent(RuntimeOrigin::signed(2), 0, 0, 4),
+			crate::Error::<Test>::InvalidRentalTerms
+		);
+
+		// two periods of 5 blocks from block 1
+		assert_ok!(NftMarketPlaceModule::rent(RuntimeOrigin::signed(2), 0, 0, 2));
+		System::assert_last_event(
+			crate::Event::Rented {
+				class_id: 0,
+				nft_id: 0,
+				owner: 1,
+				renter: 2,
+				periods: 2,
+				rent: 20,
+				expires: 11,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::reserved_balance(2), 20);
+		assert_eq!(NftModule::user_of(0, 0), Some(2));
+		assert_eq!(NftMarketPlaceModule::rental_listings(0, 0), None);
+
+		// the token can't be sold or moved while it is rented
+		assert_noop!(
+			NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 100, None),
+			crate::Error::<Test>::TokenRented
+		);
+		assert_noop!(
+			NftModule::transfer(RuntimeOrigin::signed(1), 3, 0, 0),
+			Error::<Test>::TokenInUse
+		);
+		assert_noop!(
+			NftMarketPlaceModule::claim_rent(RuntimeOrigin::signed(1), 0, 0),
+			crate::Error::<Test>::NothingToClaim
+		);
+
+		System::set_block_number(6);
+		assert_noop!(
+			NftMarketPlaceModule::claim_rent(RuntimeOrigin::signed(2), 0, 0),
+			crate::Error::<Test>::NotOwner
+		);
+		assert_ok!(NftMarketPlaceModule::claim_rent(RuntimeOrigin::signed(1), 0, 0));
+		System::assert_last_event(
+			crate::Event::RentClaimed { class_id: 0, nft_id: 0, owner: 1, amount: 10 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(2), 10);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 10);
+
+		// the right lapses on its own, the sweep pays out the rest
+		System::set_block_number(11);
+		assert_eq!(NftModule::user_of(0, 0), None);
+		NftMarketPlaceModule::on_idle(11, Weight::MAX);
+		System::assert_last_event(
+			crate::Event::RentalEnded { class_id: 0, nft_id: 0, owner: 1, renter: 2 }.into(),
+		);
+		assert_eq!(NftMarketPlaceModule::rentals(0, 0), None);
+		assert_eq!(NftModule::users(0, 0), None);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 20);
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 3, 0, 0));
+	});
+}
+
+#[test]
+fn rental_that_is_over_ends_when_the_token_changes_hands() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		assert_ok!(NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(1), 0, 0, 10, 5, 3));
+		assert_ok!(NftMarketPlaceModule::rent(RuntimeOrigin::signed(2), 0, 0, 3));
+		assert_noop!(
+			NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(1), 0, 0, 10, 5, 3),
+			crate::Error::<Test>::TokenRented
+		);
+
+		System::set_block_number(16);
+		assert_ok!(NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(1), 0, 0, 10, 5, 3));
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 3, 0, 0));
+		System::assert_has_event(
+			crate::Event::RentClaimed { class_id: 0, nft_id: 0, owner: 1, amount: 30 }.into(),
+		);
+		System::assert_has_event(
+			crate::Event::RentalListingCanceled { class_id: 0, nft_id: 0 }.into(),
+		);
+		assert_eq!(NftMarketPlaceModule::rentals(0, 0), None);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 30);
+	});
+}
+
+#[test]
+fn ending_a_rental_pays_what_is_still_reserved_and_keeps_a_new_user() {
+	new_test_ext().execute_with(|| {
+		create_class_and_mint(1, 1);
+		assert_ok!(NftMarketPlaceModule::list_for_rent(RuntimeOrigin::signed(1), 0, 0, 10, 5, 3));
+		assert_ok!(NftMarketPlaceModule::rent(RuntimeOrigin::signed(2), 0, 0, 3));
+		// some of the rent is not reserved anymore, e.g. slashed by another pallet
+		Balances::unreserve(&2, 5);
+
+		// the owner gives the token a new user before the rental is ended
+		System::set_block_number(16);
+		assert_ok!(NftModule::set_user(RuntimeOrigin::signed(1), 0, 0, 4, 30));
+		assert_ok!(NftMarketPlaceModule::claim_rent(RuntimeOrigin::signed(1), 0, 0));
+		System::assert_has_event(
+			crate::Event::RentClaimed { class_id: 0, nft_id: 0, owner: 1, amount: 25 }.into(),
+		);
+		System::assert_last_event(
+			crate::Event::RentalEnded { class_id: 0, nft_id: 0, owner: 1, renter: 2 }.into(),
+		);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 25);
+		assert_eq!(NftModule::user_of(0, 0), Some(4));
+	});
+}
+
+#[test]
+fn loan_is_repaid_with_interest_to_get_the_token_back() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 100, None));
+		assert_noop!(
+			NftMarketPlaceModule::request_loan(RuntimeOrigin::signed(2), 0, 0, 500, 50, 10),
+			crate::Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			NftMarketPlaceModule::request_loan(RuntimeOrigin::signed(1), 0, 0, 0, 50, 10),
+			crate::Error::<Test>::InvalidLoanTerms
+		);
+
+		// the token goes into escrow and its listing away
+		assert_ok!(NftMarketPlaceModule::request_loan(RuntimeOrigin::signed(1), 0, 0, 500, 50, 10));
+		System::assert_last_event(
+			crate::Event::LoanRequested {
+				class_id: 0,
+				nft_id: 0,
+				borrower: 1,
+				amount: 500,
+				interest: 50,
+				duration: 10,
+			}
+			.into(),
+		);
+		assert_eq!(owner_of(0, 0), NftMarketPlaceModule::escrow_account());
+		assert_eq!(NftMarketPlaceModule::offers(0, 0), None);
+
+		assert_noop!(
+			NftMarketPlaceModule::fund_loan(RuntimeOrigin::signed(1), 0, 0, 500, 50, 10),
+			crate::Error::<Test>::CantLendToYourself
+		);
+		// the lender only funds the terms it saw
+		assert_noop!(
+			NftMarketPlaceModule::fund_loan(RuntimeOrigin::signed(2), 0, 0, 500, 50, 5),
+			crate::Error::<Test>::LoanTermsMismatch
+		);
+		assert_noop!(
+			NftMarketPlaceModule::fund_loan(RuntimeOrigin::signed(2), 0, 0, 500, 100, 10),
+			crate::Error::<Test>::LoanTermsMismatch
+		);
+		assert_ok!(NftMarketPlaceModule::fund_loan(RuntimeOrigin::signed(2), 0, 0, 500, 50, 10));
+		System::assert_last_event(
+			crate::Event::LoanFunded {
+				class_id: 0,
+				nft_id: 0,
+				borrower: 1,
+				lender: 2,
+				amount: 500,
+				deadline: 11,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 500);
+		assert_noop!(
+			NftMarketPlaceModule::fund_loan(RuntimeOrigin::signed(3), 0, 0, 500, 50, 10),
+			crate::Error::<Test>::LoanAlreadyFunded
+		);
+		assert_noop!(
+			NftMarketPlaceModule::cancel_loan_request(RuntimeOrigin::signed(1), 0, 0),
+			crate::Error::<Test>::LoanAlreadyFunded
+		);
+		assert_noop!(
+			NftMarketPlaceModule::repay_loan(RuntimeOrigin::signed(2), 0, 0),
+			crate::Error::<Test>::NotOwner
+		);
+
+		// the deadline block is still in time
+		System::set_block_number(11);
+		assert_ok!(NftMarketPlaceModule::repay_loan(RuntimeOrigin::signed(1), 0, 0));
+		System::assert_last_event(
+			crate::Event::LoanRepaid {
+				class_id: 0,
+				nft_id: 0,
+				borrower: 1,
+				lender: 2,
+				amount: 550,
+			}
+			.into(),
+		);
+		assert_eq!(owner_of(0, 0), 1);
+		assert_eq!(NftMarketPlaceModule::loans(0, 0), None);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 50);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 50);
+	});
+}
+
+#[test]
+fn lender_forecloses_a_loan_not_repaid_in_time() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		let loan = PauseScope::Call(MarketCall::Loan);
+		assert_ok!(NftMarketPlaceModule::pause(RuntimeOrigin::root(), loan));
+		assert_noop!(
+			NftMarketPlaceModule::request_loan(RuntimeOrigin::signed(1), 0, 0, 500, 50, 10),
+			crate::Error::<Test>::Paused
+		);
+		assert_ok!(NftMarketPlaceModule::unpause(RuntimeOrigin::root(), loan));
+
+		// a request without a lender can be taken back
+		assert_ok!(NftMarketPlaceModule::request_loan(RuntimeOrigin::signed(1), 0, 0, 500, 50, 10));
+		assert_ok!(NftMarketPlaceModule::cancel_loan_request(RuntimeOrigin::signed(1), 0, 0));
+		System::assert_last_event(
+			crate::Event::LoanRequestCanceled { class_id: 0, nft_id: 0, borrower: 1 }.into(),
+		);
+		assert_eq!(owner_of(0, 0), 1);
+
+		assert_ok!(NftMarketPlaceModule::request_loan(RuntimeOrigin::signed(1), 0, 0, 500, 50, 10));
+		assert_noop!(
+			NftMarketPlaceModule::foreclose(RuntimeOrigin::signed(2), 0, 0),
+			crate::Error::<Test>::LoanNotFunded
+		);
+		assert_ok!(NftMarketPlaceModule::fund_loan(RuntimeOrigin::signed(2), 0, 0, 500, 50, 10));
+		assert_noop!(
+			NftMarketPlaceModule::foreclose(RuntimeOrigin::signed(3), 0, 0),
+			crate::Error::<Test>::NotLender
+		);
+		System::set_block_number(11);
+		assert_noop!(
+			NftMarketPlaceModule::foreclose(RuntimeOrigin::signed(2), 0, 0),
+			crate::Error::<Test>::LoanNotOverdue
+		);
+
+		System::set_block_number(12);
+		assert_noop!(
+			NftMarketPlaceModule::repay_loan(RuntimeOrigin::signed(1), 0, 0),
+			crate::Error::<Test>::LoanOverdue
+		);
+		assert_ok!(NftMarketPlaceModule::foreclose(RuntimeOrigin::signed(2), 0, 0));
+		System::assert_last_event(
+			crate::Event::LoanForeclosed { class_id: 0, nft_id: 0, borrower: 1, lender: 2 }.into(),
+		);
+		assert_eq!(owner_of(0, 0), 2);
+		assert_eq!(NftMarketPlaceModule::loans(0, 0), None);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 500);
+	});
+}
+
+#[test]
+fn escrowed_loan_token_cant_be_moved_or_burned() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 1);
+		assert_ok!(NftMarketPlaceModule::request_loan(RuntimeOrigin::signed(1), 0, 0, 500, 50, 10));
+		assert_ok!(NftMarketPlaceModule::fund_loan(RuntimeOrigin::signed(2), 0, 0, 500, 50, 10));
+
+		assert_noop!(
+			NftModule::transfer(RuntimeOrigin::signed(3), 3, 0, 0),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(NftModule::burn(RuntimeOrigin::signed(1), 0, 0), Error::<Test>::NotOwner);
+		// not even the escrow account or another pallet can take it out
+		let escrow = NftMarketPlaceModule::escrow_account();
+		assert_noop!(
+			NftModule::transfer(RuntimeOrigin::signed(escrow), 3, 0, 0),
+			Error::<Test>::TokenLocked
+		);
+		assert_noop!(
+			NftModule::burn(RuntimeOrigin::signed(escrow), 0, 0),
+			Error::<Test>::TokenLocked
+		);
+		assert_noop!(NftModule::do_transfer(0, 0, &3), Error::<Test>::TokenLocked);
+
+		// once the loan is settled the token moves again
+		assert_ok!(NftMarketPlaceModule::repay_loan(RuntimeOrigin::signed(1), 0, 0));
+		assert_ok!(NftModule::transfer(RuntimeOrigin::signed(1), 3, 0, 0));
+		assert_eq!(owner_of(0, 0), 3);
+	});
+}
+
+#[test]
+fn owner_cant_give_a_user_to_a_token_on_the_market() {
+	execute_with_try_state(new_test_ext(), || {
+		create_class_and_mint(1, 2);
+		assert_ok!(NftMarketPlaceModule::create_auction(
+			RuntimeOrigin::signed(1),
+			0,
+			0,
+			100,
+			0,
+			100,
+			TimeBase::UnixMs
+		));
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(2), 0, 0, 100));
+		assert_noop!(
+			NftModule::set_user(RuntimeOrigin::signed(1), 0, 0, 3, 50),
+			Error::<Test>::TokenLocked
+		);
+
+		// the winner gets the token without anyone else using it
+		Timestamp::set_timestamp(100);
+		assert_ok!(NftMarketPlaceModule::auction_finish(RuntimeOrigin::signed(2), 0, 0));
+		assert_eq!(owner_of(0, 0), 2);
+		assert_eq!(NftModule::user_of(0, 0), None);
+		assert_ok!(NftModule::set_user(RuntimeOrigin::signed(2), 0, 0, 3, 50));
+
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 1, 100, None));
+		assert_noop!(
+			NftModule::set_user(RuntimeOrigin::signed(1), 0, 1, 3, 50),
+			Error::<Test>::TokenLocked
+		);
+	});
+}
+
+#[test]
+fn try_state_catches_stale_listings_and_unbacked_bids() {
+	new_test_ext().execute_with(|| {
+		create_class_and_mint(1, 2);
+		assert_ok!(NftMarketPlaceModule::create_offer(RuntimeOrigin::signed(1), 0, 0, 100, None));
+		let now = NftMarketPlaceModule::now();
+		assert_ok!(NftMarketPlaceModule::create_auction(
+			RuntimeOrigin::signed(1),
+			0,
+			1,
+			10,
+			now,
+			now + 100,
+			TimeBase::UnixMs
+		));
+		assert_ok!(NftMarketPlaceModule::buy_auction(RuntimeOrigin::signed(2), 1, 0, 50));
+		assert_ok!(NftMarketPlaceModule::do_try_state());
+
+		// the token changes hands without going through `OnTransfer`
+		pallet_nft::Nfts::<Test>::mutate(0, 0, |nft| nft.as_mut().unwrap().owner = 3);
+		assert_eq!(NftMarketPlaceModule::do_try_state(), Err("listing of a non owner"));
+		pallet_nft::Nfts::<Test>::mutate(0, 0, |nft| nft.as_mut().unwrap().owner = 1);
+
+		Balances::unreserve(&2, 20);
+		assert_eq!(
+			NftMarketPlaceModule::do_try_state(),
+			Err("reserved balance does not match the outstanding bids")
+		);
+		assert_ok!(Balances::reserve(&2, 20));
+
+		// accounts without any bid are left to the pallets reserving their funds
+		assert_ok!(Balances::reserve(&3, 20));
+		assert_ok!(NftMarketPlaceModule::do_try_state());
+	});
+}
+
+#[test]
+fn v1_migration_rebuilds_the_order_books() {
+	new_test_ext().execute_with(|| {
+		let listings = [(0, 0, 50), (0, 1, 10), (0, 2, 40), (0, 3, 30), (0, 4, 20), (1, 0, 5)];
+		for (class_id, nft_id, amount) in listings {
+			let old = migrations::v0::Offer { nft_id, class_id, amount, owner: 1 };
+			let key = crate::Offers::<Test>::hashed_key_for(class_id, nft_id);
+			frame_support::storage::unhashed::put(&key, &old);
+		}
+		let auction = migrations::v3::Auction {
+			owner: 2,
+			nft_id: 0,
+			spender: 2,
+			class_id: 2,
+			min_amount: 0,
+			amount: 100,
+			start_time: 0,
+			end_time: 100,
+		};
+		let auction_key = crate::Auctions::<Test>::hashed_key_for(2, 0);
+		frame_support::storage::unhashed::put(&auction_key, &auction);
+		StorageVersion::new(0).put::<NftMarketPlaceModule>();
+
+		#[cfg(feature = "try-runtime")]
+		let state = migrations::v1::MigrateToV1::<Test>::pre_upgrade().unwrap();
+		migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		#[cfg(feature = "try-runtime")]
+		migrations::v1::MigrateToV1::<Test>::post_upgrade(state).unwrap();
+
+		assert_eq!(NftMarketPlaceModule::on_chain_storage_version(), 1);
+		assert_eq!(
+			NftMarketPlaceModule::offers(0, 1),
+			Some(Offer { nft_id: 1, class_id: 0, amount: 10, owner: 1, expires_at: None })
+		);
+		assert_eq!(
+			NftMarketPlaceModule::listings(0, 10, None),
+			vec![(10, 1), (20, 4), (30, 3), (40, 2), (50, 0)]
+		);
+		assert_eq!(NftMarketPlaceModule::listings(1, 10, None), vec![(5, 0)]);
+		assert_eq!(frame_support::storage::unhashed::get(&auction_key), Some(auction));
+	});
+}
+
+#[test]
+fn v2_migration_moves_fee_rate_and_royalty_cap_into_parameters() {
+	new_test_ext().execute_with(|| {
+		let old_value =
+			|name: &[u8]| frame_support::storage::storage_prefix(b"NftMarketPlaceModule", name);
+		frame_support::storage::unhashed::put(&old_value(b"FeeRate"), &Permill::from_percent(3));
+		frame_support::storage::unhashed::put(&old_value(b"RoyaltyCap"), &Permill::from_percent(8));
+		StorageVersion::new(1).put::<NftMarketPlaceModule>();
+
+		#[cfg(feature = "try-runtime")]
+		let state = migrations::v2::MigrateToV2::<Test>::pre_upgrade().unwrap();
+		migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+		#[cfg(feature = "try-runtime")]
+		migrations::v2::MigrateToV2::<Test>::post_upgrade(state).unwrap();
+
+		assert_eq!(NftMarketPlaceModule::on_chain_storage_version(), 2);
+		assert_eq!(
+			NftMarketPlaceModule::parameters(),
+			MarketParameters {
+				fee_rate: Permill::from_percent(3),
+				royalty_cap: Permill::from_percent(8),
+				..Default::default()
+			}
+		);
+		assert!(!frame_support::storage::unhashed::exists(&old_value(b"FeeRate")));
+	});
+}
+
+#[test]
+fn v3_migration_times_existing_auctions_in_unix_ms() {
+	new_test_ext().execute_with(|| {
+		let old = migrations::v3::Auction {
+			owner: 2u64,
+			nft_id: 0,
+			spender: 3,
+			class_id: 2,
+			min_amount: 150u128,
+			amount: 100,
+			start_time: 10,
+			end_time: 20,
+		};
+		let key = crate::Auctions::<Test>::hashed_key_for(2, 0);
+		frame_support::storage::unhashed::put(&key, &old);
+		StorageVersion::new(2).put::<NftMarketPlaceModule>();
+
+		#[cfg(feature = "try-runtime")]
+		let state = migrations::v3::MigrateToV3::<Test>::pre_upgrade().unwrap();
+		migrations::v3::MigrateToV3::<Test>::on_runtime_upgrade();
+		#[cfg(feature = "try-runtime")]
+		migrations::v3::MigrateToV3::<Test>::post_upgrade(state).unwrap();
+
+		assert_eq!(NftMarketPlaceModule::on_chain_storage_version(), 3);
+		assert_eq!(
+			NftMarketPlaceModule::auctions(2, 0),
+			Some(Auction {
+				owner: 2,
+				nft_id: 0,
+				spender: 3,
+				class_id: 2,
+				min_amount: 150,
+				amount: 100,
+				start_time: 10,
+				end_time: 20,
+				time_base: TimeBase::UnixMs,
+			})
+		);
+	});
+}
+
+#[test]
+fn fee_account_is_endowed_on_upgrade() {
+	new_test_ext().execute_with(|| {
+		let fee_account = NftMarketPlaceModule::fee_account();
+		Balances::make_free_balance_be(&fee_account, 0);
+		assert_eq!(Balances::total_balance(&fee_account), 0);
+
+		migrations::EndowFeeAccount::<Test>::on_runtime_upgrade();
+		#[cfg(feature = "try-runtime")]
+		migrations::EndowFeeAccount::<Test>::post_upgrade(Vec::new()).unwrap();
+		assert_eq!(Balances::free_balance(&fee_account), 1);
+
+		// nothing to do once the account exists
+		Balances::make_free_balance_be(&fee_account, 10);
+		migrations::EndowFeeAccount::<Test>::on_runtime_upgrade();
+		assert_eq!(Balances::free_balance(&fee_account), 10);
+	});
+}
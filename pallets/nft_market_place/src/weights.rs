@@ -0,0 +1,754 @@
+//! Placeholder weights for pallet_nft_market_place
+//!
+//! THESE ARE NOT BENCHMARK RESULTS. The storage reads and writes follow the code of each call,
+//! the execution times are round guesses meant to err on the high side. Replace this file with
+//! the output of the `benchmark pallet` command below, run on reference hardware, before relying
+//! on it in production. The command needs a template producing the `WeightInfo` trait, such as
+//! `.maintain/frame-weight-template.hbs` of Substrate, which is not part of this repository.
+
+// Command to generate:
+// ./target/release/node-template
+// benchmark
+// pallet
+// --chain=dev
+// --steps=50
+// --repeat=20
+// --pallet=pallet_nft_market_place
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --output=pallets/nft_market_place/src/weights.rs
+// --template=<path to frame-weight-template.hbs>
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_nft_market_place.
+pub trait WeightInfo {
+	fn create_offer() -> Weight;
+	fn buy_offer() -> Weight;
+	fn create_auction() -> Weight;
+	fn buy_auction() -> Weight;
+	fn auction_finish() -> Weight;
+	fn cancel_auction() -> Weight;
+	fn cancel_offer() -> Weight;
+	fn create_swap() -> Weight;
+	fn claim_swap() -> Weight;
+	fn cancel_swap() -> Weight;
+	fn make_offer() -> Weight;
+	fn withdraw_offer() -> Weight;
+	fn accept_offer() -> Weight;
+	fn buy_many(n: u32, ) -> Weight;
+	fn set_royalty() -> Weight;
+	fn pause() -> Weight;
+	fn unpause() -> Weight;
+	fn set_parameters() -> Weight;
+	fn list_for_rent() -> Weight;
+	fn cancel_rental_listing() -> Weight;
+	fn rent() -> Weight;
+	fn claim_rent() -> Weight;
+	fn request_loan() -> Weight;
+	fn cancel_loan_request() -> Weight;
+	fn fund_loan() -> Weight;
+	fn repay_loan() -> Weight;
+	fn foreclose() -> Weight;
+	fn on_transfer() -> Weight;
+	fn on_burn() -> Weight;
+}
+
+/// Weights for pallet_nft_market_place using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:2)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	fn create_offer() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	fn buy_offer() -> Weight {
+		Weight::from_ref_time(100_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(14 as u64))
+			.saturating_add(T::DbWeight::get().writes(11 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:0 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	fn create_auction() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(8 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	fn buy_auction() -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(8 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	fn auction_finish() -> Weight {
+		Weight::from_ref_time(95_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(14 as u64))
+			.saturating_add(T::DbWeight::get().writes(10 as u64))
+	}
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	fn cancel_auction() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	fn cancel_offer() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: NftMarketPlaceModule NextSwapId (r:1 w:1)
+	// Storage: NftMarketPlaceModule Swaps (r:0 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	fn create_swap() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(10 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: NftModule Nfts (r:2 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:4 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftMarketPlaceModule Offers (r:2 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:2 w:2)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:2 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:4)
+	// Storage: NftMarketPlaceModule Paused (r:4 w:0)
+	// Storage: NftModule Users (r:2 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:2 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:2 w:0)
+	fn claim_swap() -> Weight {
+		Weight::from_ref_time(105_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(26 as u64))
+			.saturating_add(T::DbWeight::get().writes(14 as u64))
+	}
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:0 w:1)
+	fn cancel_swap() -> Weight {
+		Weight::from_ref_time(35_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	fn make_offer() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn withdraw_offer() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:2 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	fn accept_offer() -> Weight {
+		Weight::from_ref_time(105_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(17 as u64))
+			.saturating_add(T::DbWeight::get().writes(12 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	/// The range of component `n` is `[1, 20]`.
+	fn buy_many(n: u32, ) -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(100_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().reads((14 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+			.saturating_add(T::DbWeight::get().writes((10 as u64).saturating_mul(n as u64)))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: NftMarketPlaceModule Royalties (r:0 w:1)
+	fn set_royalty() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:0 w:1)
+	fn pause() -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:0 w:1)
+	fn unpause() -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Parameters (r:0 w:1)
+	fn set_parameters() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:0 w:1)
+	fn list_for_rent() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	fn cancel_rental_listing() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	// Storage: NftModule Nfts (r:2 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftModule Users (r:1 w:1)
+	fn rent() -> Weight {
+		Weight::from_ref_time(65_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(13 as u64))
+			.saturating_add(T::DbWeight::get().writes(5 as u64))
+	}
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftModule Users (r:1 w:1)
+	fn claim_rent() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:2 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:2 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Loans (r:0 w:1)
+	fn request_loan() -> Weight {
+		Weight::from_ref_time(65_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(13 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	fn cancel_loan_request() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(9 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn fund_loan() -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	fn repay_loan() -> Weight {
+		Weight::from_ref_time(80_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(11 as u64))
+			.saturating_add(T::DbWeight::get().writes(6 as u64))
+	}
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	fn foreclose() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(9 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: NftMarketPlaceModule Loans (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	fn on_transfer() -> Weight {
+		Weight::from_ref_time(100_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(13 as u64))
+			.saturating_add(T::DbWeight::get().writes(13 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule Loans (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	fn on_burn() -> Weight {
+		Weight::from_ref_time(90_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(11 as u64))
+			.saturating_add(T::DbWeight::get().writes(11 as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:2)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	fn create_offer() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	fn buy_offer() -> Weight {
+		Weight::from_ref_time(100_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(14 as u64))
+			.saturating_add(RocksDbWeight::get().writes(11 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:0 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	fn create_auction() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(8 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	fn buy_auction() -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(8 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	fn auction_finish() -> Weight {
+		Weight::from_ref_time(95_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(14 as u64))
+			.saturating_add(RocksDbWeight::get().writes(10 as u64))
+	}
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	fn cancel_auction() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	fn cancel_offer() -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: NftMarketPlaceModule NextSwapId (r:1 w:1)
+	// Storage: NftMarketPlaceModule Swaps (r:0 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	fn create_swap() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(10 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: NftModule Nfts (r:2 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:4 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftMarketPlaceModule Offers (r:2 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:2 w:2)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:2 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:4)
+	// Storage: NftMarketPlaceModule Paused (r:4 w:0)
+	// Storage: NftModule Users (r:2 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:2 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:2 w:0)
+	fn claim_swap() -> Weight {
+		Weight::from_ref_time(105_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(26 as u64))
+			.saturating_add(RocksDbWeight::get().writes(14 as u64))
+	}
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:0 w:1)
+	fn cancel_swap() -> Weight {
+		Weight::from_ref_time(35_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	fn make_offer() -> Weight {
+		Weight::from_ref_time(40_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn withdraw_offer() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:2 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	fn accept_offer() -> Weight {
+		Weight::from_ref_time(105_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(17 as u64))
+			.saturating_add(RocksDbWeight::get().writes(12 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftMarketPlaceModule Royalties (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	/// The range of component `n` is `[1, 20]`.
+	fn buy_many(n: u32, ) -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(100_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().reads((14 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes((10 as u64).saturating_mul(n as u64)))
+	}
+	// Storage: NftModule Classes (r:1 w:0)
+	// Storage: NftMarketPlaceModule Parameters (r:1 w:0)
+	// Storage: NftMarketPlaceModule Royalties (r:0 w:1)
+	fn set_royalty() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:0 w:1)
+	fn pause() -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:0 w:1)
+	fn unpause() -> Weight {
+		Weight::from_ref_time(15_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Parameters (r:0 w:1)
+	fn set_parameters() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Nfts (r:1 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:0 w:1)
+	fn list_for_rent() -> Weight {
+		Weight::from_ref_time(30_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	fn cancel_rental_listing() -> Weight {
+		Weight::from_ref_time(20_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	// Storage: NftModule Nfts (r:2 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftModule Users (r:1 w:1)
+	fn rent() -> Weight {
+		Weight::from_ref_time(65_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(13 as u64))
+			.saturating_add(RocksDbWeight::get().writes(5 as u64))
+	}
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftModule Users (r:1 w:1)
+	fn claim_rent() -> Weight {
+		Weight::from_ref_time(50_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:2 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:2 w:0)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Loans (r:0 w:1)
+	fn request_loan() -> Weight {
+		Weight::from_ref_time(65_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(13 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	fn cancel_loan_request() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Paused (r:3 w:0)
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn fund_loan() -> Weight {
+		Weight::from_ref_time(45_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	fn repay_loan() -> Weight {
+		Weight::from_ref_time(80_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(11 as u64))
+			.saturating_add(RocksDbWeight::get().writes(6 as u64))
+	}
+	// Storage: NftMarketPlaceModule Loans (r:1 w:1)
+	// Storage: NftModule Nfts (r:1 w:1)
+	// Storage: NftModule Users (r:1 w:0)
+	// Storage: NftMarketPlaceModule Offers (r:1 w:0)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:0)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:0)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:0)
+	// Storage: NftModule TokensByOwner (r:0 w:2)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:0)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:0)
+	fn foreclose() -> Weight {
+		Weight::from_ref_time(55_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:4 w:4)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule BuyerOffers (r:1 w:1)
+	// Storage: NftMarketPlaceModule Loans (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	fn on_transfer() -> Weight {
+		Weight::from_ref_time(100_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(13 as u64))
+			.saturating_add(RocksDbWeight::get().writes(13 as u64))
+	}
+	// Storage: NftMarketPlaceModule Offers (r:1 w:1)
+	// Storage: NftMarketPlaceModule RentalListings (r:1 w:1)
+	// Storage: NftMarketPlaceModule Rentals (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
+	// Storage: NftModule Users (r:1 w:1)
+	// Storage: NftMarketPlaceModule Auctions (r:1 w:1)
+	// Storage: NftMarketPlaceModule NftSwaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule Swaps (r:1 w:1)
+	// Storage: NftMarketPlaceModule Loans (r:1 w:0)
+	// Storage: NftMarketPlaceModule OrderBook (r:0 w:1)
+	fn on_burn() -> Weight {
+		Weight::from_ref_time(90_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(11 as u64))
+			.saturating_add(RocksDbWeight::get().writes(11 as u64))
+	}
+}
@@ -18,7 +18,7 @@ use sp_runtime::{
 	traits::{
 		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -38,10 +38,12 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_nft::Call as NftCall;
+pub use pallet_nft_market_place::Call as NftMarketPlaceCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
 #[cfg(any(feature = "std", test))]
@@ -106,7 +108,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -279,15 +281,55 @@ impl pallet_sudo::Config for Runtime {
 /// Configure the pallet-template in pallets/template.
 
 
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = RuntimeCall;
+}
+
+parameter_types! {
+	/// Gateway `ipfs://` token urls are fetched through by the metadata off-chain worker.
+	pub const IpfsGateway: &'static [u8] = b"https://ipfs.io";
+	pub const NftMetadataPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+}
+
 impl pallet_nft::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Currency = Balances;
+	type OnTransfer = NftMarketPlaceModule;
+	type OnBurn = NftMarketPlaceModule;
+	type TokenLock = NftMarketPlaceModule;
+	type WeightInfo = pallet_nft::weights::SubstrateWeight<Runtime>;
+	type AuthorityId = pallet_nft::crypto::MetadataAuthId;
+	type IpfsGateway = IpfsGateway;
+	type MaxMetadataChecks = ConstU32<10>;
+	type MaxMetadataVerifiers = ConstU32<16>;
+	type VerifierOrigin = frame_system::EnsureRoot<AccountId>;
+	type UnsignedPriority = NftMetadataPriority;
+}
+
+parameter_types! {
+	pub const MarketPlacePalletId: PalletId = PalletId(*b"nft/mrkt");
 }
 
 impl pallet_nft_market_place::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Currency = Balances;
 	type UnixTime = Timestamp;
+	type BlockTime = ConstU64<MILLISECS_PER_BLOCK>;
+	type MaxExpiredPerBlock = ConstU32<50>;
+	type MaxBuyMany = ConstU32<20>;
+	type PalletId = MarketPlacePalletId;
+	type PauseOrigin = frame_system::EnsureRoot<AccountId>;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = pallet_nft_market_place::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -335,6 +377,15 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Storage migrations run on the next runtime upgrade, oldest first.
+pub type Migrations = (
+	pallet_nft::migrations::v1::MigrateToV1<Runtime>,
+	pallet_nft_market_place::migrations::v1::MigrateToV1<Runtime>,
+	pallet_nft_market_place::migrations::v2::MigrateToV2<Runtime>,
+	pallet_nft_market_place::migrations::v3::MigrateToV3<Runtime>,
+	pallet_nft_market_place::migrations::EndowFeeAccount<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -342,6 +393,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -508,6 +560,81 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_nft_runtime_api::NftApi<Block, AccountId, Balance> for Runtime {
+		fn owner_of(class_id: pallet_nft::Id, nft_id: pallet_nft::Id) -> Option<AccountId> {
+			NftModule::nfts(class_id, nft_id).map(|nft| nft.owner)
+		}
+
+		fn user_of(class_id: pallet_nft::Id, nft_id: pallet_nft::Id) -> Option<AccountId> {
+			NftModule::user_of(class_id, nft_id)
+		}
+
+		fn nft_info(
+			class_id: pallet_nft::Id,
+			nft_id: pallet_nft::Id,
+		) -> Option<pallet_nft::NftOf<Runtime>> {
+			NftModule::nfts(class_id, nft_id)
+		}
+
+		fn class_info(class_id: pallet_nft::Id) -> Option<pallet_nft::ClassOf<Runtime>> {
+			NftModule::classes(class_id)
+		}
+
+		fn tokens_of_owner(
+			account: AccountId,
+			class_id: pallet_nft::Id,
+			cursor: Option<pallet_nft::Id>,
+			limit: u32,
+		) -> Vec<(pallet_nft::Id, pallet_nft::NftOf<Runtime>)> {
+			NftModule::tokens_of_owner(account, class_id, cursor, limit)
+		}
+
+		fn listing_of(
+			class_id: pallet_nft::Id,
+			nft_id: pallet_nft::Id,
+		) -> Option<pallet_nft_market_place::OfferOf<Runtime>> {
+			NftMarketPlaceModule::offers(class_id, nft_id)
+		}
+
+		fn auction_of(
+			class_id: pallet_nft::Id,
+			nft_id: pallet_nft::Id,
+		) -> Option<pallet_nft_market_place::AuctionOf<Runtime>> {
+			NftMarketPlaceModule::auctions(class_id, nft_id)
+		}
+
+		fn verify_content(
+			class_id: pallet_nft::Id,
+			nft_id: pallet_nft::Id,
+			kind: pallet_nft::ContentKind,
+			data: Vec<u8>,
+		) -> Option<bool> {
+			NftModule::verify_content(class_id, nft_id, kind, &data)
+		}
+	}
+
+	impl pallet_nft_market_place_runtime_api::MarketplaceApi<Block, Balance> for Runtime {
+		fn floor_price(class_id: pallet_nft::Id) -> Option<Balance> {
+			NftMarketPlaceModule::floor_price(class_id)
+		}
+
+		fn listings(
+			class_id: pallet_nft::Id,
+			limit: u32,
+			cursor: Option<(Balance, pallet_nft::Id)>,
+		) -> Vec<(Balance, pallet_nft::Id)> {
+			NftMarketPlaceModule::listings(class_id, limit, cursor)
+		}
+
+		fn block_to_unix_ms(block: u64) -> u64 {
+			NftMarketPlaceModule::block_to_unix_ms(block)
+		}
+
+		fn unix_ms_to_block(time: u64) -> u64 {
+			NftMarketPlaceModule::unix_ms_to_block(time)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (